@@ -1,34 +1,110 @@
 //! contains the configuration for the execution
 
-use chrono::{NaiveDate, Weekday};
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+mod validate;
+pub use validate::{Diagnostic, Position, Severity, Span, has_errors, validate, validate_file};
 
 /// configuration root
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     pub dates: Dates,
     pub places: Places,
     pub group: Vec<Group>,
     pub rules: Rules,
     pub extra_task: Option<Vec<ExtraTask>>,
+    /// assignments agreed before the schedule is generated
+    #[serde(default)]
+    pub fixed: Vec<Fixed>,
+    /// previous schedules (csv) or history files, relative to the config file
+    #[serde(default)]
+    pub history: Vec<String>,
+    /// weights of the fairness objective minimised by the optimisation pass
+    #[serde(default)]
+    pub objective: Objective,
+}
+
+/// weight of each statistic in the fairness objective, 0.0 ignores a statistic
+///
+/// every statistic contributes the squared deviation of each person from the mean of all people
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Objective {
+    /// total number of services
+    pub total: f64,
+    /// number of services on each weekday
+    pub weekday: f64,
+    /// number of services at another place than the own one
+    pub different_place: f64,
+}
+
+impl Default for Objective {
+    fn default() -> Self {
+        Self {
+            total: 1.0,
+            weekday: 1.0,
+            different_place: 1.0,
+        }
+    }
+}
+
+/// a pinned assignment, the generator has to assign `person` to `place` on `date`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Fixed {
+    pub date: NaiveDate,
+    pub place: String,
+    /// full name ("Emily Smith") or a member name that is unique across all groups ("Emily")
+    pub person: String,
 }
 
 /// an additional task to be distributed across eligible group members
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct ExtraTask {
     pub name: String,
     pub groups: Vec<String>,
 }
 
 /// the schedule can create tasks per day per place
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct Places {
     pub places: Vec<String>,
+    /// number of people needed per day at a place, places not listed need one person
+    #[serde(default)]
+    pub headcount: HashMap<String, usize>,
+    /// number of people needed at a place on specific weekdays, overrides `headcount`
+    #[serde(default)]
+    pub weekday_headcount: HashMap<String, HashMap<Weekday, usize>>,
+    /// working hours per place, used by the calendar export, places not listed are all-day
+    #[serde(default)]
+    pub shifts: HashMap<String, Shift>,
+}
+
+/// working hours of a place, a shift that ends before it starts ends on the next day
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Shift {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Places {
+    /// number of people needed at the given place on the given date
+    pub fn headcount(&self, place: &str, date: NaiveDate) -> usize {
+        self.weekday_headcount
+            .get(place)
+            .and_then(|per_weekday| per_weekday.get(&date.weekday()))
+            .or_else(|| self.headcount.get(place))
+            .copied()
+            .unwrap_or(1)
+    }
 }
 
 /// date restrictions for schedule
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Dates {
     /// first day of schedule
     pub from: NaiveDate,
@@ -38,12 +114,64 @@ pub struct Dates {
     pub exceptions: Vec<NaiveDate>,
     /// schedule work only on these weekdays
     pub weekdays: Vec<Weekday>,
+    /// calendar files whose all-day and multi-day events are added to `exceptions`
+    #[serde(default)]
+    pub calendars: Vec<CalendarFile>,
+}
+
+impl Dates {
+    /// the first and the last day of the schedule
+    pub fn range(&self) -> DateRange {
+        DateRange {
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+/// a local iCalendar (.ics) file, e.g. school holidays, public holidays or a personal calendar
+///
+/// all-day events and events of 24 hours or more count, shorter ones are ignored
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct CalendarFile {
+    /// path of the file, relative to the config file
+    pub path: String,
+    /// only events whose SUMMARY contains one of these texts, ignoring case
+    #[serde(default)]
+    pub summary: Vec<String>,
+    /// only events with one of these CATEGORIES, ignoring case
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+impl CalendarFile {
+    /// the days of the matching events, recurring events within `window`, `dir` is the
+    /// directory of the config file
+    pub fn load(&self, dir: &Path, window: &DateRange) -> Result<Vec<DateRange>, Box<dyn Error>> {
+        let (path, content) = self.read(dir)?;
+        crate::ics::calendar_ranges(&content, self, window)
+            .map_err(|e| format!("could not parse calendar {}: {e}", path.display()).into())
+    }
+
+    /// the summaries of the matching events whose recurrence only counts with its first date
+    pub fn unsupported_recurrences(&self, dir: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+        let (path, content) = self.read(dir)?;
+        crate::ics::unsupported_recurrences(&content, self)
+            .map_err(|e| format!("could not parse calendar {}: {e}", path.display()).into())
+    }
+
+    fn read(&self, dir: &Path) -> Result<(PathBuf, String), Box<dyn Error>> {
+        let path = dir.join(&self.path);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("could not read calendar {}: {e}", path.display()))?;
+        Ok((path, content))
+    }
 }
 
 /// list of people to assign work to
 /// several people can be assigned to a group
 /// work for people within one group is spreat evenly across the calendar
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Group {
     /// name of group (for example family name or task force)
     pub name: String,
@@ -51,27 +179,114 @@ pub struct Group {
     pub place: String,
     /// list of members for this group
     pub members: Vec<Member>,
+    /// dates on which no member of this group can be scheduled
+    #[serde(default)]
+    pub unavailable: Unavailable,
 }
 
 /// member of a group
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Member {
     /// name of this member
     pub name: String,
+    /// dates on which this member can not be scheduled
+    #[serde(default)]
+    pub unavailable: Unavailable,
+}
+
+/// dates on which a member (or a whole group) is not available, e.g. holidays
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Unavailable {
+    /// single dates
+    #[serde(default)]
+    pub dates: Vec<NaiveDate>,
+    /// date ranges, both ends are included
+    #[serde(default)]
+    pub ranges: Vec<DateRange>,
+    /// weekdays that are never possible
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    /// absence calendars, their events are added to `ranges` when the config is loaded
+    #[serde(default)]
+    pub calendars: Vec<CalendarFile>,
+}
+
+/// a range of dates, `from` and `to` are both included
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl Unavailable {
+    /// true if the given date is blocked by any date, range or weekday
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.dates.contains(&date)
+            || self.weekdays.contains(&date.weekday())
+            || self
+                .ranges
+                .iter()
+                .any(|range| range.from <= date && date <= range.to)
+    }
+
+    /// add all entries of another set of unavailable dates
+    pub fn extend(&mut self, other: &Unavailable) {
+        self.dates.extend(other.dates.iter().copied());
+        self.ranges.extend(other.ranges.iter().copied());
+        self.weekdays.extend(other.weekdays.iter().copied());
+        self.calendars.extend(other.calendars.iter().cloned());
+    }
+
+    /// add the events of the calendar files to `ranges`, recurring events within `window`
+    pub fn load_calendars(&mut self, dir: &Path, window: &DateRange) -> Result<(), Box<dyn Error>> {
+        for calendar in &self.calendars {
+            self.ranges.extend(calendar.load(dir, window)?);
+        }
+        Ok(())
+    }
 }
 
 /// set of rules to apply when creating the schedule
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct Rules {
     /// sort member by these rules to find best match for next task
     pub sort: Vec<Rule>,
     /// filter member according to these rules for next task
     pub filter: Vec<Rule>,
+    /// seed for the random order of people, the same seed and config always create the same schedule
+    pub seed: Option<u64>,
+    /// how the sort rules are combined
+    #[serde(default)]
+    pub mode: SortMode,
+    /// weight per sort rule in `SortMode::Weighted`, rules without weight count 1.0
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
 }
 
-/// currently implemented rules
-#[derive(Deserialize, Debug, PartialEq)]
+impl Rules {
+    /// weight of a sort rule in `SortMode::Weighted`
+    pub fn weight(&self, rule: &Rule) -> f64 {
+        self.weights.get(rule.name()).copied().unwrap_or(1.0)
+    }
+}
+
+/// how the sort rules pick the best candidate
+#[derive(Deserialize, Debug, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "camelCase")]
+pub enum SortMode {
+    /// compare rule by rule, a later rule only decides if all earlier rules are equal
+    #[default]
+    Lexicographic,
+    /// rank by the weighted sum of the normalized penalties of all rules
+    Weighted,
+}
+
+/// currently implemented rules
+///
+/// names that are not built in are kept as `Custom`, they have to be registered
+/// in the `RuleRegistry` that is used to create the schedule
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(from = "String")]
 pub enum Rule {
     FilterSamePlace,                 // assign people only to their own place
     FilterDifferentPlaceCap,         // cap how many different-place assignments any one person gets
@@ -81,15 +296,90 @@ pub enum Rule {
     SortByMaxDistanceInGroup, // prefer people where a person of the same group worked the longest time ago
     SortByOwnPlace,           // prefer people within the same place
     SortByDifferentPlaceServices, // prefer people who were assigned to a different place less
+    Custom(String),           // a rule registered by a library user
+}
+
+impl Rule {
+    /// all built-in rules
+    pub const BUILTIN: [Rule; 8] = [
+        Rule::FilterSamePlace,
+        Rule::FilterDifferentPlaceCap,
+        Rule::SortByLeastServices,
+        Rule::SortByLessServicesAtSameWeekday,
+        Rule::SortByLastService,
+        Rule::SortByMaxDistanceInGroup,
+        Rule::SortByOwnPlace,
+        Rule::SortByDifferentPlaceServices,
+    ];
+
+    /// name of the rule as used in the config
+    pub fn name(&self) -> &str {
+        match self {
+            Rule::FilterSamePlace => "filterSamePlace",
+            Rule::FilterDifferentPlaceCap => "filterDifferentPlaceCap",
+            Rule::SortByLeastServices => "sortByLeastServices",
+            Rule::SortByLessServicesAtSameWeekday => "sortByLessServicesAtSameWeekday",
+            Rule::SortByLastService => "sortByLastService",
+            Rule::SortByMaxDistanceInGroup => "sortByMaxDistanceInGroup",
+            Rule::SortByOwnPlace => "sortByOwnPlace",
+            Rule::SortByDifferentPlaceServices => "sortByDifferentPlaceServices",
+            Rule::Custom(name) => name,
+        }
+    }
+}
+
+impl From<String> for Rule {
+    fn from(name: String) -> Self {
+        Rule::BUILTIN
+            .into_iter()
+            .find(|rule| rule.name() == name)
+            .unwrap_or(Rule::Custom(name))
+    }
 }
 
 /// load Config from a file
 pub fn load_config(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
     let content = fs::read_to_string(path)?;
-    let config: Config = toml::from_str(&content)?;
+    let mut config: Config = toml::from_str(&content)?;
+    config.load_calendars(Path::new(path).parent().unwrap_or(Path::new("")))?;
     Ok(config)
 }
 
+/// the config of `test/schedule.toml`, the tests of several modules start from it
+#[cfg(test)]
+pub(crate) fn test_config() -> Config {
+    load_config(concat!(env!("CARGO_MANIFEST_DIR"), "/test/schedule.toml")).unwrap()
+}
+
+impl Config {
+    /// expand the calendar files of `dates` into exceptions and those of groups and members into
+    /// unavailable ranges, paths are relative to `dir`
+    ///
+    /// only exceptions between `dates.from` and `dates.to` are added, recurring events are
+    /// expanded within these dates
+    pub fn load_calendars(&mut self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        let window = self.dates.range();
+        for calendar in &self.dates.calendars {
+            for range in calendar.load(dir, &window)? {
+                let from = range.from.max(self.dates.from);
+                let to = range.to.min(self.dates.to);
+                for date in from.iter_days().take_while(|date| *date <= to) {
+                    if !self.dates.exceptions.contains(&date) {
+                        self.dates.exceptions.push(date);
+                    }
+                }
+            }
+        }
+        for group in &mut self.group {
+            group.unavailable.load_calendars(dir, &window)?;
+            for member in &mut group.members {
+                member.unavailable.load_calendars(dir, &window)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::NaiveDate;
@@ -134,6 +424,166 @@ mod tests {
         assert_eq!(tasks[1].name, "🪟");
     }
 
+    #[test]
+    fn test_load_config_with_unavailable_dates() {
+        let toml = r#"
+            [dates]
+            from = "2025-01-01"
+            to = "2025-12-31"
+            exceptions = []
+            weekdays = ["Mon"]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+            unavailable = { weekdays = ["Fri"] }
+
+            [[group.members]]
+            name = "Alice"
+            unavailable = { dates = ["2025-03-03"], ranges = [{ from = "2025-08-01", to = "2025-08-15" }] }
+
+            [[group.members]]
+            name = "Bob"
+
+            [rules]
+            sort = []
+            filter = []
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let group = &config.group[0];
+        assert_eq!(group.unavailable.weekdays, vec![Weekday::Fri]);
+
+        let alice = &group.members[0].unavailable;
+        assert!(alice.contains(NaiveDate::from_ymd_opt(2025, 3, 3).unwrap()));
+        assert!(alice.contains(NaiveDate::from_ymd_opt(2025, 8, 1).unwrap()));
+        assert!(alice.contains(NaiveDate::from_ymd_opt(2025, 8, 15).unwrap()));
+        assert!(!alice.contains(NaiveDate::from_ymd_opt(2025, 8, 16).unwrap()));
+
+        assert_eq!(group.members[1].unavailable, Unavailable::default());
+    }
+
+    #[test]
+    fn test_load_config_with_headcount() {
+        let toml = r#"
+            [dates]
+            from = "2025-01-01"
+            to = "2025-12-31"
+            exceptions = []
+            weekdays = ["Mon", "Fri"]
+
+            [places]
+            places = ["Place A", "Place B", "Place C"]
+
+            [places.headcount]
+            "Place B" = 2
+
+            [places.weekday_headcount."Place B"]
+            Fri = 3
+
+            [places.weekday_headcount."Place C"]
+            Mon = 0
+
+            [places.shifts]
+            "Place A" = { start = "08:00", end = "12:30" }
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+            [[group.members]]
+            name = "Alice"
+
+            [rules]
+            sort = []
+            filter = []
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let friday = NaiveDate::from_ymd_opt(2025, 9, 5).unwrap();
+
+        assert_eq!(config.places.headcount("Place A", monday), 1);
+        assert_eq!(config.places.headcount("Place B", monday), 2);
+        assert_eq!(config.places.headcount("Place B", friday), 3);
+        assert_eq!(config.places.headcount("Place C", monday), 0);
+        assert_eq!(config.places.headcount("Place C", friday), 1);
+        assert_eq!(
+            config.places.shifts["Place A"],
+            Shift {
+                start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(12, 30, 0).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_config_expands_calendars() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("holidays.ics"),
+            "BEGIN:VCALENDAR\nVERSION:2.0\n\
+             BEGIN:VEVENT\nSUMMARY:Autumn holidays\nDTSTART;VALUE=DATE:20250929\n\
+             DTEND;VALUE=DATE:20251004\nEND:VEVENT\n\
+             BEGIN:VEVENT\nSUMMARY:Staff day\nDTSTART;VALUE=DATE:20250904\nEND:VEVENT\n\
+             BEGIN:VEVENT\nSUMMARY:Founders holidays\nDTSTART;VALUE=DATE:20190908\n\
+             RRULE:FREQ=YEARLY\nEND:VEVENT\n\
+             END:VCALENDAR\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("alice.ics"),
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Vacation\nDTSTART:20250915T000000\n\
+             DTEND:20250920T000000\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = ["2025-09-30"]
+            weekdays = ["Mon"]
+            calendars = [{ path = "holidays.ics", summary = ["holidays"] }]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+            [[group.members]]
+            name = "Alice"
+            unavailable = { calendars = [{ path = "alice.ics" }] }
+
+            [rules]
+            sort = []
+            filter = []
+        "#,
+        )
+        .unwrap();
+
+        let config = load_config(&config_path.to_string_lossy()).unwrap();
+
+        // the holidays are cut at the end of the schedule, the staff day is filtered out and the
+        // yearly holiday counts in this year
+        let date = |day| NaiveDate::from_ymd_opt(2025, 9, day).unwrap();
+        assert_eq!(config.dates.exceptions, vec![date(30), date(29), date(8)]);
+        assert_eq!(
+            config.group[0].members[0].unavailable.ranges,
+            vec![DateRange {
+                from: date(15),
+                to: date(19)
+            }]
+        );
+
+        fs::remove_file(dir.path().join("alice.ics")).unwrap();
+        let error = load_config(&config_path.to_string_lossy()).unwrap_err();
+        assert!(error.to_string().contains("could not read calendar"));
+    }
+
     #[test]
     fn test_load_config_without_extra_tasks_is_none() {
         let config = load_config("test/config.toml").expect("Failed to load config");
@@ -172,10 +622,12 @@ mod tests {
         assert_eq!(
             vec![
                 Member {
-                    name: "Alice".to_string()
+                    name: "Alice".to_string(),
+                    ..Default::default()
                 },
                 Member {
-                    name: "Bob".to_string()
+                    name: "Bob".to_string(),
+                    ..Default::default()
                 },
             ],
             config.group[0].members
@@ -184,7 +636,8 @@ mod tests {
         assert_eq!("Place B".to_string(), config.group[1].place);
         assert_eq!(
             vec![Member {
-                name: "Charlie".to_string()
+                name: "Charlie".to_string(),
+                ..Default::default()
             },],
             config.group[1].members
         );
@@ -200,5 +653,116 @@ mod tests {
             config.rules.sort
         );
         assert_eq!(vec![Rule::FilterSamePlace], config.rules.filter);
+        assert_eq!(None, config.rules.seed);
+        assert_eq!(SortMode::Lexicographic, config.rules.mode);
+        assert!(config.fixed.is_empty());
+        assert!(config.history.is_empty());
+    }
+
+    #[test]
+    fn test_load_config_with_fixed_assignments() {
+        let toml = r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = []
+            weekdays = ["Thu"]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Smith"
+            place = "Place A"
+            [[group.members]]
+            name = "Emily"
+
+            [rules]
+            sort = []
+            filter = []
+
+            [[fixed]]
+            date = "2025-09-04"
+            place = "Place A"
+            person = "Emily"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            vec![Fixed {
+                date: NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+                place: "Place A".to_string(),
+                person: "Emily".to_string(),
+            }],
+            config.fixed
+        );
+    }
+
+    #[test]
+    fn test_load_config_with_weighted_rules() {
+        let toml = r#"
+            [dates]
+            from = "2025-01-01"
+            to = "2025-12-31"
+            exceptions = []
+            weekdays = ["Mon"]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+            [[group.members]]
+            name = "Alice"
+
+            [rules]
+            mode = "weighted"
+            sort = ["sortByLeastServices", "sortByLastService"]
+            filter = []
+            weights = { sortByLeastServices = 3.0 }
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(SortMode::Weighted, config.rules.mode);
+        assert_eq!(3.0, config.rules.weight(&Rule::SortByLeastServices));
+        assert_eq!(1.0, config.rules.weight(&Rule::SortByLastService));
+    }
+
+    #[test]
+    fn test_rule_names() {
+        for rule in Rule::BUILTIN {
+            assert_eq!(Rule::from(rule.name().to_string()), rule);
+        }
+        assert_eq!(
+            Rule::from("sortByAge".to_string()),
+            Rule::Custom("sortByAge".to_string())
+        );
+        assert_eq!(Rule::Custom("sortByAge".to_string()).name(), "sortByAge");
+    }
+
+    #[test]
+    fn test_load_config_with_seed() {
+        let toml = r#"
+            [dates]
+            from = "2025-01-01"
+            to = "2025-12-31"
+            exceptions = []
+            weekdays = ["Mon"]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+            [[group.members]]
+            name = "Alice"
+
+            [rules]
+            sort = []
+            filter = []
+            seed = 42
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(Some(42), config.rules.seed);
     }
 }
@@ -0,0 +1,810 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use toml::de::DeTable;
+
+use super::{CalendarFile, Config, Rule, SortMode};
+use crate::dates::get_weekdays;
+use crate::schedule::{ScheduleError, create_people, fixed::fixed_by_date};
+
+/// how bad a problem of the config is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// the config can not be used or creates a wrong schedule
+    Error,
+    /// the config works, but most likely not as intended
+    Warning,
+}
+
+/// a position in the TOML source, line and column start at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// the part of the TOML source a problem refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// a problem found by `validate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// path of the value within the config, e.g. `group[1].place`
+    pub path: String,
+    pub message: String,
+    /// `None` if the value is not part of the source, e.g. a missing entry
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span {
+            write!(f, "{}:{}: ", span.start.line, span.start.column)?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        if self.path.is_empty() {
+            write!(f, "{severity}: {}", self.message)
+        } else {
+            write!(f, "{severity}: {}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// true if any of the diagnostics is an error
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+/// read a config file and check it with `validate`, the calendar files it refers to are read as well
+pub fn validate_file(path: &str) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    Ok(diagnose(&content, Some(dir)))
+}
+
+/// check the TOML source of a config for syntax errors and semantic mistakes
+///
+/// the diagnostics are sorted by their position in the source
+pub fn validate(source: &str) -> Vec<Diagnostic> {
+    diagnose(source, None)
+}
+
+/// `validate`, with the directory of the config file the calendar files are checked too
+fn diagnose(source: &str, dir: Option<&Path>) -> Vec<Diagnostic> {
+    let document = match DeTable::parse(source) {
+        Ok(document) => document.into_inner(),
+        Err(e) => return vec![toml_error(source, e.message(), e.span())],
+    };
+    let config: Config = match toml::from_str(source) {
+        Ok(config) => config,
+        Err(e) => return vec![toml_error(source, e.message(), e.span())],
+    };
+
+    let mut problems = check(&config);
+    if let Some(dir) = dir {
+        problems.extend(check_calendars(&config, dir));
+    }
+    let mut diagnostics: Vec<Diagnostic> = problems
+        .into_iter()
+        .map(|problem| {
+            let span = find_span(&document, &problem.path).map(|range| span(source, range));
+            Diagnostic {
+                severity: problem.severity,
+                path: path_to_string(&problem.path),
+                message: problem.message,
+                span,
+            }
+        })
+        .collect();
+
+    diagnostics.sort_by_key(|d| {
+        d.span
+            .map(|span| (span.start.line, span.start.column))
+            .unwrap_or((usize::MAX, 0))
+    });
+    diagnostics
+}
+
+fn toml_error(source: &str, message: &str, range: Option<Range<usize>>) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        path: String::new(),
+        message: message.trim().to_string(),
+        span: range.map(|range| span(source, range)),
+    }
+}
+
+/// one step of the path to a value in the config
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Field(String),
+    Index(usize),
+}
+
+fn field(name: &str) -> Key {
+    Key::Field(name.to_string())
+}
+
+struct Problem {
+    severity: Severity,
+    path: Vec<Key>,
+    message: String,
+}
+
+fn error(path: Vec<Key>, message: String) -> Problem {
+    Problem {
+        severity: Severity::Error,
+        path,
+        message,
+    }
+}
+
+fn warning(path: Vec<Key>, message: String) -> Problem {
+    Problem {
+        severity: Severity::Warning,
+        path,
+        message,
+    }
+}
+
+/// semantic checks of a config that deserialized without errors
+fn check(config: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let dates = &config.dates;
+
+    if dates.from > dates.to {
+        problems.push(error(
+            vec![field("dates"), field("to")],
+            format!("'to' ({}) is before 'from' ({})", dates.to, dates.from),
+        ));
+    }
+    if dates.weekdays.is_empty() {
+        problems.push(error(
+            vec![field("dates"), field("weekdays")],
+            "no weekdays given, the schedule would be empty".to_string(),
+        ));
+    }
+    for (i, exception) in dates.exceptions.iter().enumerate() {
+        if *exception < dates.from || *exception > dates.to {
+            problems.push(warning(
+                vec![field("dates"), field("exceptions"), Key::Index(i)],
+                format!(
+                    "{exception} is outside of {} to {} and has no effect",
+                    dates.from, dates.to
+                ),
+            ));
+        }
+    }
+
+    let places: HashSet<&str> = config.places.places.iter().map(String::as_str).collect();
+    let mut seen_places = HashSet::new();
+    for (i, place) in config.places.places.iter().enumerate() {
+        if !seen_places.insert(place) {
+            problems.push(error(
+                vec![field("places"), field("places"), Key::Index(i)],
+                format!("place '{place}' is listed more than once"),
+            ));
+        }
+    }
+    for place in config.places.headcount.keys() {
+        if !places.contains(place.as_str()) {
+            problems.push(warning(
+                vec![field("places"), field("headcount"), field(place)],
+                format!("unknown place '{place}'"),
+            ));
+        }
+    }
+    for place in config.places.weekday_headcount.keys() {
+        if !places.contains(place.as_str()) {
+            problems.push(warning(
+                vec![field("places"), field("weekday_headcount"), field(place)],
+                format!("unknown place '{place}'"),
+            ));
+        }
+    }
+    for place in config.places.shifts.keys() {
+        if !places.contains(place.as_str()) {
+            problems.push(warning(
+                vec![field("places"), field("shifts"), field(place)],
+                format!("unknown place '{place}'"),
+            ));
+        }
+    }
+
+    let mut group_names = HashSet::new();
+    for (i, group) in config.group.iter().enumerate() {
+        let path = |keys: &[Key]| {
+            let mut path = vec![field("group"), Key::Index(i)];
+            path.extend_from_slice(keys);
+            path
+        };
+
+        if !group_names.insert(&group.name) {
+            problems.push(error(
+                path(&[field("name")]),
+                format!("group '{}' is defined more than once", group.name),
+            ));
+        }
+        if !places.contains(group.place.as_str()) {
+            problems.push(error(
+                path(&[field("place")]),
+                format!("place '{}' is not listed in places.places", group.place),
+            ));
+        }
+        if group.members.is_empty() {
+            problems.push(warning(
+                path(&[]),
+                format!("group '{}' has no members", group.name),
+            ));
+        }
+
+        let mut member_names = HashSet::new();
+        for (j, member) in group.members.iter().enumerate() {
+            if !member_names.insert(&member.name) {
+                problems.push(error(
+                    path(&[field("members"), Key::Index(j), field("name")]),
+                    format!(
+                        "member '{}' is defined more than once in group '{}'",
+                        member.name, group.name
+                    ),
+                ));
+            }
+            for (k, range) in member.unavailable.ranges.iter().enumerate() {
+                if range.from > range.to {
+                    problems.push(warning(
+                        path(&[
+                            field("members"),
+                            Key::Index(j),
+                            field("unavailable"),
+                            field("ranges"),
+                            Key::Index(k),
+                        ]),
+                        format!("range {} to {} is empty", range.from, range.to),
+                    ));
+                }
+            }
+        }
+        for (k, range) in group.unavailable.ranges.iter().enumerate() {
+            if range.from > range.to {
+                problems.push(warning(
+                    path(&[field("unavailable"), field("ranges"), Key::Index(k)]),
+                    format!("range {} to {} is empty", range.from, range.to),
+                ));
+            }
+        }
+    }
+
+    if config.rules.filter.contains(&Rule::FilterSamePlace) {
+        for (i, place) in config.places.places.iter().enumerate() {
+            if !config.group.iter().any(|g| &g.place == place) {
+                problems.push(warning(
+                    vec![field("places"), field("places"), Key::Index(i)],
+                    format!("no group works at '{place}', filterSamePlace leaves it empty"),
+                ));
+            }
+        }
+    }
+
+    for (list, rules) in [
+        ("sort", &config.rules.sort),
+        ("filter", &config.rules.filter),
+    ] {
+        for (i, rule) in rules.iter().enumerate() {
+            // the CLI and the GUI never register custom rules, so these fail when scheduling
+            if let Rule::Custom(name) = rule {
+                let message = match closest_rule(name) {
+                    Some(builtin) => {
+                        format!("unknown rule '{name}', did you mean '{}'?", builtin.name())
+                    }
+                    None => format!(
+                        "unknown rule '{name}', custom rules have to be registered through the library"
+                    ),
+                };
+                problems.push(error(
+                    vec![field("rules"), field(list), Key::Index(i)],
+                    message,
+                ));
+            }
+        }
+    }
+
+    let mut weights: Vec<(&String, &f64)> = config.rules.weights.iter().collect();
+    weights.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, weight) in weights {
+        let path = vec![field("rules"), field("weights"), field(name)];
+        if !config.rules.sort.iter().any(|rule| rule.name() == name) {
+            problems.push(warning(
+                path,
+                format!("weight of '{name}' is unused, the rule is not listed in rules.sort"),
+            ));
+        } else if *weight < 0.0 || weight.is_nan() {
+            problems.push(error(
+                path,
+                format!("weight of '{name}' has to be zero or positive"),
+            ));
+        } else if config.rules.mode != SortMode::Weighted {
+            problems.push(warning(
+                path,
+                "weights are only used with mode = \"weighted\"".to_string(),
+            ));
+        }
+    }
+
+    for (name, weight) in [
+        ("total", config.objective.total),
+        ("weekday", config.objective.weekday),
+        ("different_place", config.objective.different_place),
+    ] {
+        if weight < 0.0 || weight.is_nan() {
+            problems.push(error(
+                vec![field("objective"), field(name)],
+                "weight has to be zero or positive".to_string(),
+            ));
+        }
+    }
+
+    for (i, task) in config.extra_task.iter().flatten().enumerate() {
+        for (j, name) in task.groups.iter().enumerate() {
+            if !config
+                .group
+                .iter()
+                .any(|g| &g.name == name || &g.place == name)
+            {
+                problems.push(warning(
+                    vec![
+                        field("extra_task"),
+                        Key::Index(i),
+                        field("groups"),
+                        Key::Index(j),
+                    ],
+                    format!("'{name}' matches no group name or place"),
+                ));
+            }
+        }
+    }
+
+    // conflicts of fixed assignments are only reported once the rest of the config is fine
+    if problems.iter().all(|p| p.severity == Severity::Warning) {
+        let schedule_dates = get_weekdays(&dates.from, &dates.to, &dates.weekdays);
+        if let Err(e) = fixed_by_date(config, &schedule_dates, &create_people(config)) {
+            let index = fixed_of(&e)
+                .and_then(|fixed| config.fixed.iter().position(|f| f == fixed))
+                .unwrap_or(0);
+            problems.push(error(
+                vec![field("fixed"), Key::Index(index)],
+                e.to_string(),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// the built-in rule with a name close to `name`, to point out typos
+fn closest_rule(name: &str) -> Option<Rule> {
+    Rule::BUILTIN
+        .into_iter()
+        .map(|rule| {
+            (
+                edit_distance(&name.to_lowercase(), &rule.name().to_lowercase()),
+                rule,
+            )
+        })
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, rule)| rule)
+}
+
+/// number of inserted, removed or replaced characters to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// calendar files that can not be read or have no matching event
+fn check_calendars(config: &Config, dir: &Path) -> Vec<Problem> {
+    let mut calendars: Vec<(Vec<Key>, &CalendarFile)> = Vec::new();
+    for (i, calendar) in config.dates.calendars.iter().enumerate() {
+        calendars.push((
+            vec![field("dates"), field("calendars"), Key::Index(i)],
+            calendar,
+        ));
+    }
+    for (i, group) in config.group.iter().enumerate() {
+        for (k, calendar) in group.unavailable.calendars.iter().enumerate() {
+            calendars.push((
+                vec![
+                    field("group"),
+                    Key::Index(i),
+                    field("unavailable"),
+                    field("calendars"),
+                    Key::Index(k),
+                ],
+                calendar,
+            ));
+        }
+        for (j, member) in group.members.iter().enumerate() {
+            for (k, calendar) in member.unavailable.calendars.iter().enumerate() {
+                calendars.push((
+                    vec![
+                        field("group"),
+                        Key::Index(i),
+                        field("members"),
+                        Key::Index(j),
+                        field("unavailable"),
+                        field("calendars"),
+                        Key::Index(k),
+                    ],
+                    calendar,
+                ));
+            }
+        }
+    }
+
+    let window = config.dates.range();
+    let mut problems = Vec::new();
+    for (mut path, calendar) in calendars {
+        match calendar.load(dir, &window) {
+            Err(e) => {
+                path.push(field("path"));
+                problems.push(error(path, e.to_string()));
+                continue;
+            }
+            Ok(ranges) if ranges.is_empty() => problems.push(warning(
+                path.clone(),
+                format!(
+                    "'{}' has no all-day or multi-day event that matches",
+                    calendar.path
+                ),
+            )),
+            Ok(_) => {}
+        }
+        for summary in calendar.unsupported_recurrences(dir).unwrap_or_default() {
+            problems.push(warning(
+                path.clone(),
+                format!(
+                    "the recurrence of '{summary}' in '{}' is not supported, only its first date counts",
+                    calendar.path
+                ),
+            ));
+        }
+    }
+    problems
+}
+
+fn fixed_of(error: &ScheduleError) -> Option<&super::Fixed> {
+    match error {
+        ScheduleError::UnknownPerson(fixed)
+        | ScheduleError::AmbiguousPerson(fixed)
+        | ScheduleError::UnknownPlace(fixed)
+        | ScheduleError::DateOutOfRange(fixed)
+        | ScheduleError::NotAScheduleDate(fixed)
+        | ScheduleError::Unavailable(fixed)
+        | ScheduleError::DoubleBooking(fixed)
+        | ScheduleError::TooManyForPlace(fixed) => Some(fixed),
+        ScheduleError::UnknownRule(_)
+        | ScheduleError::InvalidAbsence(_)
+        | ScheduleError::UnknownSlot(_) => None,
+    }
+}
+
+fn path_to_string(path: &[Key]) -> String {
+    let mut result = String::new();
+    for key in path {
+        match key {
+            Key::Field(name) => {
+                if !result.is_empty() {
+                    result.push('.');
+                }
+                result.push_str(name);
+            }
+            Key::Index(i) => result.push_str(&format!("[{i}]")),
+        }
+    }
+    result
+}
+
+/// span of the value at the given path, or of its closest parent that exists in the source
+fn find_span(document: &DeTable, path: &[Key]) -> Option<Range<usize>> {
+    let Some((Key::Field(first), rest)) = path.split_first() else {
+        return None;
+    };
+    let mut current = document.get(first.as_str())?;
+    for key in rest {
+        let next = match key {
+            Key::Field(name) => current.get_ref().get(name.as_str()),
+            Key::Index(i) => current.get_ref().get(*i),
+        };
+        match next {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+    Some(current.span())
+}
+
+fn span(source: &str, range: Range<usize>) -> Span {
+    Span {
+        start: position(source, range.start),
+        end: position(source, range.end),
+    }
+}
+
+fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"[dates]
+from = "2025-09-01"
+to = "2025-09-30"
+exceptions = ["2025-09-05"]
+weekdays = ["Thu", "Fri"]
+
+[places]
+places = ["Place A", "Place B"]
+
+[[extra_task]]
+name = "🪴"
+groups = ["Smith"]
+
+[[group]]
+name = "Smith"
+place = "Place A"
+
+[[group.members]]
+name = "Emily"
+
+[[group]]
+name = "Jones"
+place = "Place B"
+
+[[group.members]]
+name = "Olivia"
+
+[rules]
+sort = ["sortByLeastServices"]
+filter = []
+"#;
+
+    #[test]
+    fn valid_config_has_no_diagnostics() {
+        assert_eq!(validate(VALID), vec![]);
+        assert!(validate_file("test/schedule.toml").unwrap().is_empty());
+    }
+
+    #[test]
+    fn syntax_errors_have_a_position() {
+        let diagnostics = validate("[dates]\nfrom = \n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].span.unwrap().start.line, 2);
+    }
+
+    #[test]
+    fn type_errors_have_a_position() {
+        let source = VALID.replace("weekdays = [\"Thu\", \"Fri\"]", "weekdays = [\"Someday\"]");
+        let diagnostics = validate(&source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.unwrap().start.line, 5);
+    }
+
+    #[test]
+    fn reports_semantic_problems_with_positions() {
+        let source = VALID
+            .replace("to = \"2025-09-30\"", "to = \"2025-08-30\"")
+            .replace("place = \"Place B\"", "place = \"Place C\"")
+            .replace("groups = [\"Smith\"]", "groups = [\"Smith\", \"Nobody\"]")
+            .replace(
+                "name = \"Olivia\"",
+                "name = \"Olivia\"\n\n[[group.members]]\nname = \"Olivia\"",
+            );
+        let diagnostics = validate(&source);
+
+        let found: Vec<(Severity, &str, usize)> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.path.as_str(), d.span.unwrap().start.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Severity::Error, "dates.to", 3),
+                (Severity::Warning, "dates.exceptions[0]", 4),
+                (Severity::Warning, "extra_task[0].groups[1]", 12),
+                (Severity::Error, "group[1].place", 23),
+                (Severity::Error, "group[1].members[1].name", 29),
+            ]
+        );
+        assert!(has_errors(&diagnostics));
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "23:9: error: group[1].place: place 'Place C' is not listed in places.places"
+        );
+    }
+
+    #[test]
+    fn reports_empty_weekdays_and_duplicate_groups() {
+        let source = VALID
+            .replace("weekdays = [\"Thu\", \"Fri\"]", "weekdays = []")
+            .replace("name = \"Jones\"", "name = \"Smith\"");
+        let diagnostics = validate(&source);
+
+        let paths: Vec<&str> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["dates.weekdays", "group[1].name"]);
+    }
+
+    #[test]
+    fn reports_unknown_rules() {
+        let source = VALID.replace(
+            "sort = [\"sortByLeastServices\"]",
+            "sort = [\"sortByLeastServices\", \"sortByAge\"]",
+        );
+        let diagnostics = validate(&source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].path, "rules.sort[1]");
+        assert_eq!(
+            diagnostics[0].message,
+            "unknown rule 'sortByAge', custom rules have to be registered through the library"
+        );
+    }
+
+    #[test]
+    fn suggests_the_builtin_rule_for_a_typo() {
+        let source = VALID.replace(
+            "sort = [\"sortByLeastServices\"]",
+            "sort = [\"sortByLeastService\"]",
+        );
+        let diagnostics = validate(&source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(
+            diagnostics[0].message,
+            "unknown rule 'sortByLeastService', did you mean 'sortByLeastServices'?"
+        );
+        assert_eq!(closest_rule("FilterSamePlace"), Some(Rule::FilterSamePlace));
+        assert_eq!(closest_rule("sortByAge"), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn reports_unused_and_negative_weights() {
+        let source = VALID.replace(
+            "filter = []",
+            "filter = []\nmode = \"weighted\"\nweights = { sortByLeastServices = -1.0, sortByLastService = 2.0 }",
+        );
+        let diagnostics = validate(&source);
+
+        let found: Vec<(Severity, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.path.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Severity::Error, "rules.weights.sortByLeastServices"),
+                (Severity::Warning, "rules.weights.sortByLastService"),
+            ]
+        );
+
+        let objective = format!("{VALID}\n[objective]\nweekday = -2.0\n");
+        let diagnostics = validate(&objective);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "objective.weekday");
+
+        let lexicographic = VALID.replace(
+            "filter = []",
+            "filter = []\nweights = { sortByLeastServices = 2.0 }",
+        );
+        let diagnostics = validate(&lexicographic);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("weighted"));
+    }
+
+    #[test]
+    fn validate_file_checks_calendars() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("holidays.ics"),
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Holiday\nDTSTART;VALUE=DATE:20250911\n\
+             END:VEVENT\nBEGIN:VEVENT\nSUMMARY:Team day\nDTSTART;VALUE=DATE:20250901\n\
+             RRULE:FREQ=MONTHLY;BYDAY=1MO\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        let source = VALID
+            .replace(
+                "weekdays = [\"Thu\", \"Fri\"]",
+                "weekdays = [\"Thu\", \"Fri\"]\n\
+                 calendars = [{ path = \"holidays.ics\" }, { path = \"holidays.ics\", summary = [\"exam\"] }]",
+            )
+            .replace(
+                "name = \"Olivia\"",
+                "name = \"Olivia\"\nunavailable = { calendars = [{ path = \"olivia.ics\" }] }",
+            );
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, &source).unwrap();
+
+        // `validate` alone does not know where the calendars are
+        assert!(validate(&source).is_empty());
+
+        let diagnostics = validate_file(&config_path.to_string_lossy()).unwrap();
+        let found: Vec<(Severity, &str, usize)> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.path.as_str(), d.span.unwrap().start.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Severity::Warning, "dates.calendars[0]", 6),
+                (Severity::Warning, "dates.calendars[1]", 6),
+                (
+                    Severity::Error,
+                    "group[1].members[0].unavailable.calendars[0].path",
+                    28
+                ),
+            ]
+        );
+        assert!(diagnostics[0].message.contains("'Team day'"));
+        assert!(diagnostics[2].message.contains("could not read calendar"));
+    }
+
+    #[test]
+    fn reports_conflicting_fixed_assignments() {
+        let source = format!(
+            "{VALID}\n[[fixed]]\ndate = \"2025-09-04\"\nplace = \"Place A\"\nperson = \"Emily\"\n\n[[fixed]]\ndate = \"2025-09-04\"\nplace = \"Place B\"\nperson = \"Nobody\"\n"
+        );
+        let diagnostics = validate(&source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "fixed[1]");
+        assert!(diagnostics[0].message.contains("unknown person"));
+    }
+
+    #[test]
+    fn warns_about_places_without_group_when_filtering_same_place() {
+        let source = VALID
+            .replace("place = \"Place B\"", "place = \"Place A\"")
+            .replace("filter = []", "filter = [\"filterSamePlace\"]");
+        let diagnostics = validate(&source);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].path, "places.places[1]");
+    }
+}
@@ -1,24 +1,22 @@
-//! helper function to store the generated assignments into a csv String
+//! helper functions to store the generated assignments and their summary into a csv String and to
+//! read the assignments back
+
+use std::error::Error;
 
 use chrono::NaiveDate;
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    error::Error,
-};
 
-use crate::schedule::Assignment;
+use crate::config::Config;
+use crate::extra_tasks::strip_extra_tasks;
+use crate::schedule::{Assignment, slot_column, slot_column_label, slot_columns, slots_by_date};
+use crate::stats::person_summaries;
 
 /// convert assignments to csv String
-pub fn assignments_to_csv(assignments: &Vec<Assignment>) -> Result<String, Box<dyn Error>> {
-    let mut places: BTreeSet<String> = BTreeSet::new();
-    let mut data: BTreeMap<NaiveDate, BTreeMap<String, String>> = BTreeMap::new();
-
-    for a in assignments {
-        places.insert(a.place.clone());
-        data.entry(a.date)
-            .or_default()
-            .insert(a.place.clone(), a.person.clone());
-    }
+///
+/// places with more than one person per day get one column per slot, missing and unfilled slots
+/// stay empty
+pub fn assignments_to_csv(assignments: &[Assignment]) -> Result<String, Box<dyn Error>> {
+    let columns = slot_columns(assignments);
+    let data = slots_by_date(assignments);
 
     let mut wtr = csv::WriterBuilder::new()
         .delimiter(b',')
@@ -29,19 +27,22 @@ pub fn assignments_to_csv(assignments: &Vec<Assignment>) -> Result<String, Box<d
         .from_writer(vec![]);
 
     let mut header = vec![];
-    header.push("date");
-    for g in &places {
-        header.push(g);
+    header.push("date".to_string());
+    for (place, slot) in &columns {
+        header.push(slot_column_label(place, *slot));
     }
     wtr.write_record(header)?;
 
     for (date, action) in data {
         let mut row = vec![];
         row.push(date.to_string());
-        for g in &places {
-            if let Some(person) = action.get(g) {
-                row.push(person.clone());
-            }
+        for (place, slot) in &columns {
+            let person = action
+                .get(place)
+                .and_then(|persons| persons.get(*slot))
+                .cloned()
+                .unwrap_or_default();
+            row.push(person);
         }
         wtr.write_record(row)?;
     }
@@ -49,10 +50,123 @@ pub fn assignments_to_csv(assignments: &Vec<Assignment>) -> Result<String, Box<d
     Ok(String::from_utf8(wtr.into_inner()?)?)
 }
 
+/// the summary block written after the schedule: the seed line and a table with one row per
+/// person of the config
+///
+/// the columns are fixed by the config: person, group, home_place, total, every weekday, every
+/// place, different_place and every extra task
+pub fn summary_to_csv(
+    config: &Config,
+    assignments: &[Assignment],
+    seed: Option<u64>,
+) -> Result<String, Box<dyn Error>> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b',')
+        .quote_style(csv::QuoteStyle::Necessary)
+        .quote(b'"')
+        .double_quote(false)
+        .escape(b'\\')
+        .from_writer(vec![]);
+
+    let mut header: Vec<String> = ["person", "group", "home_place", "total"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    header.extend(config.dates.weekdays.iter().map(|day| day.to_string()));
+    header.extend(config.places.places.iter().cloned());
+    header.push("different_place".to_string());
+    header.extend(
+        config
+            .extra_task
+            .iter()
+            .flatten()
+            .map(|task| task.name.clone()),
+    );
+    wtr.write_record(header)?;
+
+    for summary in person_summaries(config, assignments) {
+        let mut row = vec![
+            summary.person,
+            summary.group,
+            summary.place,
+            summary.total.to_string(),
+        ];
+        row.extend(summary.weekdays.iter().map(|(_, n)| n.to_string()));
+        row.extend(summary.places.iter().map(|(_, n)| n.to_string()));
+        row.push(summary.different_place.to_string());
+        row.extend(summary.extra_tasks.iter().map(|(_, n)| n.to_string()));
+        wtr.write_record(row)?;
+    }
+
+    let table = String::from_utf8(wtr.into_inner()?)?;
+    Ok(match seed {
+        Some(seed) => format!("seed: {seed}\n\n{table}"),
+        None => table,
+    })
+}
+
+/// read a schedule csv written by `assignments_to_csv` back into assignments
+///
+/// everything after the first empty line, like the summary of a saved schedule, is ignored.
+/// Icons of extra tasks are removed from `base_person`. An empty cell is an unfilled slot if the
+/// place needs that many people on the date, otherwise the place has no such slot that day
+pub fn csv_to_assignments(
+    content: &str,
+    config: &Config,
+) -> Result<Vec<Assignment>, Box<dyn Error>> {
+    let table = content
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .double_quote(false)
+        .escape(Some(b'\\'))
+        .from_reader(table.as_bytes());
+
+    let header: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|column| column.trim().to_string())
+        .collect();
+    if header.first().map(String::as_str) != Some("date") {
+        return Err(format!("expected a date column first, got '{}'", header.join(",")).into());
+    }
+
+    let mut assignments = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let date = record.get(0).unwrap_or_default().trim();
+        let date: NaiveDate = date
+            .parse()
+            .map_err(|e| format!("invalid date '{date}': {e}"))?;
+
+        for (column, person) in header.iter().zip(record.iter()).skip(1) {
+            let (place, slot) = slot_column(column);
+            let person = person.trim();
+            if !person.is_empty() {
+                assignments.push(Assignment {
+                    date,
+                    place: place.to_string(),
+                    person: person.to_string(),
+                    base_person: strip_extra_tasks(person, config),
+                });
+            } else if slot < config.places.headcount(place, date) {
+                assignments.push(Assignment::unfilled(date, place.to_string()));
+            }
+        }
+    }
+
+    Ok(assignments)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::NaiveDate;
+    use crate::config::test_config;
+    use std::collections::HashMap;
 
     #[test]
     fn test_assignments_to_csv() {
@@ -93,4 +207,169 @@ date,PlaceA,PlaceB
 
         assert_eq!(expected, csv);
     }
+
+    #[test]
+    fn test_assignments_to_csv_with_multiple_slots() {
+        let assignment = |day: u32, place: &str, person: &str| Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        };
+        let assignments = vec![
+            assignment(6, "PlaceA", "Alice"),
+            assignment(6, "PlaceB", "Bob"),
+            assignment(6, "PlaceB", "Charlie"),
+            assignment(7, "PlaceA", "Charlie"),
+            assignment(7, "PlaceB", "Alice"),
+        ];
+
+        let csv = assignments_to_csv(&assignments).unwrap();
+
+        let expected = "\
+date,PlaceA,PlaceB,PlaceB #2
+2025-09-06,Alice,Bob,Charlie
+2025-09-07,Charlie,Alice,
+";
+
+        assert_eq!(expected, csv);
+    }
+
+    #[test]
+    fn test_assignments_to_csv_with_unfilled_slot() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 6).unwrap();
+        let assignments = vec![
+            Assignment {
+                date,
+                place: "PlaceA".to_string(),
+                person: "Alice".to_string(),
+                base_person: "Alice".to_string(),
+            },
+            Assignment::unfilled(date, "PlaceB".to_string()),
+        ];
+
+        let csv = assignments_to_csv(&assignments).unwrap();
+
+        assert_eq!(csv, "date,PlaceA,PlaceB\n2025-09-06,Alice,\n");
+    }
+
+    /// Transport needs two people on Fridays
+    fn config() -> Config {
+        let mut config = test_config();
+        config.places.headcount = HashMap::from([("Transport".to_string(), 2)]);
+        config.places.weekday_headcount = HashMap::from([(
+            "Transport".to_string(),
+            HashMap::from([(chrono::Weekday::Thu, 1)]),
+        )]);
+        config
+    }
+
+    #[test]
+    fn test_csv_to_assignments_reads_back_a_saved_schedule() {
+        let content = "\
+date,Office,Transport,Transport #2
+2025-09-04,Emily Smith,Olivia Jones,
+2025-09-12,Emily Smith 🪴,,Daniel Brown
+
+seed: 42
+person,total
+Emily Smith,2
+";
+        let assignments = csv_to_assignments(content, &config()).unwrap();
+
+        let cells: Vec<(u32, &str, &str, &str)> = assignments
+            .iter()
+            .map(|a| {
+                (
+                    chrono::Datelike::day(&a.date),
+                    a.place.as_str(),
+                    a.person.as_str(),
+                    a.base_person.as_str(),
+                )
+            })
+            .collect();
+        // Thursday needs one person at Transport, Friday two and one of them is missing
+        assert_eq!(
+            cells,
+            vec![
+                (4, "Office", "Emily Smith", "Emily Smith"),
+                (4, "Transport", "Olivia Jones", "Olivia Jones"),
+                (12, "Office", "Emily Smith 🪴", "Emily Smith"),
+                (12, "Transport", "", ""),
+                (12, "Transport", "Daniel Brown", "Daniel Brown"),
+            ]
+        );
+        assert!(assignments[3].is_unfilled());
+
+        // writing the assignments again gives the same schedule block
+        let csv = assignments_to_csv(&assignments).unwrap();
+        assert_eq!(
+            csv,
+            content.split("\n\n").next().unwrap().to_string() + "\n"
+        );
+    }
+
+    #[test]
+    fn test_summary_to_csv() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 9, day).unwrap();
+        let assignments = vec![
+            Assignment {
+                date: date(4),
+                place: "Transport".to_string(),
+                person: "Emily Smith 🪴".to_string(),
+                base_person: "Emily Smith".to_string(),
+            },
+            Assignment {
+                date: date(12),
+                place: "Office".to_string(),
+                person: "Emily Smith".to_string(),
+                base_person: "Emily Smith".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            summary_to_csv(&config(), &assignments, Some(42)).unwrap(),
+            "\
+seed: 42
+
+person,group,home_place,total,Thu,Fri,Office,Transport,different_place,🪴,🪟
+Emily Smith,Smith,Office,2,1,1,1,1,1,1,0
+William Smith,Smith,Office,0,0,0,0,0,0,0,0
+Olivia Jones,Jones,Transport,0,0,0,0,0,0,0,0
+Daniel Brown,Brown,Transport,0,0,0,0,0,0,0,0
+Sophie Brown,Brown,Transport,0,0,0,0,0,0,0,0
+"
+        );
+        assert!(
+            summary_to_csv(&config(), &[], None)
+                .unwrap()
+                .starts_with("person,")
+        );
+    }
+
+    #[test]
+    fn test_saved_schedule_reads_back_without_summary() {
+        let assignments = vec![Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+            place: "Office".to_string(),
+            person: "Emily Smith".to_string(),
+            base_person: "Emily Smith".to_string(),
+        }];
+        let content = format!(
+            "{}\n{}",
+            assignments_to_csv(&assignments).unwrap(),
+            summary_to_csv(&config(), &assignments, Some(7)).unwrap()
+        );
+
+        let read = csv_to_assignments(&content, &config()).unwrap();
+
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].base_person, "Emily Smith");
+    }
+
+    #[test]
+    fn test_csv_to_assignments_reports_invalid_content() {
+        assert!(csv_to_assignments("person,date\nEmily Smith,2025-09-04\n", &config()).is_err());
+        assert!(csv_to_assignments("date,Office\nyesterday,Emily Smith\n", &config()).is_err());
+    }
 }
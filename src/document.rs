@@ -0,0 +1,239 @@
+//! a schedule as versioned JSON document for other tools
+//!
+//! unlike the csv, the document keeps the person without extra tasks, the extra tasks and the
+//! statistics of each person as separate fields, nothing has to be parsed out of a text. `version`
+//! grows with incompatible changes
+
+use std::error::Error;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::extra_tasks::extra_tasks_of;
+use crate::schedule::Assignment;
+use crate::stats::{PersonSummary, person_summaries};
+
+/// the version of the document format written by this build
+pub const DOCUMENT_VERSION: u32 = 1;
+
+/// a schedule with everything needed to use it without the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleDocument {
+    pub version: u32,
+    /// `config_digest` of the config file the schedule belongs to
+    pub config_digest: String,
+    /// the seed that created the schedule, none for schedules of unknown origin
+    pub seed: Option<u64>,
+    /// one entry per slot, an unfilled slot has an empty person
+    pub assignments: Vec<ScheduledAssignment>,
+    /// the statistics of every person of the config
+    pub people: Vec<PersonSummary>,
+}
+
+/// an assignment with its extra tasks as list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledAssignment {
+    pub date: NaiveDate,
+    pub place: String,
+    /// full name of the person without extra tasks, empty for an unfilled slot
+    pub base_person: String,
+    #[serde(default)]
+    pub extra_tasks: Vec<String>,
+}
+
+impl ScheduledAssignment {
+    pub fn new(assignment: &Assignment) -> Self {
+        Self {
+            date: assignment.date,
+            place: assignment.place.clone(),
+            base_person: assignment.base_person.clone(),
+            extra_tasks: extra_tasks_of(assignment),
+        }
+    }
+
+    /// the assignment with the extra tasks appended to the person
+    pub fn assignment(&self) -> Assignment {
+        if self.base_person.is_empty() {
+            return Assignment::unfilled(self.date, self.place.clone());
+        }
+        Assignment {
+            date: self.date,
+            place: self.place.clone(),
+            person: std::iter::once(self.base_person.as_str())
+                .chain(self.extra_tasks.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+            base_person: self.base_person.clone(),
+        }
+    }
+}
+
+impl ScheduleDocument {
+    /// the document of a schedule, `config_source` is the content of the config file
+    pub fn new(
+        config_source: &str,
+        config: &Config,
+        seed: Option<u64>,
+        assignments: &[Assignment],
+    ) -> Self {
+        Self {
+            version: DOCUMENT_VERSION,
+            config_digest: config_digest(config_source),
+            seed,
+            assignments: assignments.iter().map(ScheduledAssignment::new).collect(),
+            people: person_summaries(config, assignments),
+        }
+    }
+
+    /// the assignments of the document, with the extra tasks appended to the person
+    pub fn assignments(&self) -> Vec<Assignment> {
+        self.assignments
+            .iter()
+            .map(ScheduledAssignment::assignment)
+            .collect()
+    }
+
+    /// true if the document was written for a config file with this content
+    pub fn matches_config(&self, config_source: &str) -> bool {
+        self.config_digest == config_digest(config_source)
+    }
+
+    /// the document as pretty printed JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// read a document, documents of a newer version are rejected
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let document: Self = serde_json::from_str(json)?;
+        if document.version > DOCUMENT_VERSION {
+            return Err(format!(
+                "schedule document version {} is newer than the supported version {DOCUMENT_VERSION}",
+                document.version
+            )
+            .into());
+        }
+        Ok(document)
+    }
+}
+
+/// a short digest of the content of a config file, e.g. "fnv1a64:af63dc4c8601ec8c"
+///
+/// FNV-1a is stable across builds and platforms, it detects changes but is not a security measure
+pub fn config_digest(config_source: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in config_source.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("fnv1a64:{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    const CONFIG: &str = include_str!("../test/schedule.toml");
+
+    fn assignments() -> Vec<Assignment> {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 9, day).unwrap();
+        vec![
+            Assignment {
+                date: date(4),
+                place: "Office".to_string(),
+                person: "Emily Smith 🪴".to_string(),
+                base_person: "Emily Smith".to_string(),
+            },
+            Assignment::unfilled(date(11), "Office".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_document_as_json() {
+        let config = test_config();
+        let document = ScheduleDocument::new(CONFIG, &config, Some(42), &assignments());
+
+        let value: serde_json::Value = serde_json::from_str(&document.to_json().unwrap()).unwrap();
+
+        assert_eq!(value["version"], DOCUMENT_VERSION);
+        assert_eq!(value["seed"], 42);
+        assert_eq!(value["config_digest"], config_digest(CONFIG));
+        assert_eq!(
+            value["assignments"][0],
+            serde_json::json!({
+                "date": "2025-09-04",
+                "place": "Office",
+                "base_person": "Emily Smith",
+                "extra_tasks": ["🪴"],
+            })
+        );
+        assert_eq!(
+            value["assignments"][1],
+            serde_json::json!({
+                "date": "2025-09-11",
+                "place": "Office",
+                "base_person": "",
+                "extra_tasks": [],
+            })
+        );
+        assert_eq!(value["people"][0]["group"], "Smith");
+        assert_eq!(
+            value["people"][0]["weekdays"],
+            serde_json::json!([["Thu", 1], ["Fri", 0]])
+        );
+        assert_eq!(
+            value["people"][0]["extra_tasks"],
+            serde_json::json!([["🪴", 1], ["🪟", 0]])
+        );
+    }
+
+    #[test]
+    fn test_document_roundtrip() {
+        let config = test_config();
+        let json = ScheduleDocument::new(CONFIG, &config, None, &assignments())
+            .to_json()
+            .unwrap();
+
+        let document = ScheduleDocument::from_json(&json).unwrap();
+
+        assert_eq!(document.seed, None);
+        assert!(document.matches_config(CONFIG));
+        assert!(!document.matches_config(&CONFIG.replace("Emily", "Emma")));
+        let read: Vec<(String, String)> = document
+            .assignments()
+            .into_iter()
+            .map(|a| (a.person, a.base_person))
+            .collect();
+        assert_eq!(
+            read,
+            vec![
+                ("Emily Smith 🪴".to_string(), "Emily Smith".to_string()),
+                (String::new(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_newer_documents_are_rejected() {
+        let config = test_config();
+        let mut document = ScheduleDocument::new(CONFIG, &config, None, &[]);
+        document.version = DOCUMENT_VERSION + 1;
+
+        let error = ScheduleDocument::from_json(&document.to_json().unwrap()).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("newer than the supported version")
+        );
+        assert!(ScheduleDocument::from_json("date,Place A").is_err());
+    }
+
+    #[test]
+    fn test_config_digest_is_stable() {
+        assert_eq!(config_digest(""), "fnv1a64:cbf29ce484222325");
+        assert_eq!(config_digest("a"), "fnv1a64:af63dc4c8601ec8c");
+    }
+}
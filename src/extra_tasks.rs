@@ -10,7 +10,7 @@ use crate::schedule::Assignment;
 /// among eligible persons (those whose group is listed in the task's `groups`).
 /// For each date, picks the eligible person with the fewest prior assignments of
 /// this task; prefers persons not already carrying another extra task that day (soft).
-pub fn apply_extra_tasks(assignments: &mut Vec<Assignment>, config: &Config) {
+pub fn apply_extra_tasks(assignments: &mut [Assignment], config: &Config) {
     // Reset person to base_person for all assignments
     for a in assignments.iter_mut() {
         a.person = a.base_person.clone();
@@ -180,7 +180,6 @@ pub fn apply_extra_tasks(assignments: &mut Vec<Assignment>, config: &Config) {
                     let mut dates = person_dates.get(&person).cloned().unwrap_or_default();
                     dates.sort();
                     for date in dates {
-                        let date = date; // shadow to owned
                         // This person doesn't hold the task on this date
                         if date_holder.get(&date).map(|h| h.as_str()) == Some(person.as_str()) {
                             continue;
@@ -251,10 +250,33 @@ pub fn apply_extra_tasks(assignments: &mut Vec<Assignment>, config: &Config) {
     }
 }
 
+/// remove the icons of extra tasks that `apply_extra_tasks` appended to a person
+pub fn strip_extra_tasks(person: &str, config: &Config) -> String {
+    let mut base = person.trim_end();
+    let tasks = config.extra_task.as_deref().unwrap_or_default();
+    while let Some(stripped) = tasks.iter().find_map(|task| {
+        base.strip_suffix(task.name.as_str())
+            .and_then(|rest| rest.strip_suffix(' '))
+    }) {
+        base = stripped.trim_end();
+    }
+    base.to_string()
+}
+
+/// the icons of the extra tasks appended to the person of an assignment
+pub fn extra_tasks_of(assignment: &Assignment) -> Vec<String> {
+    // guard against manual edits that changed the person
+    assignment
+        .person
+        .strip_prefix(assignment.base_person.as_str())
+        .map(|icons| icons.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, Dates, ExtraTask, Group, Member, Places, Rules};
+    use crate::config::{Config, Dates, ExtraTask, Group, Member, Places, Rules, Unavailable};
     use chrono::{NaiveDate, Weekday};
 
     fn date(y: i32, m: u32, d: u32) -> NaiveDate {
@@ -274,9 +296,11 @@ mod tests {
                     Weekday::Thu,
                     Weekday::Fri,
                 ],
+                calendars: vec![],
             },
             places: Places {
                 places: vec!["Maier".to_string(), "Doe".to_string()],
+                ..Default::default()
             },
             group: vec![
                 Group {
@@ -285,25 +309,34 @@ mod tests {
                     members: vec![
                         Member {
                             name: "Alice".to_string(),
+                            ..Default::default()
                         },
                         Member {
                             name: "Bob".to_string(),
+                            ..Default::default()
                         },
                     ],
+                    unavailable: Unavailable::default(),
                 },
                 Group {
                     name: "Doe".to_string(),
                     place: "Doe".to_string(),
                     members: vec![Member {
                         name: "Charlie".to_string(),
+                        ..Default::default()
                     }],
+                    unavailable: Unavailable::default(),
                 },
             ],
             rules: Rules {
                 sort: vec![],
                 filter: vec![],
+                ..Default::default()
             },
             extra_task: Some(extra_tasks),
+            fixed: Vec::new(),
+            history: Vec::new(),
+            objective: Default::default(),
         }
     }
 
@@ -316,6 +349,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn strip_extra_tasks_recovers_base_person() {
+        let config = make_config(vec![
+            ExtraTask {
+                name: "🪴".to_string(),
+                groups: vec!["Maier".to_string()],
+            },
+            ExtraTask {
+                name: "🪟".to_string(),
+                groups: vec!["Maier".to_string()],
+            },
+        ]);
+
+        assert_eq!(
+            strip_extra_tasks("Alice Maier 🪴 🪟", &config),
+            "Alice Maier"
+        );
+        assert_eq!(strip_extra_tasks("Alice Maier 🪟", &config), "Alice Maier");
+        assert_eq!(strip_extra_tasks("Alice Maier", &config), "Alice Maier");
+        assert_eq!(strip_extra_tasks("Alice Maier🪴", &config), "Alice Maier🪴");
+    }
+
+    #[test]
+    fn extra_tasks_of_lists_the_icons() {
+        let mut assignment = Assignment {
+            date: date(2025, 9, 1),
+            place: "Maier".to_string(),
+            person: "Alice Maier 🪴 🪟".to_string(),
+            base_person: "Alice Maier".to_string(),
+        };
+        assert_eq!(extra_tasks_of(&assignment), vec!["🪴", "🪟"]);
+
+        assignment.person = "Bob Maier".to_string();
+        assert!(extra_tasks_of(&assignment).is_empty());
+    }
+
     #[test]
     fn extra_task_appended_to_eligible_person() {
         let config = make_config(vec![ExtraTask {
@@ -522,7 +591,7 @@ mod tests {
         // 88 dates, 11 Sonn-eligible + 10 Stern-eligible = 21 people for 🪟
         // Each date: one Sonn person + one Stern person assigned.
         // People appear in round-robin order across their group.
-        use crate::config::{Config, Dates, ExtraTask, Group, Member, Places, Rules};
+        use crate::config::{Config, Dates, ExtraTask, Group, Member, Places, Rules, Unavailable};
         use chrono::Weekday;
 
         // Use pool sizes that divide evenly into 88 to avoid unequal appearances.
@@ -547,9 +616,11 @@ mod tests {
                 to: date(2026, 8, 7),
                 exceptions: vec![],
                 weekdays: vec![Weekday::Thu, Weekday::Fri],
+                calendars: vec![],
             },
             places: Places {
                 places: vec!["Sonn".to_string(), "Stern".to_string(), "Mond".to_string()],
+                ..Default::default()
             },
             group: {
                 let mut groups = vec![];
@@ -559,7 +630,9 @@ mod tests {
                         place: "Sonn".to_string(),
                         members: vec![Member {
                             name: name.to_string(),
+                            ..Default::default()
                         }],
+                        unavailable: Unavailable::default(),
                     });
                 }
                 for name in &stern_names {
@@ -568,7 +641,9 @@ mod tests {
                         place: "Stern".to_string(),
                         members: vec![Member {
                             name: name.to_string(),
+                            ..Default::default()
                         }],
+                        unavailable: Unavailable::default(),
                     });
                 }
                 groups
@@ -576,6 +651,7 @@ mod tests {
             rules: Rules {
                 sort: vec![],
                 filter: vec![],
+                ..Default::default()
             },
             extra_task: Some(vec![
                 ExtraTask {
@@ -595,6 +671,9 @@ mod tests {
                         .collect(),
                 },
             ]),
+            fixed: Vec::new(),
+            history: Vec::new(),
+            objective: Default::default(),
         };
 
         let mut assignments = Vec::new();
@@ -1,14 +1,23 @@
-use iced::widget::{button, column, container, row, scrollable, text};
-use iced::{Element, Fill, FillPortion, Task};
-
-use crate::config::load_config;
-use crate::csv::assignments_to_csv;
-use crate::schedule::Assignment;
-
-use super::state::AppState;
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use iced::keyboard::{self, Modifiers};
+use iced::widget::{
+    button, column, container, pick_list, progress_bar, row, scrollable, text, text_input,
+};
+use iced::{Element, Fill, FillPortion, Subscription, Task, event};
+
+use super::assignment::{Replacement, replacement_candidates};
+use super::diagnostics;
+use super::explain;
+use super::state::{AppState, PendingReplacement, PendingSwap};
 use super::summary;
 use super::table;
 use super::utils;
+use super::utils::{GeneratedSchedule, OpenedSchedule};
+use crate::config::{has_errors, validate_file};
+use crate::csv::{assignments_to_csv, summary_to_csv};
+use crate::schedule::{Assignment, OptimizeBudget, replace_violations, swap_violations};
+use crate::stats::ScheduleStats;
 
 /// Tab selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,16 +39,39 @@ pub enum Message {
     ConfigSelected(String),
     RefreshConfigList,
     GenerateSchedule,
+    RegenerateUnlocked,
     ApplyExtraTasks,
+    Undo,
+    Redo,
     SaveScheduleWithDate,
     SaveSchedule(String), // filename only
+    SaveDocument,
+    ExportCalendar,
+    CalendarExported(Result<String, String>),
+    RefreshSavedSchedules,
+    OpenSchedule(String),
+    ScheduleOpened(Result<OpenedSchedule, String>),
     ConfigsLoaded(Result<Vec<String>, String>),
-    ScheduleGenerated(Result<Vec<Assignment>, String>), // assignments only
+    ScheduleGenerated(Result<GeneratedSchedule, String>),
+    SeedChanged(String),
+    OptimizeChanged(String),
+    AttemptsChanged(String),
+    GenerationProgress(usize, usize), // finished attempts, all attempts
+    AbsencePersonSelected(String),
+    AbsenceFromChanged(String),
+    AbsenceToChanged(String),
+    ReplanAbsence,
     ScheduleSaved(Result<(), String>),
     TabSelected(Tab),
     SummaryPersonClicked(String),
     CellClicked(CellPosition),
     CellRightClicked(CellPosition),
+    ReplacePerson(Replacement),
+    ConfirmSwap,
+    CancelSwap,
+    ConfirmReplacement,
+    CancelReplacement,
+    ModifiersChanged(Modifiers),
     CellHovered(CellPosition),
     MouseEntered(CellPosition),
     MouseLeft,
@@ -95,6 +127,7 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
             if !app.state.config_files.is_empty() && app.state.selected_config.is_none() {
                 let selected = app.state.config_files[0].clone();
                 app.state.selected_config = Some(selected);
+                app.reload_config();
             }
             Task::none()
         }
@@ -105,12 +138,13 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
         Message::ConfigSelected(config_path) => {
             app.state.selected_config = Some(config_path.clone());
             app.state.assignments = Vec::new();
+            app.state.unfilled = Vec::new();
+            app.state.trace = Vec::new();
             app.state.people = Vec::new();
+            app.state.locked.clear();
+            app.state.history.clear();
             app.state.error = None;
-            Task::perform(
-                utils::generate_schedule(config_path),
-                Message::ScheduleGenerated,
-            )
+            app.generate_schedule_task(config_path)
         }
         Message::RefreshConfigList => {
             // Only refresh the file list, don't generate a schedule
@@ -120,47 +154,108 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
             )
         }
         Message::ApplyExtraTasks => {
-            if let Some(config_path) = &app.state.selected_config.clone()
-                && let Ok(config) = load_config(config_path)
+            if let Some(config) = app
+                .state
+                .config
+                .as_ref()
+                .map(|loaded| loaded.config.clone())
                 && !app.state.assignments.is_empty()
             {
+                app.state.record_edit();
                 crate::extra_tasks::apply_extra_tasks(&mut app.state.assignments, &config);
             }
             Task::none()
         }
+        Message::Undo => {
+            app.state.undo();
+            Task::none()
+        }
+        Message::Redo => {
+            app.state.redo();
+            Task::none()
+        }
         Message::GenerateSchedule => {
-            if let Some(config_path) = &app.state.selected_config {
-                Task::perform(
-                    utils::generate_schedule(config_path.clone()),
-                    Message::ScheduleGenerated,
-                )
+            if let Some(config_path) = app.state.selected_config.clone() {
+                app.state.locked.clear();
+                app.generate_schedule_task(config_path)
             } else {
                 Task::none()
             }
         }
-        Message::ScheduleGenerated(Ok(assignments)) => {
-            // Store the assignments
-            app.state.assignments = assignments.clone();
+        Message::RegenerateUnlocked => {
+            if let Some(config_path) = app.state.selected_config.clone() {
+                app.generate_schedule_task(config_path)
+            } else {
+                Task::none()
+            }
+        }
+        Message::SeedChanged(input) => {
+            app.state.seed_input = input;
+            Task::none()
+        }
+        Message::OptimizeChanged(input) => {
+            app.state.optimize_input = input;
+            Task::none()
+        }
+        Message::AttemptsChanged(input) => {
+            app.state.attempts_input = input;
+            Task::none()
+        }
+        Message::GenerationProgress(done, attempts) => {
+            app.state.generation_progress = Some((done, attempts));
+            Task::none()
+        }
+        Message::AbsencePersonSelected(person) => {
+            app.state.absence_person = Some(person);
+            Task::none()
+        }
+        Message::AbsenceFromChanged(input) => {
+            app.state.absence_from_input = input;
+            Task::none()
+        }
+        Message::AbsenceToChanged(input) => {
+            app.state.absence_to_input = input;
+            Task::none()
+        }
+        Message::ReplanAbsence => app.replan_absence_task(),
+        Message::ScheduleGenerated(Ok(generated)) => {
+            // Store the assignments and the seed that created them
+            app.state.assignments = generated.assignments;
+            app.state.unfilled = generated.unfilled;
+            app.state.trace = generated.trace;
+            app.state.seed = Some(generated.seed);
+            app.state.score = Some(generated.score);
+            app.state.optimization = generated.optimization;
+            app.state.generation_progress = None;
             app.state.selected_cell = None;
+            app.state.retain_locks();
+            app.state.history.clear();
+            app.state.pending_swap = None;
+            app.state.pending_replacement = None;
 
             // Generate people states from the config
-            if let Some(config_path) = &app.state.selected_config
-                && let Ok(config) = load_config(config_path)
-            {
-                let mut people = crate::schedule::create_people(&config);
-
-                for a in &app.state.assignments {
-                    if let Some(person) = people.iter_mut().find(|p| p.name() == a.person) {
-                        person.register_service(a.date, a.place.clone());
-                    }
-                }
-
-                app.state.people = people;
+            if let Some(loaded) = &app.state.config {
+                app.state.people = crate::schedule::people_from_assignments(
+                    &loaded.config,
+                    &app.state.assignments,
+                );
             }
 
-            Task::none()
+            if generated.dropped_fixed.is_empty() {
+                return Task::none();
+            }
+            let dropped: Vec<String> = generated
+                .dropped_fixed
+                .iter()
+                .map(|fixed| format!("{} {} {}", fixed.date, fixed.place, fixed.person))
+                .collect();
+            Task::done(Message::ShowSuccessMessage(format!(
+                "Re-planned the fixed assignments during the absence: {}",
+                dropped.join(", ")
+            )))
         }
         Message::ScheduleGenerated(Err(e)) => {
+            app.state.generation_progress = None;
             app.state.error = Some(format!("Error generating schedule: {e}"));
             Task::none()
         }
@@ -178,6 +273,104 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
             }
         }
 
+        Message::SaveDocument => {
+            let Some(config_path) = app.state.selected_config.clone() else {
+                return Task::none();
+            };
+            if app.state.assignments.is_empty() {
+                app.state.error = Some("No schedule to save".to_string());
+                return Task::none();
+            }
+            let filename =
+                std::path::Path::new(&crate::gui::generate_filename(config_path.clone()))
+                    .with_extension("json")
+                    .to_string_lossy()
+                    .to_string();
+            let filename_for_message = filename.clone();
+            Task::perform(
+                utils::save_document(
+                    config_path,
+                    filename,
+                    app.state.assignments.clone(),
+                    app.state.seed,
+                ),
+                move |result| map_save_file_result(filename_for_message, result),
+            )
+        }
+        Message::ExportCalendar => {
+            let Some(config_path) = app.state.selected_config.clone() else {
+                return Task::none();
+            };
+            if app.state.assignments.is_empty() {
+                app.state.error = Some("No schedule to export".to_string());
+                return Task::none();
+            }
+            let assignments = app.state.assignments.clone();
+            Task::perform(
+                async move { utils::export_calendar(&config_path, &assignments) },
+                Message::CalendarExported,
+            )
+        }
+        Message::CalendarExported(Ok(dir)) => Task::done(Message::ShowSuccessMessage(format!(
+            "Calendar exported to {dir}"
+        ))),
+        Message::CalendarExported(Err(e)) => {
+            app.state.error = Some(format!("Error exporting calendar: {e}"));
+            Task::none()
+        }
+
+        Message::RefreshSavedSchedules => {
+            app.state.saved_schedules = app
+                .state
+                .selected_config
+                .as_deref()
+                .map(crate::gui::find_saved_schedules)
+                .unwrap_or_default();
+            Task::none()
+        }
+        Message::OpenSchedule(path) => {
+            let Some(config_path) = app.state.selected_config.clone() else {
+                return Task::none();
+            };
+            app.reload_config();
+            Task::perform(
+                async move { utils::open_schedule(&config_path, &path) },
+                Message::ScheduleOpened,
+            )
+        }
+        Message::ScheduleOpened(Ok(opened)) => {
+            let Some(config) = app.state.config.as_ref().map(|loaded| &loaded.config) else {
+                return Task::none();
+            };
+            app.state.people =
+                crate::schedule::people_from_assignments(config, &opened.assignments);
+            app.state.score = Some(ScheduleStats::new(config, &opened.assignments).score());
+            app.state.assignments = opened.assignments;
+            app.state.unfilled = Vec::new();
+            app.state.trace = Vec::new();
+            app.state.seed = opened.seed;
+            app.state.optimization = None;
+            app.state.selected_cell = None;
+            app.state.replacements.clear();
+            app.state.pending_swap = None;
+            app.state.pending_replacement = None;
+            app.state.locked.clear();
+            app.state.history.clear();
+            app.state.error = None;
+            let note = if opened.config_changed {
+                ", the configuration changed since it was saved"
+            } else {
+                ""
+            };
+            Task::done(Message::ShowSuccessMessage(format!(
+                "Opened schedule {}{note}",
+                opened.path
+            )))
+        }
+        Message::ScheduleOpened(Err(e)) => {
+            app.state.error = Some(format!("Error opening schedule: {e}"));
+            Task::none()
+        }
         Message::SaveSchedule(filename) => {
             let csv_result = assignments_to_csv(&app.state.assignments);
             app.handle_save_schedule(filename, csv_result)
@@ -218,15 +411,54 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
             app.state.toggle_highlighted_name(person);
             Task::none()
         }
-        Message::CellClicked(position) => app.state.handle_cell_click(position),
+        Message::CellClicked(position) => {
+            if app.hold_swap_with_violations(position) {
+                return Task::none();
+            }
+            let task = app.state.handle_cell_click(position);
+            app.update_replacements();
+            task
+        }
+        Message::ConfirmSwap => {
+            if let Some(pending) = app.state.pending_swap.take() {
+                app.state.swap_cells(pending.first, pending.second);
+            }
+            Task::none()
+        }
+        Message::CancelSwap => {
+            app.state.pending_swap = None;
+            Task::none()
+        }
+        Message::ReplacePerson(replacement) => {
+            if !app.hold_replacement_with_violations(&replacement.person) {
+                app.state.replace_selected(&replacement.person);
+            }
+            Task::none()
+        }
+        Message::ConfirmReplacement => {
+            if let Some(pending) = app.state.pending_replacement.take() {
+                app.state.selected_cell = Some(pending.position);
+                app.state.replace_selected(&pending.person);
+            }
+            Task::none()
+        }
+        Message::CancelReplacement => {
+            app.state.pending_replacement = None;
+            Task::none()
+        }
         Message::CellRightClicked(position) => {
             if let Some((_, _, person)) = app.state.get_cell_info(position) {
                 app.state.toggle_highlighted_name(person);
-            } else {
+            } else if app.state.highlighted_names.iter().any(|p| p.is_some()) {
+                app.state.record_edit();
                 app.state.highlighted_names = [None, None, None, None];
             }
             Task::none()
         }
+        Message::ModifiersChanged(modifiers) => {
+            app.state.modifiers = modifiers;
+            Task::none()
+        }
         Message::CellHovered(position) => {
             app.state.hovered_cell = Some(position);
             Task::none()
@@ -246,6 +478,37 @@ pub fn update(app: &mut DutyRosterApp, message: Message) -> Task<Message> {
     }
 }
 
+/// Listen to the modifier keys, also while a text input has the focus, and to the shortcuts
+///
+/// Ctrl+Z undoes the last edit, Ctrl+Shift+Z redoes it, unless a text input uses the keys
+pub fn subscription(_app: &DutyRosterApp) -> Subscription<Message> {
+    event::listen_with(|event, status, _window| match event {
+        iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+            Some(Message::ModifiersChanged(modifiers))
+        }
+        iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+            if status == event::Status::Ignored =>
+        {
+            shortcut(&key, modifiers)
+        }
+        _ => None,
+    })
+}
+
+/// the message of a keyboard shortcut
+fn shortcut(key: &keyboard::Key, modifiers: Modifiers) -> Option<Message> {
+    match key {
+        keyboard::Key::Character(c) if modifiers.command() && c.eq_ignore_ascii_case("z") => {
+            Some(if modifiers.shift() {
+                Message::Redo
+            } else {
+                Message::Undo
+            })
+        }
+        _ => None,
+    }
+}
+
 /// View the application state
 pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
     let title = text("Duty Roster").size(24);
@@ -258,27 +521,179 @@ pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
         Message::RefreshConfigList,
     );
 
-    let generate_button =
-        button(text("Generate Schedule").size(14)).on_press(Message::GenerateSchedule);
+    let generate_button = if app.state.generation_progress.is_none() {
+        button(text("Generate Schedule").size(14)).on_press(Message::GenerateSchedule)
+    } else {
+        button(text("Generate Schedule").size(14)).style(button::secondary)
+    };
+    let regenerate_button =
+        if app.state.generation_progress.is_none() && !app.state.locked.is_empty() {
+            button(text("Regenerate Unlocked").size(14)).on_press(Message::RegenerateUnlocked)
+        } else {
+            button(text("Regenerate Unlocked").size(14)).style(button::secondary)
+        };
     let extra_tasks_button = if !app.state.assignments.is_empty() {
         button(text("Apply Extra Tasks").size(14)).on_press(Message::ApplyExtraTasks)
     } else {
         button(text("Apply Extra Tasks").size(14)).style(button::secondary)
     };
+    let undo_button = if app.state.history.can_undo() {
+        button(text("Undo").size(14)).on_press(Message::Undo)
+    } else {
+        button(text("Undo").size(14)).style(button::secondary)
+    };
+    let redo_button = if app.state.history.can_redo() {
+        button(text("Redo").size(14)).on_press(Message::Redo)
+    } else {
+        button(text("Redo").size(14)).style(button::secondary)
+    };
     let save_button = if !app.state.assignments.is_empty() {
         button(text("Save").size(14)).on_press(Message::SaveScheduleWithDate)
     } else {
         button(text("Save").size(14)).style(button::secondary)
     };
 
+    let save_json_button = if !app.state.assignments.is_empty() {
+        button(text("Save JSON").size(14)).on_press(Message::SaveDocument)
+    } else {
+        button(text("Save JSON").size(14)).style(button::secondary)
+    };
+    let export_button = if !app.state.assignments.is_empty() {
+        button(text("Export Calendar").size(14)).on_press(Message::ExportCalendar)
+    } else {
+        button(text("Export Calendar").size(14)).style(button::secondary)
+    };
+
+    let open_picker = pick_list(
+        app.state.saved_schedules.clone(),
+        None::<String>,
+        Message::OpenSchedule,
+    )
+    .placeholder("Open schedule")
+    .on_open(Message::RefreshSavedSchedules)
+    .text_size(14);
+
+    let seed_input = text_input("random seed", &app.state.seed_input)
+        .on_input(Message::SeedChanged)
+        .size(14)
+        .width(160);
+    let seed_label = match (app.state.seed, app.state.score) {
+        (Some(seed), Some(score)) => text(format!("Seed: {seed}, Score: {score}")).size(14),
+        (Some(seed), None) => text(format!("Seed: {seed}")).size(14),
+        _ => text("").size(14),
+    };
+    let attempts_input = text_input("attempts", &app.state.attempts_input)
+        .on_input(Message::AttemptsChanged)
+        .size(14)
+        .width(80);
+    let optimize_input = text_input("optimize: moves or 5s", &app.state.optimize_input)
+        .on_input(Message::OptimizeChanged)
+        .size(14)
+        .width(160);
+    let optimization_label = match &app.state.optimization {
+        Some(report) => text(format!(
+            "Objective: {:.2} → {:.2}",
+            report.before, report.after
+        ))
+        .size(14),
+        None => text("").size(14),
+    };
+
     let mut content = column![
         title,
         config_selector,
-        row![generate_button, extra_tasks_button, save_button]
+        row![
+            generate_button,
+            regenerate_button,
+            extra_tasks_button,
+            undo_button,
+            redo_button,
+            save_button,
+            save_json_button,
+            export_button,
+            open_picker,
+            seed_input,
+            attempts_input,
+            seed_label,
+            optimize_input,
+            optimization_label
+        ]
+        .spacing(5)
+        .align_y(iced::Alignment::Center)
     ]
     .spacing(15)
     .padding(15);
 
+    if !app.state.assignments.is_empty() {
+        let people: Vec<String> = app.state.people.iter().map(|p| p.name()).collect();
+        let replan_button = if app.state.generation_progress.is_none() {
+            button(text("Re-plan from absence").size(14)).on_press(Message::ReplanAbsence)
+        } else {
+            button(text("Re-plan from absence").size(14)).style(button::secondary)
+        };
+        content = content.push(
+            row![
+                text("Absence:").size(14),
+                pick_list(
+                    people,
+                    app.state.absence_person.clone(),
+                    Message::AbsencePersonSelected
+                )
+                .placeholder("person")
+                .text_size(14),
+                text_input("from YYYY-MM-DD", &app.state.absence_from_input)
+                    .on_input(Message::AbsenceFromChanged)
+                    .size(14)
+                    .width(140),
+                text_input("to YYYY-MM-DD", &app.state.absence_to_input)
+                    .on_input(Message::AbsenceToChanged)
+                    .size(14)
+                    .width(140),
+                replan_button
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    if let Some((date, place, person)) = app
+        .state
+        .selected_cell
+        .filter(|_| !app.state.replacements.is_empty())
+        .and_then(|pos| app.state.get_cell_info(pos))
+    {
+        let slot = if person.is_empty() {
+            format!("Fill {place} on {date} with")
+        } else {
+            format!("Replace {person} at {place} on {date} with")
+        };
+        content = content.push(
+            row![
+                text(slot).size(14),
+                pick_list(
+                    app.state.replacements.clone(),
+                    None::<Replacement>,
+                    Message::ReplacePerson
+                )
+                .placeholder("person")
+                .text_size(14)
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    if let Some((done, attempts)) = app.state.generation_progress {
+        content = content.push(
+            row![
+                progress_bar(0.0..=attempts as f32, done as f32).length(300),
+                text(format!("Generating {done}/{attempts}")).size(12)
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
     // Display error if any
     if let Some(error) = &app.state.error {
         content = content.push(
@@ -288,6 +703,36 @@ pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
         );
     }
 
+    let unfilled = app
+        .state
+        .assignments
+        .iter()
+        .filter(|assignment| assignment.is_unfilled())
+        .count();
+    if unfilled > 0 {
+        content = content.push(
+            text(format!(
+                "{unfilled} slots could not be filled, hover the red cells for the reason"
+            ))
+            .size(12)
+            .color(iced::Color::from_rgb(0.8, 0.0, 0.0)),
+        );
+    }
+
+    if let Some(pending) = &app.state.pending_swap {
+        content = content.push(diagnostics::create_swap_warning_panel(pending));
+    }
+
+    if let Some(pending) = &app.state.pending_replacement {
+        content = content.push(diagnostics::create_replacement_warning_panel(pending));
+    }
+
+    if !app.state.diagnostics.is_empty() {
+        content = content.push(diagnostics::create_diagnostics_panel(
+            &app.state.diagnostics,
+        ));
+    }
+
     // Display success message if any
     if let Some(message) = &app.state.success_message {
         content = content.push(
@@ -333,27 +778,24 @@ pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
                     .map(|a| a.base_person.clone())
             })
             .and_then(|base| {
-                app.state
-                    .selected_config
-                    .as_ref()
-                    .and_then(|p| load_config(p).ok())
-                    .map(|cfg| {
-                        cfg.group
-                            .iter()
-                            .find(|g| {
-                                g.members
-                                    .iter()
-                                    .any(|m| format!("{} {}", m.name, g.name) == base)
-                            })
-                            .map(|g| {
-                                g.members
-                                    .iter()
-                                    .map(|m| format!("{} {}", m.name, g.name))
-                                    .filter(|n| n != &base)
-                                    .collect()
-                            })
-                            .unwrap_or_default()
-                    })
+                app.state.config.as_ref().map(|loaded| {
+                    let cfg = &loaded.config;
+                    cfg.group
+                        .iter()
+                        .find(|g| {
+                            g.members
+                                .iter()
+                                .any(|m| format!("{} {}", m.name, g.name) == base)
+                        })
+                        .map(|g| {
+                            g.members
+                                .iter()
+                                .map(|m| format!("{} {}", m.name, g.name))
+                                .filter(|n| n != &base)
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
             })
             .unwrap_or_default();
 
@@ -363,16 +805,47 @@ pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
                 if !app.state.assignments.is_empty() {
                     let table_view = table::create_table_from_assignments(
                         &app.state.assignments,
+                        &app.state.unfilled,
                         app.state.selected_cell.as_ref(),
                         app.state.hovered_cell.as_ref(),
                         &app.state.highlighted_names,
                         &hovered_groupmates,
+                        &app.state.locked,
                     );
-                    content = content.push(scrollable(table_view).height(FillPortion(3)));
+                    let table_view = scrollable(table_view).height(FillPortion(3));
+                    let hovered_trace = app
+                        .state
+                        .hovered_cell
+                        .and_then(|pos| app.state.get_cell_info(pos))
+                        .and_then(|(date, place, person)| {
+                            explain::trace_for(
+                                &app.state.trace,
+                                &app.state.assignments,
+                                date,
+                                &place,
+                                &person,
+                            )
+                        });
+                    content = match hovered_trace {
+                        Some(trace) => content.push(
+                            row![
+                                table_view.width(FillPortion(3)),
+                                scrollable(explain::create_trace_panel(trace))
+                                    .width(FillPortion(1))
+                            ]
+                            .spacing(10)
+                            .height(FillPortion(3)),
+                        ),
+                        None => content.push(table_view),
+                    };
                 }
             }
             Tab::Summary => {
                 if !app.state.people.is_empty() {
+                    if let Some(loaded) = &app.state.config {
+                        let stats = ScheduleStats::new(&loaded.config, &app.state.assignments);
+                        content = content.push(summary::create_stats_view(&stats));
+                    }
                     let summary_view = summary::create_summary_view_from_people(
                         &app.state.people,
                         &app.state.assignments,
@@ -391,45 +864,241 @@ pub fn view(app: &DutyRosterApp) -> Element<'_, Message> {
         .into()
 }
 
+/// generate on a blocking thread, report the finished attempts and then the schedule
+fn generation_stream(
+    config_path: String,
+    seed: Option<u64>,
+    budget: Option<OptimizeBudget>,
+    attempts: usize,
+    locked: Vec<Assignment>,
+) -> impl Stream<Item = Message> {
+    iced::stream::channel(attempts + 1, async move |mut output| {
+        let (progress, mut finished) = mpsc::unbounded();
+        let job = tokio::task::spawn_blocking(move || {
+            utils::generate_schedule(&config_path, seed, budget, attempts, &locked, &|done| {
+                let _ = progress.unbounded_send(done);
+            })
+        });
+
+        // the channel closes once the generation is done
+        while let Some(done) = finished.next().await {
+            let _ = output
+                .send(Message::GenerationProgress(done, attempts))
+                .await;
+        }
+        let result = job
+            .await
+            .unwrap_or_else(|e| Err(format!("Generation stopped: {e}")));
+        let _ = output.send(Message::ScheduleGenerated(result)).await;
+    })
+}
+
 impl DutyRosterApp {
+    fn generate_schedule_task(&mut self, config_path: String) -> Task<Message> {
+        self.reload_config();
+        // a missing or unreadable file is reported when loading the config
+        self.state.diagnostics = validate_file(&config_path).unwrap_or_default();
+        if has_errors(&self.state.diagnostics) {
+            self.state.error = Some("The config contains errors, see the list of problems".into());
+            return Task::none();
+        }
+
+        let input = utils::parse_seed(&self.state.seed_input).and_then(|seed| {
+            let budget = utils::parse_budget(&self.state.optimize_input)?;
+            let attempts = utils::parse_attempts(&self.state.attempts_input)?;
+            Ok((seed, budget, attempts))
+        });
+        match input {
+            Ok((seed, budget, attempts)) => {
+                self.state.generation_progress = Some((0, attempts));
+                let locked = self.state.locked_assignments();
+                Task::stream(generation_stream(
+                    config_path,
+                    seed,
+                    budget,
+                    attempts,
+                    locked,
+                ))
+            }
+            Err(e) => {
+                self.state.error = Some(e);
+                Task::none()
+            }
+        }
+    }
+
+    /// load the selected config with its history again, the edits of the schedule use this copy
+    fn reload_config(&mut self) {
+        self.state.config = self
+            .state
+            .selected_config
+            .as_deref()
+            .and_then(|path| utils::load_config_with_history(path).ok());
+    }
+
+    /// keep a swap that breaks rules of the config until the user confirms it, true if held
+    ///
+    /// the rules see the history of the config like when generating
+    fn hold_swap_with_violations(&mut self, position: CellPosition) -> bool {
+        let state = &self.state;
+        let Some(selected) = state.selected_cell else {
+            return false;
+        };
+        if state.modifiers.command()
+            || selected == position
+            || state.is_locked(selected)
+            || state.is_locked(position)
+        {
+            return false;
+        }
+        let Some((first, second)) = state.swap_indices(selected, position) else {
+            return false;
+        };
+        let violations = state
+            .config
+            .as_ref()
+            .and_then(|loaded| {
+                swap_violations(
+                    &loaded.config,
+                    &loaded.options,
+                    &state.assignments,
+                    first,
+                    second,
+                )
+                .ok()
+            })
+            .unwrap_or_default();
+        if violations.is_empty() {
+            return false;
+        }
+
+        self.state.pending_swap = Some(PendingSwap {
+            first: selected,
+            second: position,
+            violations,
+        });
+        self.state.selected_cell = None;
+        self.state.replacements.clear();
+        true
+    }
+
+    /// keep a replacement of the selected cell that breaks rules of the config until the user
+    /// confirms it, true if held
+    fn hold_replacement_with_violations(&mut self, person: &str) -> bool {
+        let state = &self.state;
+        let Some(position) = state.selected_cell.filter(|pos| !state.is_locked(*pos)) else {
+            return false;
+        };
+        let Some(slot) = state.slot_index(position) else {
+            return false;
+        };
+        let violations = state
+            .config
+            .as_ref()
+            .and_then(|loaded| {
+                replace_violations(
+                    &loaded.config,
+                    &loaded.options,
+                    &state.assignments,
+                    slot,
+                    person,
+                )
+                .ok()
+            })
+            .unwrap_or_default();
+        if violations.is_empty() {
+            return false;
+        }
+
+        self.state.pending_replacement = Some(PendingReplacement {
+            position,
+            person: person.to_string(),
+            violations,
+        });
+        self.state.selected_cell = None;
+        self.state.replacements.clear();
+        true
+    }
+
+    /// offer replacements for the selected cell, the config provides the rules to rank them
+    fn update_replacements(&mut self) {
+        self.state.replacements = match (self.state.selected_cell, self.state.config.as_ref()) {
+            (Some(position), Some(loaded)) => self
+                .state
+                .get_cell_info(position)
+                .map(|(date, place, person)| {
+                    replacement_candidates(
+                        &loaded.config,
+                        &loaded.options,
+                        &self.state.assignments,
+                        &self.state.people,
+                        date,
+                        &place,
+                        &person,
+                    )
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+    }
+
+    fn replan_absence_task(&mut self) -> Task<Message> {
+        let Some(config_path) = self.state.selected_config.clone() else {
+            return Task::none();
+        };
+        self.reload_config();
+        let absence = match utils::parse_absence(
+            self.state.absence_person.as_deref(),
+            &self.state.absence_from_input,
+            &self.state.absence_to_input,
+        ) {
+            Ok(absence) => absence,
+            Err(e) => {
+                self.state.error = Some(e);
+                return Task::none();
+            }
+        };
+
+        self.state.error = None;
+        self.state.generation_progress = Some((0, 1));
+        let seed = self.state.seed;
+        let assignments = self.state.assignments.clone();
+        let locked = self.state.locked_assignments();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    utils::replan_schedule(&config_path, seed, &assignments, &locked, &absence)
+                })
+                .await
+                .unwrap_or_else(|e| Err(format!("Re-planning stopped: {e}")))
+            },
+            Message::ScheduleGenerated,
+        )
+    }
+
     fn handle_save_schedule(
         &mut self,
         filename: String,
         csv_result: Result<String, Box<dyn std::error::Error>>,
     ) -> Task<Message> {
-        match csv_result {
-            Ok(csv_content) => {
-                // Create summary content directly from people states
-                let extra_counts = summary::extra_task_counts(&self.state.assignments);
-                let mut summary_content = String::new();
-                for person in &self.state.people {
-                    let name = person.name();
-                    let total = person.total_services();
-                    summary_content.push_str(&format!("{name}, total: {total}"));
-
-                    for (day, count) in person.weekday_counts() {
-                        summary_content.push_str(&format!(", {day}: {count}"));
-                    }
-
-                    summary_content.push_str(&format!(
-                        ", different_place: {}",
-                        person.different_place_services()
-                    ));
-
-                    if let Some(counts) = extra_counts.get(&name) {
-                        let mut entries: Vec<String> = counts
-                            .iter()
-                            .map(|(icon, n)| format!("{icon}: {n}"))
-                            .collect();
-                        entries.sort();
-                        for e in entries {
-                            summary_content.push_str(&format!(", {e}"));
-                        }
-                    }
-
-                    summary_content.push('\n');
-                }
-
+        let csv_content = match csv_result {
+            Ok(csv_content) => csv_content,
+            Err(e) => {
+                self.state.error = Some(format!("Failed to create CSV: {e}"));
+                return Task::none();
+            }
+        };
+        let summary = self
+            .state
+            .config
+            .as_ref()
+            .ok_or_else(|| "No config selected".to_string())
+            .and_then(|loaded| {
+                summary_to_csv(&loaded.config, &self.state.assignments, self.state.seed)
+                    .map_err(|e| e.to_string())
+            });
+        match summary {
+            Ok(summary_content) => {
                 let filename_for_message = filename.clone();
                 Task::perform(
                     utils::save_file(filename, csv_content, summary_content),
@@ -437,7 +1106,7 @@ impl DutyRosterApp {
                 )
             }
             Err(e) => {
-                self.state.error = Some(format!("Failed to create CSV: {e}"));
+                self.state.error = Some(format!("Failed to create summary: {e}"));
                 Task::none()
             }
         }
@@ -447,12 +1116,9 @@ impl DutyRosterApp {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schedule::GroupState;
     use crate::schedule::PersonState;
     use chrono::NaiveDate;
-    use std::cell::RefCell;
     use std::collections::HashMap;
-    use std::rc::Rc;
     use std::time::Instant;
     use tempfile::TempDir;
 
@@ -503,6 +1169,34 @@ mod tests {
         assert_eq!(app.state.selected_config, Some(config_path));
     }
 
+    #[test]
+    fn test_update_config_selected_with_invalid_config() {
+        let mut app = create_test_app();
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("invalid.toml");
+        std::fs::write(
+            &config_path,
+            std::fs::read_to_string("test/schedule.toml")
+                .unwrap()
+                .replace("place = \"Office\"", "place = \"Kitchen\""),
+        )
+        .unwrap();
+
+        let _cmd = update(
+            &mut app,
+            Message::ConfigSelected(config_path.to_string_lossy().to_string()),
+        );
+
+        assert!(has_errors(&app.state.diagnostics));
+        assert!(
+            app.state
+                .diagnostics
+                .iter()
+                .any(|d| d.message.contains("Kitchen"))
+        );
+        assert!(app.state.error.is_some());
+    }
+
     #[test]
     fn test_update_tab_selected() {
         let mut app = create_test_app();
@@ -545,6 +1239,35 @@ mod tests {
         assert_eq!(app.state.highlighted_names, [None, None, None, None]);
     }
 
+    #[test]
+    fn test_view_explains_hovered_cell() {
+        let mut app = create_test_app();
+        app.state.assignments = create_test_assignments();
+        let first = &app.state.assignments[0];
+        app.state.trace = vec![crate::schedule::SlotTrace::fixed(
+            first.date,
+            first.place.clone(),
+            first.base_person.clone(),
+        )];
+        app.state.hovered_cell = Some(CellPosition { row: 1, column: 1 });
+
+        let (date, place, person) = app
+            .state
+            .get_cell_info(CellPosition { row: 1, column: 1 })
+            .unwrap();
+        assert!(
+            explain::trace_for(
+                &app.state.trace,
+                &app.state.assignments,
+                date,
+                &place,
+                &person
+            )
+            .is_some()
+        );
+        let _ = view(&app);
+    }
+
     #[test]
     fn test_update_mouse_left() {
         let mut app = create_test_app();
@@ -686,6 +1409,101 @@ mod tests {
         let _ = cmd;
     }
 
+    #[test]
+    fn test_update_seed_changed() {
+        let mut app = create_test_app();
+
+        let _ = update(&mut app, Message::SeedChanged("123".to_string()));
+
+        assert_eq!(app.state.seed_input, "123");
+    }
+
+    #[test]
+    fn test_update_generate_schedule_with_invalid_seed() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+        app.state.seed_input = "not a number".to_string();
+
+        let _ = update(&mut app, Message::GenerateSchedule);
+
+        assert!(app.state.error.as_ref().unwrap().contains("Invalid seed"));
+    }
+
+    #[test]
+    fn test_update_generation_progress() {
+        let mut app = create_test_app();
+
+        let _ = update(&mut app, Message::AttemptsChanged("5".to_string()));
+        let _ = update(&mut app, Message::GenerationProgress(2, 5));
+
+        assert_eq!(app.state.attempts_input, "5");
+        assert_eq!(app.state.generation_progress, Some((2, 5)));
+    }
+
+    #[test]
+    fn test_update_replan_absence() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+        app.state.assignments = create_test_assignments();
+
+        let _ = update(
+            &mut app,
+            Message::AbsenceFromChanged("2025-09-02".to_string()),
+        );
+        let _ = update(
+            &mut app,
+            Message::AbsenceToChanged("2025-09-04".to_string()),
+        );
+        let _ = update(&mut app, Message::ReplanAbsence);
+
+        // nobody is selected yet
+        assert!(app.state.error.as_ref().unwrap().contains("absent person"));
+        assert_eq!(app.state.generation_progress, None);
+        let _ = view(&app);
+
+        let _ = update(
+            &mut app,
+            Message::AbsencePersonSelected("Person1".to_string()),
+        );
+        let _ = update(&mut app, Message::ReplanAbsence);
+
+        assert_eq!(app.state.absence_person.as_deref(), Some("Person1"));
+        assert_eq!(app.state.absence_from_input, "2025-09-02");
+        assert_eq!(app.state.absence_to_input, "2025-09-04");
+        assert_eq!(app.state.error, None);
+        assert_eq!(app.state.generation_progress, Some((0, 1)));
+    }
+
+    #[test]
+    fn test_update_generate_schedule_with_invalid_attempts() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+        app.state.attempts_input = "0".to_string();
+
+        let _ = update(&mut app, Message::GenerateSchedule);
+
+        assert!(app.state.error.as_ref().unwrap().contains("attempts"));
+        assert_eq!(app.state.generation_progress, None);
+    }
+
+    #[test]
+    fn test_update_generate_schedule_with_invalid_budget() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+        let _ = update(&mut app, Message::OptimizeChanged("soon".to_string()));
+        assert_eq!(app.state.optimize_input, "soon");
+
+        let _ = update(&mut app, Message::GenerateSchedule);
+
+        assert!(
+            app.state
+                .error
+                .as_ref()
+                .unwrap()
+                .contains("Invalid optimization budget")
+        );
+    }
+
     #[test]
     fn test_update_generate_schedule_with_config() {
         let mut app = create_test_app();
@@ -729,6 +1547,34 @@ mod tests {
         let _ = cmd;
     }
 
+    #[test]
+    fn test_update_save_document_without_schedule() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+
+        let _ = update(&mut app, Message::SaveDocument);
+
+        assert_eq!(app.state.error, Some("No schedule to save".to_string()));
+    }
+
+    #[test]
+    fn test_update_export_calendar() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+
+        let _ = update(&mut app, Message::ExportCalendar);
+        assert_eq!(app.state.error, Some("No schedule to export".to_string()));
+
+        let _ = update(
+            &mut app,
+            Message::CalendarExported(Err("Failed to write calendar".to_string())),
+        );
+        assert_eq!(
+            app.state.error,
+            Some("Error exporting calendar: Failed to write calendar".to_string())
+        );
+    }
+
     #[test]
     fn test_update_show_success_message_detailed() {
         let mut app = create_test_app();
@@ -774,8 +1620,17 @@ mod tests {
         }];
 
         // Test handling a successful schedule generation
-        let message = Message::ScheduleGenerated(Ok(assignments.clone()));
-
+        let message = Message::ScheduleGenerated(Ok(GeneratedSchedule {
+            assignments: assignments.clone(),
+            unfilled: Vec::new(),
+            trace: Vec::new(),
+            seed: 42,
+            score: 7,
+            optimization: None,
+            dropped_fixed: Vec::new(),
+        }));
+
+        app.state.generation_progress = Some((3, 3));
         let _cmd = update(&mut app, message);
 
         // Verify assignments were stored
@@ -784,8 +1639,307 @@ mod tests {
         assert_eq!(app.state.assignments[0].place, "Place A");
         assert_eq!(app.state.assignments[0].person, "Person1");
 
-        // Verify selected cell was reset
+        // Verify selected cell was reset, the seed and score stored and the progress gone
         assert_eq!(app.state.selected_cell, None);
+        assert_eq!(app.state.seed, Some(42));
+        assert_eq!(app.state.score, Some(7));
+        assert_eq!(app.state.generation_progress, None);
+    }
+
+    #[test]
+    fn test_update_regenerate_unlocked_keeps_locks() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some("test_config.toml".to_string());
+        app.state.assignments = create_test_assignments();
+        let _ = update(&mut app, Message::ModifiersChanged(Modifiers::COMMAND));
+        assert!(app.state.modifiers.command());
+        let _ = update(
+            &mut app,
+            Message::CellClicked(CellPosition { row: 1, column: 1 }),
+        );
+        let _ = update(&mut app, Message::ModifiersChanged(Modifiers::default()));
+        assert_eq!(app.state.locked.len(), 1);
+
+        // the generated schedule contains the locked assignment, the lock stays
+        let _ = update(&mut app, Message::RegenerateUnlocked);
+        let generated = GeneratedSchedule {
+            assignments: create_test_assignments(),
+            unfilled: Vec::new(),
+            trace: Vec::new(),
+            seed: 1,
+            score: 0,
+            optimization: None,
+            dropped_fixed: Vec::new(),
+        };
+        let _ = update(&mut app, Message::ScheduleGenerated(Ok(generated)));
+        assert_eq!(app.state.locked.len(), 1);
+
+        // a full generation starts without locks
+        let _ = update(&mut app, Message::GenerateSchedule);
+        assert!(app.state.locked.is_empty());
+    }
+
+    #[test]
+    fn test_update_undo_redo() {
+        let mut app = create_test_app();
+        app.state.assignments = create_test_assignments();
+        let _ = update(
+            &mut app,
+            Message::CellClicked(CellPosition { row: 1, column: 1 }),
+        );
+        let _ = update(
+            &mut app,
+            Message::CellClicked(CellPosition { row: 2, column: 2 }),
+        );
+        assert_eq!(app.state.assignments[0].person, "Person2");
+
+        let _ = update(&mut app, Message::Undo);
+        assert_eq!(app.state.assignments[0].person, "Person1");
+        let _ = update(&mut app, Message::Redo);
+        assert_eq!(app.state.assignments[0].person, "Person2");
+
+        // a new schedule can not be undone
+        let generated = GeneratedSchedule {
+            assignments: create_test_assignments(),
+            unfilled: Vec::new(),
+            trace: Vec::new(),
+            seed: 1,
+            score: 0,
+            optimization: None,
+            dropped_fixed: Vec::new(),
+        };
+        let _ = update(&mut app, Message::ScheduleGenerated(Ok(generated)));
+        assert!(!app.state.history.can_undo());
+    }
+
+    #[test]
+    fn test_update_replace_person() {
+        let mut app = create_test_app();
+        app.state.selected_config = Some(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test/schedule.toml")
+                .to_string_lossy()
+                .to_string(),
+        );
+        app.reload_config();
+        let generated = utils::generate_schedule(
+            app.state.selected_config.as_deref().unwrap(),
+            Some(3),
+            None,
+            1,
+            &[],
+            &|_| {},
+        )
+        .unwrap();
+        let _ = update(&mut app, Message::ScheduleGenerated(Ok(generated)));
+
+        let _ = update(
+            &mut app,
+            Message::CellClicked(CellPosition { row: 1, column: 1 }),
+        );
+        assert!(!app.state.replacements.is_empty());
+        let _ = view(&app);
+
+        let replacement = app.state.replacements[0].clone();
+        let _ = update(&mut app, Message::ReplacePerson(replacement.clone()));
+
+        let (_, _, person) = app
+            .state
+            .get_cell_info(CellPosition { row: 1, column: 1 })
+            .unwrap();
+        assert_eq!(person, replacement.person);
+        assert!(app.state.replacements.is_empty());
+        let services: usize = app.state.people.iter().map(|p| p.total_services()).sum();
+        assert_eq!(services, app.state.assignments.len());
+    }
+
+    /// an app with Emily at Place A and Olivia at Place B on one date, people stay at their place
+    fn create_same_place_app(temp_dir: &TempDir) -> DutyRosterApp {
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = []
+            weekdays = ["Mon"]
+
+            [places]
+            places = ["Place A", "Place B"]
+
+            [[group]]
+            name = "Smith"
+            place = "Place A"
+
+            [[group.members]]
+            name = "Emily"
+
+            [[group]]
+            name = "Jones"
+            place = "Place B"
+
+            [[group.members]]
+            name = "Olivia"
+
+            [rules]
+            sort = []
+            filter = ["filterSamePlace"]
+            "#,
+        )
+        .unwrap();
+        let mut app = create_test_app();
+        app.state.selected_config = Some(config_path.to_string_lossy().to_string());
+        app.reload_config();
+        let date = create_test_date(2025, 9, 1);
+        app.state.assignments = vec![
+            Assignment {
+                date,
+                place: "Place A".to_string(),
+                person: "Emily Smith".to_string(),
+                base_person: "Emily Smith".to_string(),
+            },
+            Assignment {
+                date,
+                place: "Place B".to_string(),
+                person: "Olivia Jones".to_string(),
+                base_person: "Olivia Jones".to_string(),
+            },
+        ];
+        app
+    }
+
+    #[test]
+    fn test_update_swap_breaking_rules_needs_confirmation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = create_same_place_app(&temp_dir);
+        let click = |app: &mut DutyRosterApp, column| {
+            let _ = update(app, Message::CellClicked(CellPosition { row: 1, column }));
+        };
+
+        click(&mut app, 1);
+        click(&mut app, 2);
+        let pending = app.state.pending_swap.as_ref().unwrap();
+        assert_eq!(pending.violations.len(), 2);
+        assert_eq!(app.state.assignments[0].person, "Emily Smith");
+        let _ = view(&app);
+
+        let _ = update(&mut app, Message::CancelSwap);
+        assert!(app.state.pending_swap.is_none());
+        assert_eq!(app.state.assignments[0].person, "Emily Smith");
+
+        click(&mut app, 1);
+        click(&mut app, 2);
+        let _ = update(&mut app, Message::ConfirmSwap);
+        assert!(app.state.pending_swap.is_none());
+        assert_eq!(app.state.assignments[0].person, "Olivia Jones");
+        assert!(app.state.history.can_undo());
+    }
+
+    #[test]
+    fn test_update_replacement_breaking_rules_needs_confirmation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = create_same_place_app(&temp_dir);
+        let emily = CellPosition { row: 1, column: 1 };
+        let pick_olivia = |app: &mut DutyRosterApp| {
+            let _ = update(app, Message::CellClicked(emily));
+            let olivia = app
+                .state
+                .replacements
+                .iter()
+                .find(|r| r.person == "Olivia Jones")
+                .unwrap()
+                .clone();
+            let _ = update(app, Message::ReplacePerson(olivia));
+        };
+
+        pick_olivia(&mut app);
+        let pending = app.state.pending_replacement.as_ref().unwrap();
+        assert_eq!(pending.position, emily);
+        assert_eq!(pending.violations.len(), 2);
+        assert_eq!(app.state.assignments[0].person, "Emily Smith");
+        assert!(app.state.selected_cell.is_none());
+        let _ = view(&app);
+
+        let _ = update(&mut app, Message::CancelReplacement);
+        assert!(app.state.pending_replacement.is_none());
+        assert_eq!(app.state.assignments[0].person, "Emily Smith");
+        assert!(!app.state.history.can_undo());
+
+        pick_olivia(&mut app);
+        let _ = update(&mut app, Message::ConfirmReplacement);
+        assert!(app.state.pending_replacement.is_none());
+        assert_eq!(app.state.assignments[0].person, "Olivia Jones");
+        assert!(app.state.history.can_undo());
+    }
+
+    #[test]
+    fn test_update_open_schedule() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("roster.toml");
+        std::fs::copy(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml"),
+            &config_path,
+        )
+        .unwrap();
+        let schedule_path = temp_dir.path().join("roster_2025_09_01_10_00.csv");
+        std::fs::write(
+            &schedule_path,
+            "date,Office\n2025-09-04,Emily Smith\n2025-09-11,\n\nseed: 9\n",
+        )
+        .unwrap();
+        let mut app = create_test_app();
+        app.state.selected_config = Some(config_path.to_string_lossy().to_string());
+        app.reload_config();
+        app.state.locked.insert((
+            create_test_date(2025, 9, 4),
+            "Office".to_string(),
+            "Emily Smith".to_string(),
+        ));
+
+        let _ = update(&mut app, Message::RefreshSavedSchedules);
+        assert_eq!(
+            app.state.saved_schedules,
+            vec![schedule_path.to_string_lossy().to_string()]
+        );
+
+        let opened = utils::open_schedule(
+            app.state.selected_config.as_deref().unwrap(),
+            &app.state.saved_schedules[0],
+        );
+        let _ = update(&mut app, Message::ScheduleOpened(opened));
+
+        assert_eq!(app.state.assignments.len(), 2);
+        assert!(app.state.assignments[1].is_unfilled());
+        assert_eq!(app.state.seed, Some(9));
+        assert!(app.state.locked.is_empty());
+        let emily = app
+            .state
+            .people
+            .iter()
+            .find(|p| p.name() == "Emily Smith")
+            .unwrap();
+        assert_eq!(emily.total_services(), 1);
+
+        let _ = update(&mut app, Message::ScheduleOpened(Err("broken".to_string())));
+        assert!(app.state.error.as_ref().unwrap().contains("broken"));
+    }
+
+    #[test]
+    fn test_shortcuts() {
+        let z = keyboard::Key::Character("z".into());
+        assert!(matches!(
+            shortcut(&z, Modifiers::COMMAND),
+            Some(Message::Undo)
+        ));
+        assert!(matches!(
+            shortcut(
+                &keyboard::Key::Character("Z".into()),
+                Modifiers::COMMAND | Modifiers::SHIFT
+            ),
+            Some(Message::Redo)
+        ));
+        assert!(shortcut(&z, Modifiers::default()).is_none());
     }
 
     #[test]
@@ -818,6 +1972,7 @@ mod tests {
         std::fs::write(&config_path, config_content).unwrap();
 
         app.state.selected_config = Some(config_path.to_string_lossy().to_string());
+        app.reload_config();
 
         let assignments = vec![Assignment {
             date: create_test_date(2025, 9, 1),
@@ -826,7 +1981,18 @@ mod tests {
             base_person: "Person1".to_string(),
         }];
 
-        let _ = update(&mut app, Message::ScheduleGenerated(Ok(assignments)));
+        let _ = update(
+            &mut app,
+            Message::ScheduleGenerated(Ok(GeneratedSchedule {
+                assignments,
+                unfilled: Vec::new(),
+                trace: Vec::new(),
+                seed: 1,
+                score: 0,
+                optimization: None,
+                dropped_fixed: Vec::new(),
+            })),
+        );
         assert!(!app.state.people.is_empty());
     }
 
@@ -869,6 +2035,7 @@ mod tests {
         std::fs::write(&config_path, config_content).unwrap();
 
         app.state.selected_config = Some(config_path.to_string_lossy().to_string());
+        app.reload_config();
 
         let dates = vec![
             create_test_date(2025, 9, 1),
@@ -898,7 +2065,15 @@ mod tests {
 
         let _ = update(
             &mut app,
-            Message::ScheduleGenerated(Ok(assignments.clone())),
+            Message::ScheduleGenerated(Ok(GeneratedSchedule {
+                assignments: assignments.clone(),
+                unfilled: Vec::new(),
+                trace: Vec::new(),
+                seed: 1,
+                score: 0,
+                optimization: None,
+                dropped_fixed: Vec::new(),
+            })),
         );
 
         let mut expected: HashMap<(String, String), usize> = HashMap::new();
@@ -969,12 +2144,7 @@ mod tests {
         }];
 
         // Add test people
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
         person1.register_service(date, "Place A".to_string());
         app.state.people = vec![person1];
 
@@ -982,21 +2152,29 @@ mod tests {
         let filename = "test_schedule.csv".to_string();
         let message = Message::SaveSchedule(filename.clone());
 
+        // the summary needs the config
+        let _ = update(&mut app, message.clone());
+        assert_eq!(
+            app.state.error,
+            Some("Failed to create summary: No config selected".to_string())
+        );
+
+        app.state.error = None;
+        app.state.selected_config =
+            Some(concat!(env!("CARGO_MANIFEST_DIR"), "/test/schedule.toml").to_string());
+        app.reload_config();
         let cmd = update(&mut app, message);
 
         // Verify a command was returned (we can't easily test the actual command)
         // Just check that it's not empty by using a dummy variable
         let _ = cmd;
-
-        // The test passes if we get here without panicking
-        assert!(true);
+        assert_eq!(app.state.error, None);
     }
 
     #[test]
     fn test_handle_save_schedule_csv_error_branch() {
         let mut app = create_test_app();
-        let err: Box<dyn std::error::Error> =
-            Box::new(std::io::Error::new(std::io::ErrorKind::Other, "csv error"));
+        let err: Box<dyn std::error::Error> = Box::new(std::io::Error::other("csv error"));
 
         let _cmd = app.handle_save_schedule("x.csv".to_string(), Err(err));
         assert!(
@@ -1067,12 +2245,7 @@ mod tests {
         app.state.active_tab = Tab::Schedule;
         let _ = view(&app);
 
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
         p.register_service(create_test_date(2025, 9, 1), "Place A".to_string());
 
         app.state.people = vec![p];
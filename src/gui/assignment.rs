@@ -1,8 +1,18 @@
+use std::collections::HashSet;
+use std::fmt;
+
 use chrono::NaiveDate;
 
-use crate::schedule::{Assignment, PersonState};
+use crate::config::Config;
+use crate::schedule::optimize::people_with_history;
+use crate::schedule::{
+    Assignment, Exclusion, PersonState, PlaceCap, RuleContext, ScheduleOptions, exclusion,
+    group_states,
+};
 
 /// Swap assignments between two positions
+///
+/// one of the positions may be an unfilled slot, its person moves into the empty slot
 #[allow(clippy::too_many_arguments)]
 pub fn swap_assignments(
     assignments: &mut [Assignment],
@@ -14,75 +24,169 @@ pub fn swap_assignments(
     place2: &str,
     person2: &str,
 ) -> bool {
-    // First, find if both assignments exist
-    let mut found1 = false;
-    let mut found2 = false;
-
-    // Check if both assignments exist
-    for assignment in assignments.iter() {
-        if assignment.date == date1 && assignment.place == place1 {
-            found1 = true;
-        } else if assignment.date == date2 && assignment.place == place2 {
-            found2 = true;
-        }
+    // the person identifies the slot of a place with several slots
+    let find = |date: NaiveDate, place: &str, person: &str, skip: Option<usize>| {
+        assignments.iter().enumerate().position(|(idx, a)| {
+            Some(idx) != skip && a.date == date && a.place == place && a.person == person
+        })
+    };
+    let idx1 = find(date1, place1, person1, None);
+    let idx2 = find(date2, place2, person2, idx1);
+
+    // Only update if both assignments are found
+    let (Some(idx1), Some(idx2)) = (idx1, idx2) else {
+        return false;
+    };
 
-        if found1 && found2 {
-            break;
+    let first = assignments[idx1].clone();
+    let second = assignments[idx2].clone();
+    assignments[idx1].person = second.person;
+    assignments[idx1].base_person = second.base_person.clone();
+    assignments[idx2].person = first.person;
+    assignments[idx2].base_person = first.base_person.clone();
+
+    // Update the service records, nobody moves out of an unfilled slot
+    let mut move_service = |person: &str, from: (NaiveDate, &str), to: (NaiveDate, &str)| {
+        if let Some(p) = people.iter_mut().find(|p| p.name() == person) {
+            p.unregister_service(from.0, from.1.to_string());
+            p.register_service(to.0, to.1.to_string());
         }
-    }
+    };
+    move_service(&first.base_person, (date1, place1), (date2, place2));
+    move_service(&second.base_person, (date2, place2), (date1, place1));
 
-    // Only update if both assignments are found
-    if found1 && found2 {
-        for assignment in assignments {
-            if assignment.date == date1 && assignment.place == place1 {
-                assignment.person = person2.to_string();
-            } else if assignment.date == date2 && assignment.place == place2 {
-                assignment.person = person1.to_string();
-            }
+    true
+}
+
+/// a person who can take over a slot, as offered by the replace picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub person: String,
+    /// services of the person in the current schedule
+    pub total: usize,
+    /// services of the person at another place than their own
+    pub different_place: usize,
+    /// why the generator would not pick the person for the slot
+    pub exclusion: Option<Exclusion>,
+}
+
+impl fmt::Display for Replacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} total, {} elsewhere)",
+            self.person, self.total, self.different_place
+        )?;
+        if let Some(exclusion) = &self.exclusion {
+            write!(f, " - {exclusion}")?;
         }
+        Ok(())
     }
+}
 
-    // Update the people's service records
-    if found1 && found2 {
-        // Find the indices of the people involved
-        let person1_idx = people.iter().position(|p| p.name() == person1);
-        let person2_idx = people.iter().position(|p| p.name() == person2);
-
-        // Update the service records if both people are found
-        if let (Some(idx1), Some(idx2)) = (person1_idx, person2_idx) {
-            // We need to be careful with borrowing rules here
-            // First, unregister the old services
-            {
-                let p1 = &mut people[idx1];
-                p1.unregister_service(date1, place1.to_string());
-            }
-            {
-                let p2 = &mut people[idx2];
-                p2.unregister_service(date2, place2.to_string());
-            }
+/// every person of the config except the current one, the most suitable for the slot first
+///
+/// the people are ranked by the sort rules of the config as if the slot were empty, the history
+/// of `options` counts like for the generator. People the generator would leave out follow at the
+/// end. The totals are the ones of `people`
+pub fn replacement_candidates(
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &[Assignment],
+    people: &[PersonState],
+    date: NaiveDate,
+    place: &str,
+    person: &str,
+) -> Vec<Replacement> {
+    let Some(slot) = assignments
+        .iter()
+        .position(|a| a.date == date && a.place == place && a.person == person)
+    else {
+        return Vec::new();
+    };
+    let current = &assignments[slot].base_person;
+    let rest: Vec<Assignment> = assignments
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx != slot)
+        .map(|(_, a)| a.clone())
+        .collect();
+    let states = people_with_history(config, options, &rest);
+    let context = RuleContext {
+        date,
+        place,
+        people: &states,
+        groups: &group_states(&states),
+        place_cap: &PlaceCap::new(&states),
+        assignments: &rest,
+    };
 
-            // Then register the new services
-            {
-                let p1 = &mut people[idx1];
-                p1.register_service(date2, place2.to_string());
-            }
-            {
-                let p2 = &mut people[idx2];
-                p2.register_service(date1, place1.to_string());
+    let Ok(rules) = options.rules.resolve(&config.rules) else {
+        return Vec::new();
+    };
+    let others: Vec<&PersonState> = states.iter().filter(|p| p.name() != *current).collect();
+    let assigned_today: HashSet<String> = rest
+        .iter()
+        .filter(|a| a.date == date && !a.is_unfilled())
+        .map(|a| a.base_person.clone())
+        .collect();
+
+    let mut replacements: Vec<Replacement> = rules
+        .ranking(&others, &context)
+        .into_iter()
+        .map(|idx| {
+            let candidate = others[idx];
+            let name = candidate.name();
+            let exclusion = exclusion(candidate, &assigned_today, &rules, &context);
+            let current = people.iter().find(|p| p.name() == name);
+            Replacement {
+                total: current.map_or(0, |p| p.total_services()),
+                different_place: current.map_or(0, |p| p.different_place_services()),
+                person: name,
+                exclusion,
             }
-        }
+        })
+        .collect();
+    // a stable sort keeps the ranking within both parts
+    replacements.sort_by_key(|r| r.exclusion.is_some());
+    replacements
+}
+
+/// put another person into a slot, an unfilled slot gets filled
+///
+/// the extra task icons of the slot are dropped, apply the extra tasks again to restore them
+pub fn replace_person(
+    assignments: &mut [Assignment],
+    people: &mut [PersonState],
+    date: NaiveDate,
+    place: &str,
+    person: &str,
+    replacement: &str,
+) -> bool {
+    let Some(a) = assignments
+        .iter_mut()
+        .find(|a| a.date == date && a.place == place && a.person == person)
+    else {
+        return false;
+    };
+    let previous = std::mem::replace(&mut a.base_person, replacement.to_string());
+    a.person = replacement.to_string();
+
+    if let Some(p) = people.iter_mut().find(|p| p.name() == previous) {
+        p.unregister_service(date, place.to_string());
+    }
+    if let Some(p) = people.iter_mut().find(|p| p.name() == replacement) {
+        p.register_service(date, place.to_string());
     }
 
-    found1 && found2
+    true
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schedule::GroupState;
+    use crate::schedule::people_from_assignments;
     use chrono::NaiveDate;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     fn create_test_date(year: i32, month: u32, day: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(year, month, day).unwrap()
@@ -106,20 +210,9 @@ mod tests {
     }
 
     fn create_test_people() -> Vec<PersonState> {
-        let group_state1 = Rc::new(RefCell::new(GroupState::default()));
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
 
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state1),
-        );
-
-        let mut person2 = PersonState::new(
-            "Person2".to_string(),
-            "Place B".to_string(),
-            Rc::clone(&group_state2),
-        );
+        let mut person2 = PersonState::new("Person2".to_string(), "Place B".to_string(), 1);
 
         // Register initial services
         person1.register_service(create_test_date(2025, 9, 1), "Place A".to_string());
@@ -163,6 +256,77 @@ mod tests {
         assert_eq!(people[1].total_services(), 1); // Should remain 1 (unregistered 1, registered 1)
     }
 
+    #[test]
+    fn test_swap_assignments_between_slots_of_same_place() {
+        let date = create_test_date(2025, 9, 1);
+        let other = create_test_date(2025, 9, 2);
+        let mut assignments = vec![
+            Assignment {
+                date,
+                place: "Place A".to_string(),
+                person: "Person1".to_string(),
+                base_person: "Person1".to_string(),
+            },
+            Assignment {
+                date,
+                place: "Place A".to_string(),
+                person: "Person3".to_string(),
+                base_person: "Person3".to_string(),
+            },
+            Assignment {
+                date: other,
+                place: "Place B".to_string(),
+                person: "Person2".to_string(),
+                base_person: "Person2".to_string(),
+            },
+        ];
+        let mut people = create_test_people();
+
+        let result = swap_assignments(
+            &mut assignments,
+            &mut people,
+            date,
+            "Place A",
+            "Person3",
+            other,
+            "Place B",
+            "Person2",
+        );
+
+        assert!(result);
+        // only the second slot of Place A changes
+        assert_eq!(assignments[0].person, "Person1");
+        assert_eq!(assignments[1].person, "Person2");
+        assert_eq!(assignments[2].person, "Person3");
+    }
+
+    #[test]
+    fn test_swap_assignments_into_unfilled_slot() {
+        let mut assignments = create_test_assignments();
+        let date = create_test_date(2025, 9, 3);
+        assignments.push(Assignment::unfilled(date, "Place B".to_string()));
+        let mut people = create_test_people();
+
+        let result = swap_assignments(
+            &mut assignments,
+            &mut people,
+            create_test_date(2025, 9, 1),
+            "Place A",
+            "Person1",
+            date,
+            "Place B",
+            "",
+        );
+
+        assert!(result);
+        assert!(assignments[0].is_unfilled());
+        assert_eq!(assignments[2].person, "Person1");
+        assert_eq!(assignments[2].base_person, "Person1");
+        assert_eq!(people[0].total_services(), 1);
+        assert_eq!(people[0].last_service(), Some(date));
+        assert_eq!(people[0].different_place_services(), 1);
+    }
+
     #[test]
     fn test_swap_assignments_not_found() {
         let mut assignments = create_test_assignments();
@@ -187,4 +351,147 @@ mod tests {
         assert_eq!(assignments[0].person, "Person1"); // First assignment still has Person1
         assert_eq!(assignments[1].person, "Person2"); // Second assignment still has Person2
     }
+
+    #[test]
+    fn test_replace_person() {
+        let mut assignments = create_test_assignments();
+        assignments[0].person = "Person1 🪴".to_string();
+        let mut people = create_test_people();
+        people.push(PersonState::new(
+            "Person3".to_string(),
+            "Place B".to_string(),
+            1,
+        ));
+
+        let result = replace_person(
+            &mut assignments,
+            &mut people,
+            create_test_date(2025, 9, 1),
+            "Place A",
+            "Person1 🪴",
+            "Person3",
+        );
+
+        assert!(result);
+        assert_eq!(assignments[0].person, "Person3");
+        assert_eq!(assignments[0].base_person, "Person3");
+        assert_eq!(people[0].total_services(), 0);
+        assert_eq!(people[2].total_services(), 1);
+        assert_eq!(people[2].different_place_services(), 1);
+
+        assert!(!replace_person(
+            &mut assignments,
+            &mut people,
+            create_test_date(2025, 9, 1),
+            "Place A",
+            "Person1",
+            "Person2",
+        ));
+    }
+
+    #[test]
+    fn test_replacement_candidates() {
+        let config: Config = toml::from_str(
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = []
+            weekdays = ["Mon"]
+
+            [places]
+            places = ["Place A", "Place B"]
+
+            [[group]]
+            name = "Smith"
+            place = "Place A"
+
+            [[group.members]]
+            name = "Emily"
+
+            [[group.members]]
+            name = "William"
+            unavailable = { dates = ["2025-09-15"] }
+
+            [[group]]
+            name = "Jones"
+            place = "Place B"
+
+            [[group.members]]
+            name = "Olivia"
+
+            [[group.members]]
+            name = "Noah"
+
+            [rules]
+            sort = ["sortByLeastServices"]
+            filter = []
+        "#,
+        )
+        .unwrap();
+        let assignment = |day: u32, place: &str, person: &str| Assignment {
+            date: create_test_date(2025, 9, day),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        };
+        let assignments = vec![
+            assignment(1, "Place A", "Emily Smith"),
+            assignment(1, "Place B", "Olivia Jones"),
+            assignment(8, "Place A", "Emily Smith"),
+            assignment(8, "Place B", "Noah Jones"),
+            assignment(15, "Place A", "Emily Smith"),
+            assignment(15, "Place B", "Olivia Jones"),
+        ];
+        let people = people_from_assignments(&config, &assignments);
+
+        let candidates = replacement_candidates(
+            &config,
+            &ScheduleOptions::default(),
+            &assignments,
+            &people,
+            create_test_date(2025, 9, 15),
+            "Place A",
+            "Emily Smith",
+        );
+
+        let names: Vec<&str> = candidates.iter().map(|c| c.person.as_str()).collect();
+        // Noah has the fewest services, William is unavailable and Olivia already on duty
+        assert_eq!(names, vec!["Noah Jones", "William Smith", "Olivia Jones"]);
+        assert_eq!(candidates[0].exclusion, None);
+        assert_eq!(candidates[1].exclusion, Some(Exclusion::Unavailable));
+        assert_eq!(candidates[2].exclusion, Some(Exclusion::AlreadyAssigned));
+        assert_eq!(
+            candidates[0].to_string(),
+            "Noah Jones (1 total, 0 elsewhere)"
+        );
+        assert_eq!(
+            candidates[2].to_string(),
+            "Olivia Jones (2 total, 0 elsewhere) - already assigned"
+        );
+
+        // with the services of the history William ranks behind Olivia
+        let options = ScheduleOptions {
+            history: (0..3)
+                .map(|week| Assignment {
+                    date: create_test_date(2025, 8, 4 + 7 * week),
+                    ..assignment(1, "Place A", "William Smith")
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let candidates = replacement_candidates(
+            &config,
+            &options,
+            &assignments,
+            &people,
+            create_test_date(2025, 9, 15),
+            "Place A",
+            "Emily Smith",
+        );
+
+        let names: Vec<&str> = candidates.iter().map(|c| c.person.as_str()).collect();
+        assert_eq!(names, vec!["Noah Jones", "Olivia Jones", "William Smith"]);
+        assert_eq!(candidates[2].total, 0);
+    }
 }
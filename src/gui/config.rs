@@ -64,6 +64,57 @@ pub fn generate_filename(config_path: String) -> String {
     out_path.to_string_lossy().to_string()
 }
 
+/// The directory for the calendar export of a config, next to the config file
+pub fn calendar_dir(config_path: &str) -> String {
+    let path = Path::new(config_path);
+    let file_stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("schedule");
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    parent
+        .join(format!("{file_stem}_calendar"))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Find the schedules (CSV or JSON) saved for a config, see `generate_filename`, the newest first
+pub fn find_saved_schedules(config_path: &str) -> Vec<String> {
+    let path = Path::new(config_path);
+    let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{file_stem}_");
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut schedules: Vec<String> = fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|ext| ext == "csv" || ext == "json")
+                        && path
+                            .file_name()
+                            .and_then(|f| f.to_str())
+                            .is_some_and(|f| f.starts_with(&prefix))
+                })
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    // the timestamp in the name sorts the schedules by time
+    schedules.sort();
+    schedules.reverse();
+    schedules
+}
+
 /// Create a UI row with config dropdown and refresh button
 pub fn create_config_selector<'a, Message>(
     config_files: &[String],
@@ -169,6 +220,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calendar_dir() {
+        assert_eq!(
+            calendar_dir("configs/roster.toml"),
+            Path::new("configs")
+                .join("roster_calendar")
+                .to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_find_saved_schedules() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("roster.toml");
+        for file in [
+            "roster_2025_09_01_10_00.csv",
+            "roster_2025_09_02_10_00.csv",
+            "roster_2025_09_03_10_00.json",
+            "other_2025_09_02_10_00.csv",
+            "roster_notes.txt",
+        ] {
+            fs::write(temp_dir.path().join(file), "date\n").unwrap();
+        }
+
+        let schedules = find_saved_schedules(&config_path.to_string_lossy());
+
+        let names: Vec<&str> = schedules
+            .iter()
+            .map(|s| Path::new(s).file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "roster_2025_09_03_10_00.json",
+                "roster_2025_09_02_10_00.csv",
+                "roster_2025_09_01_10_00.csv"
+            ]
+        );
+        assert!(find_saved_schedules("missing/roster.toml").is_empty());
+    }
+
     #[test]
     fn test_find_config_files_no_configs() {
         let temp_dir = TempDir::new().unwrap();
@@ -213,8 +305,5 @@ mod tests {
             TestMessage::ConfigSelected,
             TestMessage::Refresh,
         );
-
-        // Just ensure the test passes without panicking
-        assert!(true);
     }
 }
@@ -0,0 +1,190 @@
+use iced::widget::{button, column, container, row, text};
+use iced::{Element, Fill, Theme};
+
+use super::Message;
+use super::state::{PendingReplacement, PendingSwap};
+use crate::config::{Diagnostic, Severity};
+use crate::schedule::Violation;
+
+pub fn diagnostics_panel_style(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(iced::Color::from_rgb(1.0, 0.95, 0.9).into()),
+        border: iced::Border {
+            color: iced::Color::from_rgb(0.9, 0.6, 0.4),
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
+/// color of a diagnostic line, red for errors and orange for warnings
+pub fn severity_color(severity: Severity) -> iced::Color {
+    match severity {
+        Severity::Error => iced::Color::from_rgb(0.8, 0.0, 0.0),
+        Severity::Warning => iced::Color::from_rgb(0.8, 0.5, 0.0),
+    }
+}
+
+/// heading of the panel, e.g. "Config problems: 1 error, 2 warnings"
+pub fn diagnostics_heading(diagnostics: &[Diagnostic]) -> String {
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    let plural = |count: usize, word: &str| {
+        if count == 1 {
+            format!("{count} {word}")
+        } else {
+            format!("{count} {word}s")
+        }
+    };
+    format!(
+        "Config problems: {}, {}",
+        plural(errors, "error"),
+        plural(warnings, "warning")
+    )
+}
+
+/// list the problems of the selected config
+pub fn create_diagnostics_panel(diagnostics: &[Diagnostic]) -> Element<'_, Message> {
+    let mut lines = column![text(diagnostics_heading(diagnostics)).size(14)].spacing(4);
+    for diagnostic in diagnostics {
+        lines = lines.push(
+            text(diagnostic.to_string())
+                .size(12)
+                .color(severity_color(diagnostic.severity)),
+        );
+    }
+
+    container(lines)
+        .padding(10)
+        .width(Fill)
+        .style(diagnostics_panel_style)
+        .into()
+}
+
+/// list the rules a swap breaks and let the user swap anyway or cancel
+pub fn create_swap_warning_panel(pending: &PendingSwap) -> Element<'_, Message> {
+    warning_panel(
+        "The swap breaks rules of the config:",
+        &pending.violations,
+        "Swap anyway",
+        Message::ConfirmSwap,
+        Message::CancelSwap,
+    )
+}
+
+/// list the rules a replacement breaks and let the user replace anyway or cancel
+pub fn create_replacement_warning_panel(pending: &PendingReplacement) -> Element<'_, Message> {
+    warning_panel(
+        "The replacement breaks rules of the config:",
+        &pending.violations,
+        "Replace anyway",
+        Message::ConfirmReplacement,
+        Message::CancelReplacement,
+    )
+}
+
+fn warning_panel<'a>(
+    heading: &'a str,
+    violations: &[Violation],
+    confirm_label: &'a str,
+    confirm: Message,
+    cancel: Message,
+) -> Element<'a, Message> {
+    let mut lines = column![text(heading).size(14)].spacing(4);
+    for violation in violations {
+        lines = lines.push(
+            text(violation.to_string())
+                .size(12)
+                .color(severity_color(Severity::Warning)),
+        );
+    }
+    lines = lines.push(
+        row![
+            button(text(confirm_label).size(14)).on_press(confirm),
+            button(text("Cancel").size(14))
+                .style(button::secondary)
+                .on_press(cancel)
+        ]
+        .spacing(5),
+    );
+
+    container(lines)
+        .padding(10)
+        .width(Fill)
+        .style(diagnostics_panel_style)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: Severity) -> Diagnostic {
+        Diagnostic {
+            severity,
+            path: "group[0].place".to_string(),
+            message: "place 'X' is not listed in places.places".to_string(),
+            span: None,
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_heading() {
+        assert_eq!(
+            diagnostics_heading(&[diagnostic(Severity::Error)]),
+            "Config problems: 1 error, 0 warnings"
+        );
+        assert_eq!(
+            diagnostics_heading(&[
+                diagnostic(Severity::Warning),
+                diagnostic(Severity::Error),
+                diagnostic(Severity::Warning)
+            ]),
+            "Config problems: 1 error, 2 warnings"
+        );
+    }
+
+    #[test]
+    fn test_severity_color() {
+        assert_ne!(
+            severity_color(Severity::Error),
+            severity_color(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn test_create_diagnostics_panel() {
+        let diagnostics = vec![diagnostic(Severity::Error), diagnostic(Severity::Warning)];
+        let _panel = create_diagnostics_panel(&diagnostics);
+    }
+
+    #[test]
+    fn test_create_swap_warning_panel() {
+        let pending = PendingSwap {
+            first: super::super::CellPosition { row: 1, column: 1 },
+            second: super::super::CellPosition { row: 1, column: 2 },
+            violations: vec![crate::schedule::Violation::DoubleBooking {
+                person: "Emily Smith".to_string(),
+                date: chrono::NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            }],
+        };
+        let _panel = create_swap_warning_panel(&pending);
+    }
+
+    #[test]
+    fn test_create_replacement_warning_panel() {
+        let pending = PendingReplacement {
+            position: super::super::CellPosition { row: 1, column: 1 },
+            person: "Emily Smith".to_string(),
+            violations: vec![crate::schedule::Violation::Unavailable {
+                person: "Emily Smith".to_string(),
+                date: chrono::NaiveDate::from_ymd_opt(2025, 9, 11).unwrap(),
+            }],
+        };
+        let _panel = create_replacement_warning_panel(&pending);
+    }
+}
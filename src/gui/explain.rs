@@ -0,0 +1,75 @@
+use chrono::NaiveDate;
+use iced::widget::{column, container, text};
+use iced::{Element, Fill};
+
+use super::Message;
+use super::diagnostics::diagnostics_panel_style;
+use crate::schedule::{Assignment, SlotTrace};
+
+/// the decision of the generator for a cell of the table
+///
+/// the cell shows the person with the icons of extra tasks, the trace knows the plain name.
+/// None if the cell was changed after the schedule was generated
+pub fn trace_for<'a>(
+    trace: &'a [SlotTrace],
+    assignments: &[Assignment],
+    date: NaiveDate,
+    place: &str,
+    person: &str,
+) -> Option<&'a SlotTrace> {
+    let base = assignments
+        .iter()
+        .find(|a| a.date == date && a.place == place && a.person == person)
+        .map(|a| a.base_person.as_str())?;
+    trace
+        .iter()
+        .find(|t| t.date == date && t.place == place && t.person == base)
+}
+
+/// explain the hovered cell, next to the schedule table
+pub fn create_trace_panel(trace: &SlotTrace) -> Element<'_, Message> {
+    let mut lines = trace.lines().into_iter();
+    let heading = lines.next().unwrap_or_default();
+    let mut content = column![text(heading).size(14)].spacing(4);
+    for line in lines {
+        content = content.push(text(line).size(12));
+    }
+
+    container(content)
+        .padding(10)
+        .width(Fill)
+        .style(diagnostics_panel_style)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_for_cell() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let assignments = vec![Assignment {
+            date,
+            place: "Place A".to_string(),
+            person: "Person1 🪴".to_string(),
+            base_person: "Person1".to_string(),
+        }];
+        let trace = vec![SlotTrace::fixed(
+            date,
+            "Place A".to_string(),
+            "Person1".to_string(),
+        )];
+
+        assert_eq!(
+            trace_for(&trace, &assignments, date, "Place A", "Person1 🪴"),
+            Some(&trace[0])
+        );
+        assert_eq!(
+            trace_for(&trace, &assignments, date, "Place A", "Person2"),
+            None
+        );
+
+        let _panel = create_trace_panel(&trace[0]);
+    }
+}
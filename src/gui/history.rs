@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+
+use crate::schedule::{Assignment, PersonState};
+
+/// the number of edits that can be undone
+const MAX_EDITS: usize = 100;
+
+/// the editable part of the application state
+///
+/// the people and the locks are stored with the assignments, so their statistics and the locked
+/// cells match again after an undo
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub assignments: Vec<Assignment>,
+    pub people: Vec<PersonState>,
+    pub highlighted_names: [Option<String>; 4],
+    /// locked cells as date, place and person
+    pub locked: HashSet<(NaiveDate, String, String)>,
+}
+
+/// undo and redo stacks of the manual edits of a schedule
+#[derive(Debug, Clone, Default)]
+pub struct EditHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl EditHistory {
+    /// remember the state before an edit, a new edit can not be redone anymore
+    pub fn record(&mut self, before: Snapshot) {
+        self.undo.push(before);
+        if self.undo.len() > MAX_EDITS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// the state before the last edit, `current` can be redone afterwards
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// the state after the last undone edit, `current` can be undone afterwards
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// forget all edits, e.g. after a new schedule was generated
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(name: &str) -> Snapshot {
+        Snapshot {
+            assignments: Vec::new(),
+            people: Vec::new(),
+            highlighted_names: [Some(name.to_string()), None, None, None],
+            locked: HashSet::new(),
+        }
+    }
+
+    fn name(snapshot: Option<Snapshot>) -> Option<String> {
+        snapshot.and_then(|s| s.highlighted_names[0].clone())
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut history = EditHistory::default();
+        assert!(!history.can_undo());
+        assert_eq!(name(history.undo(snapshot("now"))), None);
+
+        history.record(snapshot("first"));
+        history.record(snapshot("second"));
+
+        assert_eq!(name(history.undo(snapshot("third"))), Some("second".into()));
+        assert_eq!(name(history.undo(snapshot("second"))), Some("first".into()));
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+
+        assert_eq!(name(history.redo(snapshot("first"))), Some("second".into()));
+        assert_eq!(name(history.redo(snapshot("second"))), Some("third".into()));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_new_edit_drops_redo() {
+        let mut history = EditHistory::default();
+        history.record(snapshot("first"));
+        let _ = history.undo(snapshot("second"));
+
+        history.record(snapshot("first"));
+
+        assert!(!history.can_redo());
+        history.clear();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_history_is_limited() {
+        let mut history = EditHistory::default();
+        for i in 0..MAX_EDITS + 5 {
+            history.record(snapshot(&i.to_string()));
+        }
+
+        let mut undone = 0;
+        let mut last = None;
+        while let Some(previous) = history.undo(snapshot("now")) {
+            undone += 1;
+            last = Some(previous);
+        }
+
+        assert_eq!(undone, MAX_EDITS);
+        assert_eq!(name(last), Some("5".into()));
+    }
+}
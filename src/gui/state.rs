@@ -1,17 +1,45 @@
+use crate::config::Diagnostic;
+use crate::schedule::{
+    Assignment, OptimizeReport, PersonState, SlotTrace, UnfilledSlot, Violation, slot_columns,
+    slots_by_date,
+};
 use chrono::NaiveDate;
 use iced::Task;
-use std::collections::{BTreeMap, BTreeSet};
+use iced::keyboard::Modifiers;
+use std::collections::HashSet;
 
-use crate::schedule::{Assignment, PersonState};
-
-use super::assignment;
+use super::assignment::{self, Replacement};
+use super::history::{EditHistory, Snapshot};
+use super::utils::LoadedConfig;
 use super::{CellPosition, Message, Tab};
 
+/// two cells to swap and the rules the swap breaks
+#[derive(Debug, Clone)]
+pub struct PendingSwap {
+    pub first: CellPosition,
+    pub second: CellPosition,
+    pub violations: Vec<Violation>,
+}
+
+/// a person to put into a cell and the rules the replacement breaks
+#[derive(Debug, Clone)]
+pub struct PendingReplacement {
+    pub position: CellPosition,
+    pub person: String,
+    pub violations: Vec<Violation>,
+}
+
 /// Application state
 pub struct AppState {
     pub config_files: Vec<String>,
     pub selected_config: Option<String>,
+    /// the selected config with its history, loaded when it is selected or a schedule is created
+    pub config: Option<LoadedConfig>,
     pub assignments: Vec<Assignment>,
+    /// the slots of the generated schedule nobody could fill
+    pub unfilled: Vec<UnfilledSlot>,
+    /// why the generator chose each person, shown for the hovered cell
+    pub trace: Vec<SlotTrace>,
     pub people: Vec<PersonState>,
     pub error: Option<String>,
     pub success_message: Option<String>,
@@ -20,6 +48,34 @@ pub struct AppState {
     pub selected_cell: Option<CellPosition>,
     pub hovered_cell: Option<CellPosition>,
     pub highlighted_names: [Option<String>; 4],
+    /// locked cells as date, place and person, they stay when regenerating the unlocked ones
+    pub locked: HashSet<(NaiveDate, String, String)>,
+    /// the modifier keys currently held, Ctrl+click locks a cell
+    pub modifiers: Modifiers,
+    /// the manual edits of the current schedule that can be undone
+    pub history: EditHistory,
+    /// who can take over the selected cell, the most suitable first
+    pub replacements: Vec<Replacement>,
+    /// a swap that breaks rules of the config, waiting for the user to confirm it
+    pub pending_swap: Option<PendingSwap>,
+    /// a replacement that breaks rules of the config, waiting for the user to confirm it
+    pub pending_replacement: Option<PendingReplacement>,
+    /// the schedules saved for the selected config, the newest first
+    pub saved_schedules: Vec<String>,
+    pub seed_input: String,
+    pub seed: Option<u64>,
+    pub optimize_input: String,
+    pub optimization: Option<OptimizeReport>,
+    pub attempts_input: String,
+    /// finished and requested attempts while a schedule is generated
+    pub generation_progress: Option<(usize, usize)>,
+    /// fairness score of the current schedule, smaller is fairer
+    pub score: Option<usize>,
+    /// the absent person and the dates entered to re-plan the schedule
+    pub absence_person: Option<String>,
+    pub absence_from_input: String,
+    pub absence_to_input: String,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Default for AppState {
@@ -27,7 +83,10 @@ impl Default for AppState {
         Self {
             config_files: Vec::new(),
             selected_config: None,
+            config: None,
             assignments: Vec::new(),
+            unfilled: Vec::new(),
+            trace: Vec::new(),
             people: Vec::new(),
             error: None,
             success_message: None,
@@ -36,6 +95,24 @@ impl Default for AppState {
             selected_cell: None,
             hovered_cell: None,
             highlighted_names: [None, None, None, None],
+            locked: HashSet::new(),
+            modifiers: Modifiers::default(),
+            history: EditHistory::default(),
+            replacements: Vec::new(),
+            pending_swap: None,
+            pending_replacement: None,
+            saved_schedules: Vec::new(),
+            seed_input: String::new(),
+            seed: None,
+            optimize_input: String::new(),
+            optimization: None,
+            attempts_input: String::new(),
+            generation_progress: None,
+            score: None,
+            absence_person: None,
+            absence_from_input: String::new(),
+            absence_to_input: String::new(),
+            diagnostics: Vec::new(),
         }
     }
 }
@@ -46,7 +123,67 @@ impl AppState {
         Self::default()
     }
 
+    /// the assignments, people and highlights as they are now
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            assignments: self.assignments.clone(),
+            people: self.people.clone(),
+            highlighted_names: self.highlighted_names.clone(),
+            locked: self.locked.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.assignments = snapshot.assignments;
+        self.people = snapshot.people;
+        self.highlighted_names = snapshot.highlighted_names;
+        self.locked = snapshot.locked;
+        self.selected_cell = None;
+        self.replacements.clear();
+        self.pending_swap = None;
+        self.pending_replacement = None;
+    }
+
+    /// remember the current state before an edit, so the edit can be undone
+    pub fn record_edit(&mut self) {
+        let before = self.snapshot();
+        self.history.record(before);
+    }
+
+    /// revert the last edit, returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let current = self.snapshot();
+        match self.history.undo(current) {
+            Some(previous) => {
+                self.restore(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// apply the last undone edit again, returns false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let current = self.snapshot();
+        match self.history.redo(current) {
+            Some(next) => {
+                self.restore(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// highlight a person or remove the highlight, the change can be undone
     pub fn toggle_highlighted_name(&mut self, person: String) {
+        let is_highlighted = self
+            .highlighted_names
+            .iter()
+            .any(|p| p.as_deref() == Some(&person));
+        if is_highlighted || self.highlighted_names.iter().any(|p| p.is_none()) {
+            self.record_edit();
+        }
+
         if let Some(slot) = self
             .highlighted_names
             .iter()
@@ -61,41 +198,102 @@ impl AppState {
         }
     }
 
-    /// Handle a cell click
+    /// the lock of the assignment in a cell, unfilled cells can not be locked
+    fn lock_key(&self, position: CellPosition) -> Option<(NaiveDate, String, String)> {
+        let (date, place, person) = self.get_cell_info(position)?;
+        self.assignments
+            .iter()
+            .find(|a| a.date == date && a.place == place && a.person == person)
+            .filter(|a| !a.is_unfilled())
+            .map(|a| (date, place, a.base_person.clone()))
+    }
+
+    /// lock or unlock the assignment in a cell, the change can be undone
+    pub fn toggle_lock(&mut self, position: CellPosition) {
+        let Some(key) = self.lock_key(position) else {
+            return;
+        };
+        self.record_edit();
+        if !self.locked.remove(&key) {
+            self.locked.insert(key);
+        }
+    }
+
+    pub fn is_locked(&self, position: CellPosition) -> bool {
+        self.lock_key(position)
+            .is_some_and(|key| self.locked.contains(&key))
+    }
+
+    /// the assignments of the locked cells
+    pub fn locked_assignments(&self) -> Vec<Assignment> {
+        self.assignments
+            .iter()
+            .filter(|a| {
+                self.locked
+                    .contains(&(a.date, a.place.clone(), a.base_person.clone()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// drop the locks of assignments that are no longer in the schedule
+    pub fn retain_locks(&mut self) {
+        let current: HashSet<(NaiveDate, String, String)> = self
+            .assignments
+            .iter()
+            .map(|a| (a.date, a.place.clone(), a.base_person.clone()))
+            .collect();
+        self.locked.retain(|key| current.contains(key));
+    }
+
+    /// put another person into the selected cell, the change can be undone
+    pub fn replace_selected(&mut self, replacement: &str) {
+        let Some(position) = self.selected_cell.take() else {
+            return;
+        };
+        self.replacements.clear();
+        if self.is_locked(position) {
+            self.error = Some("Locked cells can not be changed, Ctrl+click to unlock".into());
+            return;
+        }
+        if let Some((date, place, person)) = self.get_cell_info(position) {
+            let before = self.snapshot();
+            if assignment::replace_person(
+                &mut self.assignments,
+                &mut self.people,
+                date,
+                &place,
+                &person,
+                replacement,
+            ) {
+                self.history.record(before);
+            }
+        }
+    }
+
+    /// Handle a cell click, a click with Ctrl locks or unlocks the cell
     pub fn handle_cell_click(&mut self, position: CellPosition) -> Task<Message> {
         // Don't allow selecting header row
         if position.row == 0 {
             return Task::none();
         }
 
+        if self.modifiers.command() {
+            self.selected_cell = None;
+            self.toggle_lock(position);
+            return Task::none();
+        }
+
         if let Some(prev_selected) = self.selected_cell.take() {
             // Second cell clicked - attempt to swap
             if prev_selected == position {
                 // Clicked same cell twice - deselect
                 Task::none()
-            } else if position.row > 0 && prev_selected.row > 0 {
-                // Get cell information
-                let cell1_info = self.get_cell_info(prev_selected);
-                let cell2_info = self.get_cell_info(position);
-
-                if let (Some((date1, place1, person1)), Some((date2, place2, person2))) =
-                    (cell1_info, cell2_info)
-                {
-                    // Swap the assignments and update person statistics
-                    assignment::swap_assignments(
-                        &mut self.assignments,
-                        &mut self.people,
-                        date1,
-                        &place1,
-                        &person1,
-                        date2,
-                        &place2,
-                        &person2,
-                    );
-                }
-
+            } else if self.is_locked(prev_selected) || self.is_locked(position) {
+                self.error = Some("Locked cells can not be swapped, Ctrl+click to unlock".into());
                 Task::none()
             } else {
+                self.swap_cells(prev_selected, position);
                 Task::none()
             }
         } else {
@@ -105,6 +303,52 @@ impl AppState {
         }
     }
 
+    /// swap the people of two cells and update the person statistics, the swap can be undone
+    pub fn swap_cells(&mut self, first: CellPosition, second: CellPosition) {
+        if let (Some((date1, place1, person1)), Some((date2, place2, person2))) =
+            (self.get_cell_info(first), self.get_cell_info(second))
+        {
+            let before = self.snapshot();
+            let swapped = assignment::swap_assignments(
+                &mut self.assignments,
+                &mut self.people,
+                date1,
+                &place1,
+                &person1,
+                date2,
+                &place2,
+                &person2,
+            );
+            if swapped {
+                self.history.record(before);
+            }
+        }
+    }
+
+    /// the indices of the assignments of two cells, as swapped by `swap_cells`
+    pub fn swap_indices(
+        &self,
+        first: CellPosition,
+        second: CellPosition,
+    ) -> Option<(usize, usize)> {
+        let find = |pos: CellPosition, skip: Option<usize>| {
+            let (date, place, person) = self.get_cell_info(pos)?;
+            self.assignments.iter().enumerate().position(|(idx, a)| {
+                Some(idx) != skip && a.date == date && a.place == place && a.person == person
+            })
+        };
+        let first = find(first, None)?;
+        Some((first, find(second, Some(first))?))
+    }
+
+    /// index of the assignment shown in a cell
+    pub fn slot_index(&self, pos: CellPosition) -> Option<usize> {
+        let (date, place, person) = self.get_cell_info(pos)?;
+        self.assignments
+            .iter()
+            .position(|a| a.date == date && a.place == place && a.person == person)
+    }
+
     /// Get information about a cell at the given position
     pub fn get_cell_info(&self, pos: CellPosition) -> Option<(NaiveDate, String, String)> {
         // Ignore header row and date column
@@ -112,29 +356,13 @@ impl AppState {
             return None;
         }
 
-        // First, organize assignments by date and place
-        let mut places: BTreeSet<String> = BTreeSet::new();
-        let mut dates: Vec<NaiveDate> = Vec::new();
-        let mut data: BTreeMap<NaiveDate, BTreeMap<String, String>> = BTreeMap::new();
-
-        // Extract all places and organize data by date and place
-        for assignment in &self.assignments {
-            places.insert(assignment.place.clone());
-            dates.push(assignment.date);
-            data.entry(assignment.date)
-                .or_default()
-                .insert(assignment.place.clone(), assignment.person.clone());
-        }
-
-        // Sort dates
-        dates.sort();
-        dates.dedup();
-
-        // Convert places to a vector for indexing
-        let places_vec: Vec<String> = places.into_iter().collect();
+        // First, organize assignments by date and place, one column per slot of a place
+        let columns = slot_columns(&self.assignments);
+        let data = slots_by_date(&self.assignments);
+        let dates: Vec<NaiveDate> = data.keys().copied().collect();
 
         // Check if position is valid
-        if pos.row > dates.len() || pos.column > places_vec.len() {
+        if pos.row > dates.len() || pos.column > columns.len() {
             return None;
         }
 
@@ -145,17 +373,18 @@ impl AppState {
             None => return None,
         };
 
-        // Get the place from the column index
-        let place: String = match places_vec.get(pos.column - 1) {
+        // Get the place and slot from the column index
+        let (place, slot) = match columns.get(pos.column - 1) {
             // -1 because column 0 is date
-            Some(place) => place.clone(),
+            Some(column) => column.clone(),
             None => return None,
         };
 
         // Get the person from the assignments
         let person: String = match data
             .get(&date)
-            .and_then(|row: &BTreeMap<String, String>| row.get(&place))
+            .and_then(|row| row.get(&place))
+            .and_then(|persons| persons.get(slot))
         {
             Some(person) => person.clone(),
             None => return None,
@@ -168,10 +397,7 @@ impl AppState {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schedule::GroupState;
     use chrono::NaiveDate;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     fn create_test_date(year: i32, month: u32, day: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(year, month, day).unwrap()
@@ -196,20 +422,9 @@ mod tests {
 
     #[allow(dead_code)]
     fn create_test_people() -> Vec<PersonState> {
-        let group_state1 = Rc::new(RefCell::new(GroupState::default()));
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
 
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state1),
-        );
-
-        let mut person2 = PersonState::new(
-            "Person2".to_string(),
-            "Place B".to_string(),
-            Rc::clone(&group_state2),
-        );
+        let mut person2 = PersonState::new("Person2".to_string(), "Place B".to_string(), 1);
 
         // Register initial services
         person1.register_service(create_test_date(2025, 9, 1), "Place A".to_string());
@@ -232,12 +447,22 @@ mod tests {
         assert!(state.selected_cell.is_none());
         assert!(state.hovered_cell.is_none());
         assert_eq!(state.highlighted_names, [None, None, None, None]);
+        assert!(state.seed_input.is_empty());
+        assert!(state.seed.is_none());
+        assert!(state.optimize_input.is_empty());
+        assert!(state.optimization.is_none());
+        assert!(state.attempts_input.is_empty());
+        assert!(state.generation_progress.is_none());
+        assert!(state.score.is_none());
+        assert!(state.diagnostics.is_empty());
     }
 
     #[test]
     fn test_get_cell_info_valid_position() {
-        let mut state = AppState::default();
-        state.assignments = create_test_assignments();
+        let state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
 
         // Test valid position
         let cell_info = state.get_cell_info(CellPosition { row: 1, column: 1 });
@@ -252,8 +477,10 @@ mod tests {
 
     #[test]
     fn test_get_cell_info_invalid_position() {
-        let mut state = AppState::default();
-        state.assignments = create_test_assignments();
+        let state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
 
         // Test header row (row 0)
         let cell_info = state.get_cell_info(CellPosition { row: 0, column: 1 });
@@ -273,8 +500,10 @@ mod tests {
 
     #[test]
     fn test_handle_cell_click_first_selection() {
-        let mut state = AppState::default();
-        state.assignments = create_test_assignments();
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
 
         // First click should select the cell
         let pos = CellPosition { row: 1, column: 1 };
@@ -285,8 +514,10 @@ mod tests {
 
     #[test]
     fn test_handle_cell_click_deselect() {
-        let mut state = AppState::default();
-        state.assignments = create_test_assignments();
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
 
         // First click selects the cell
         let pos = CellPosition { row: 1, column: 1 };
@@ -300,8 +531,10 @@ mod tests {
 
     #[test]
     fn test_handle_cell_click_header_row() {
-        let mut state = AppState::default();
-        state.assignments = create_test_assignments();
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
 
         // Clicking on header row should do nothing
         let pos = CellPosition { row: 0, column: 1 };
@@ -332,21 +565,10 @@ mod tests {
         ];
 
         // People must have matching services registered, otherwise swapping won't update stats.
-        let group_state1 = Rc::new(RefCell::new(GroupState::default()));
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state1),
-        );
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
         person1.register_service(date, "Place A".to_string());
 
-        let mut person2 = PersonState::new(
-            "Person2".to_string(),
-            "Place B".to_string(),
-            Rc::clone(&group_state2),
-        );
+        let mut person2 = PersonState::new("Person2".to_string(), "Place B".to_string(), 1);
         person2.register_service(date, "Place B".to_string());
 
         state.people = vec![person1, person2];
@@ -376,4 +598,191 @@ mod tests {
         assert_eq!(a.person, "Person2");
         assert_eq!(b.person, "Person1");
     }
+
+    #[test]
+    fn test_ctrl_click_toggles_lock() {
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            modifiers: Modifiers::COMMAND,
+            ..AppState::default()
+        };
+        let pos = CellPosition { row: 1, column: 1 };
+
+        let _ = state.handle_cell_click(pos);
+        assert!(state.is_locked(pos));
+        assert_eq!(state.selected_cell, None);
+        let locked = state.locked_assignments();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].person, "Person1");
+
+        let _ = state.handle_cell_click(pos);
+        assert!(!state.is_locked(pos));
+        assert!(state.locked_assignments().is_empty());
+    }
+
+    #[test]
+    fn test_locked_cells_are_not_swapped() {
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
+        let locked = CellPosition { row: 1, column: 1 };
+        state.toggle_lock(locked);
+
+        let _ = state.handle_cell_click(CellPosition { row: 2, column: 2 });
+        let _ = state.handle_cell_click(locked);
+
+        assert!(state.error.is_some());
+        assert_eq!(state.assignments[0].person, "Person1");
+        assert_eq!(state.assignments[1].person, "Person2");
+    }
+
+    #[test]
+    fn test_retain_locks_drops_moved_assignments() {
+        let mut state = AppState {
+            assignments: create_test_assignments(),
+            ..AppState::default()
+        };
+        state.toggle_lock(CellPosition { row: 1, column: 1 });
+        state.toggle_lock(CellPosition { row: 2, column: 2 });
+
+        state.assignments[0].person = "Person3".to_string();
+        state.assignments[0].base_person = "Person3".to_string();
+        state.retain_locks();
+
+        assert_eq!(state.locked.len(), 1);
+        assert!(state.is_locked(CellPosition { row: 2, column: 2 }));
+    }
+
+    fn create_swap_state() -> AppState {
+        let date = create_test_date(2025, 9, 1);
+        let mut state = AppState::default();
+        for (idx, place) in ["Place A", "Place B"].into_iter().enumerate() {
+            let name = format!("Person{}", idx + 1);
+            state.assignments.push(Assignment {
+                date,
+                place: place.to_string(),
+                person: name.clone(),
+                base_person: name.clone(),
+            });
+            let mut person = PersonState::new(name, place.to_string(), idx);
+            person.register_service(date, place.to_string());
+            state.people.push(person);
+        }
+        state
+    }
+
+    fn swap(state: &mut AppState) {
+        let _ = state.handle_cell_click(CellPosition { row: 1, column: 1 });
+        let _ = state.handle_cell_click(CellPosition { row: 1, column: 2 });
+    }
+
+    #[test]
+    fn test_undo_and_redo_a_swap() {
+        let mut state = create_swap_state();
+        assert!(!state.undo());
+
+        swap(&mut state);
+        assert_eq!(state.assignments[0].person, "Person2");
+        assert_eq!(state.people[0].different_place_services(), 1);
+
+        assert!(state.undo());
+        assert_eq!(state.assignments[0].person, "Person1");
+        assert_eq!(state.assignments[1].person, "Person2");
+        assert_eq!(state.people[0].different_place_services(), 0);
+        assert_eq!(state.people[0].total_services(), 1);
+
+        assert!(state.redo());
+        assert_eq!(state.assignments[0].person, "Person2");
+        assert_eq!(state.people[0].different_place_services(), 1);
+        assert!(!state.redo());
+    }
+
+    #[test]
+    fn test_undo_restores_locks() {
+        let mut state = create_swap_state();
+        let pos = CellPosition { row: 1, column: 1 };
+
+        state.toggle_lock(pos);
+        assert!(state.undo());
+        assert!(!state.is_locked(pos));
+        assert!(state.redo());
+        assert!(state.is_locked(pos));
+
+        // undoing the edits after a lock leaves no lock of a person who is not in the schedule
+        state.toggle_lock(pos);
+        swap(&mut state);
+        state.toggle_lock(pos);
+        assert!(state.is_locked(pos));
+        assert!(state.undo());
+        assert!(state.undo());
+        assert_eq!(state.assignments[0].person, "Person1");
+        assert!(!state.is_locked(pos));
+        assert!(state.locked.is_empty());
+    }
+
+    #[test]
+    fn test_undo_highlight() {
+        let mut state = create_swap_state();
+
+        state.toggle_highlighted_name("Person1".to_string());
+        state.toggle_highlighted_name("Person2".to_string());
+        assert!(state.undo());
+
+        assert_eq!(
+            state.highlighted_names,
+            [Some("Person1".to_string()), None, None, None]
+        );
+        assert!(state.undo());
+        assert_eq!(state.highlighted_names, [None, None, None, None]);
+    }
+
+    #[test]
+    fn test_replace_selected() {
+        let mut state = create_swap_state();
+        state.people.push(PersonState::new(
+            "Person3".to_string(),
+            "Place A".to_string(),
+            0,
+        ));
+        let _ = state.handle_cell_click(CellPosition { row: 1, column: 1 });
+
+        state.replace_selected("Person3");
+
+        assert_eq!(state.selected_cell, None);
+        assert_eq!(state.assignments[0].person, "Person3");
+        assert_eq!(state.people[0].total_services(), 0);
+        assert_eq!(state.people[2].total_services(), 1);
+
+        assert!(state.undo());
+        assert_eq!(state.assignments[0].person, "Person1");
+        assert_eq!(state.people[2].total_services(), 0);
+    }
+
+    #[test]
+    fn test_swap_indices() {
+        let date = create_test_date(2025, 9, 1);
+        let mut state = create_swap_state();
+        state.assignments.push(Assignment {
+            date,
+            place: "Place A".to_string(),
+            person: "Person1".to_string(),
+            base_person: "Person1".to_string(),
+        });
+
+        assert_eq!(
+            state.swap_indices(
+                CellPosition { row: 1, column: 1 },
+                CellPosition { row: 1, column: 3 }
+            ),
+            Some((0, 1))
+        );
+        assert_eq!(
+            state.swap_indices(
+                CellPosition { row: 1, column: 1 },
+                CellPosition { row: 5, column: 1 }
+            ),
+            None
+        );
+    }
 }
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
 use iced::widget::{column, container, mouse_area, row, text};
-use iced::{Element, FillPortion, Theme};
+use iced::{Element, Fill, FillPortion, Theme};
 
 use super::Message;
+use crate::extra_tasks::extra_tasks_of;
 use crate::schedule::{Assignment, PersonState};
+use crate::stats::ScheduleStats;
 
 // Container style functions
 pub fn summary_header_style(_theme: &Theme) -> container::Style {
@@ -64,25 +66,32 @@ pub(crate) fn extra_task_counts(
 ) -> HashMap<String, HashMap<String, usize>> {
     let mut result: HashMap<String, HashMap<String, usize>> = HashMap::new();
     for a in assignments {
-        if a.person == a.base_person {
-            continue;
-        }
-        // Icons are everything after base_person + space; guard in case of manual edits
-        let Some(rest) = a.person.strip_prefix(a.base_person.as_str()) else {
-            continue;
-        };
-        let suffix = rest.trim();
-        for icon in suffix.split_whitespace() {
+        for icon in extra_tasks_of(a) {
             *result
                 .entry(a.base_person.clone())
                 .or_default()
-                .entry(icon.to_string())
+                .entry(icon)
                 .or_default() += 1;
         }
     }
     result
 }
 
+/// Fairness metrics of the schedule, shown above the summary table
+pub fn create_stats_view<'a>(stats: &ScheduleStats) -> Element<'a, Message> {
+    let mut lines = column![
+        container(text("Fairness").size(14))
+            .padding(3)
+            .width(Fill)
+            .style(summary_header_style)
+    ]
+    .spacing(1);
+    for line in stats.lines() {
+        lines = lines.push(container(text(line).size(12)).padding(3));
+    }
+    lines.into()
+}
+
 /// Create a summary view from people states
 pub fn create_summary_view_from_people<'a>(
     people: &'a [PersonState],
@@ -108,6 +117,7 @@ pub fn create_summary_view_from_people<'a>(
             text("Weekday Stats").size(12).width(FillPortion(3)),
             text("Place Counts").size(12).width(FillPortion(3)),
             text("Different Place").size(12).width(FillPortion(1)),
+            text("Blocked Days").size(12).width(FillPortion(1)),
             text("Extra Tasks").size(12).width(FillPortion(2)),
         ])
         .padding(3)
@@ -120,6 +130,7 @@ pub fn create_summary_view_from_people<'a>(
         let person_name = format!("{} ({})", person.name(), person.place());
         let total = person.total_services().to_string();
         let different_place = person.different_place_services().to_string();
+        let blocked_days = person.blocked_days().to_string();
         let person_key = person.name();
 
         // Format weekday stats
@@ -158,6 +169,7 @@ pub fn create_summary_view_from_people<'a>(
             text(weekday_stats).size(12).width(FillPortion(2)),
             text(place_stats).size(12).width(FillPortion(3)),
             text(different_place).size(12).width(FillPortion(1)),
+            text(blocked_days).size(12).width(FillPortion(1)),
             text(extra_stats).size(12).width(FillPortion(2)),
         ])
         .padding(3);
@@ -187,36 +199,23 @@ pub fn create_summary_view_from_people<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schedule::GroupState;
     use chrono::NaiveDate;
     use iced::Background;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
     fn create_test_date(year: i32, month: u32, day: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(year, month, day).unwrap()
     }
 
     fn create_test_people() -> Vec<PersonState> {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-
         // Create a person with some services
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
 
         // Add some services on different days
         person1.register_service(create_test_date(2025, 9, 1), "Place A".to_string()); // Monday
         person1.register_service(create_test_date(2025, 9, 3), "Place B".to_string()); // Wednesday
 
         // Create another person with different services
-        let mut person2 = PersonState::new(
-            "Person2".to_string(),
-            "Place B".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person2 = PersonState::new("Person2".to_string(), "Place B".to_string(), 0);
 
         person2.register_service(create_test_date(2025, 9, 2), "Place A".to_string()); // Tuesday
 
@@ -304,7 +303,28 @@ mod tests {
 
         // We can't easily test the actual UI rendering, but we can ensure the function runs without panicking
         // and returns an Element
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
+    }
+
+    #[test]
+    fn test_create_stats_view() {
+        let stats = ScheduleStats {
+            total_spread: 1,
+            weekday_spread: vec![(chrono::Weekday::Mon, 1)],
+            place_spread: vec![("Place A".to_string(), 0)],
+            cross_place_spread: 0,
+            min_gap: Some(7),
+            mean_gap: Some(7.0),
+            group_clustering: 0,
+        };
+
+        let element = create_stats_view(&stats);
+
+        // heading and one line per metric
+        assert_eq!(
+            element.as_widget().children().len(),
+            1 + stats.lines().len()
+        );
     }
 
     #[test]
@@ -316,17 +336,12 @@ mod tests {
         let element = create_summary_view_from_people(&people, &[], &[None, None, None, None]);
 
         // Should still create headers even with no data
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
     }
 
     #[test]
     fn test_summary_view_with_multiple_places() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut person = PersonState::new(
-            "Alice".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person = PersonState::new("Alice".to_string(), "Place A".to_string(), 0);
 
         // Register services at multiple places
         person.register_service(create_test_date(2025, 9, 1), "Place A".to_string());
@@ -338,21 +353,16 @@ mod tests {
         let element = create_summary_view_from_people(&people, &[], &[None, None, None, None]);
 
         // Verify the element is created successfully
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
     }
 
     #[test]
     fn test_summary_view_with_many_people() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
         let mut people = Vec::new();
 
         // Create 5 people with various service patterns
         for i in 0..5 {
-            let mut person = PersonState::new(
-                format!("Person{}", i),
-                format!("Place{}", i),
-                Rc::clone(&group_state),
-            );
+            let mut person = PersonState::new(format!("Person{}", i), format!("Place{}", i), 0);
 
             // Each person has different number of services
             for j in 0..=i {
@@ -368,17 +378,12 @@ mod tests {
         let element = create_summary_view_from_people(&people, &[], &[None, None, None, None]);
 
         // Should have created element successfully
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
     }
 
     #[test]
     fn test_summary_view_verifies_place_counts() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut person = PersonState::new(
-            "Test Person".to_string(),
-            "Home".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person = PersonState::new("Test Person".to_string(), "Home".to_string(), 0);
 
         // Register multiple services to verify place_counts getter is called
         person.register_service(create_test_date(2025, 9, 1), "Home".to_string());
@@ -394,17 +399,12 @@ mod tests {
         let element = create_summary_view_from_people(&people, &[], &[None, None, None, None]);
 
         // Verify element is created
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
     }
 
     #[test]
     fn test_summary_view_with_special_characters_in_place_names() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut person = PersonState::new(
-            "Alice".to_string(),
-            "Place-A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person = PersonState::new("Alice".to_string(), "Place-A".to_string(), 0);
 
         // Test with special characters and spaces
         person.register_service(create_test_date(2025, 9, 1), "Place-A".to_string());
@@ -414,6 +414,6 @@ mod tests {
         let people = vec![person];
         let element = create_summary_view_from_people(&people, &[], &[None, None, None, None]);
 
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
     }
 }
@@ -1,17 +1,17 @@
 use chrono::NaiveDate;
-use iced::widget::{button, column, container, mouse_area, row, text};
+use iced::widget::{button, column, container, mouse_area, row, text, tooltip};
 use iced::{Color, Element, Fill, Theme};
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
 use super::{CellPosition, Message};
-use crate::schedule::Assignment;
+use crate::schedule::{Assignment, UnfilledSlot, slot_column_label, slot_columns, slots_by_date};
 
 /// Represents the state of the schedule table
 pub struct TableState {
     selected_cell: Option<CellPosition>,
-    data: BTreeMap<NaiveDate, BTreeMap<String, String>>,
+    data: BTreeMap<NaiveDate, BTreeMap<String, Vec<String>>>,
     dates: Vec<NaiveDate>,
-    places: BTreeSet<String>,
+    columns: Vec<(String, usize)>,
 }
 
 // Helper to create a colored button style with active/hovered backgrounds
@@ -68,28 +68,14 @@ pub fn highlighted_cell_button_style_blue(_theme: &Theme, status: button::Status
 impl TableState {
     /// Create a new TableState from assignments
     pub fn new(assignments: &[Assignment]) -> Self {
-        let mut places = BTreeSet::new();
-        let mut dates = Vec::new();
-        let mut data: BTreeMap<NaiveDate, BTreeMap<String, String>> = BTreeMap::new();
-
-        // Extract all places and organize data by date and place
-        for assignment in assignments {
-            places.insert(assignment.place.clone());
-            dates.push(assignment.date);
-            data.entry(assignment.date)
-                .or_default()
-                .insert(assignment.place.clone(), assignment.person.clone());
-        }
-
-        // Sort dates
-        dates.sort();
-        dates.dedup();
+        let data = slots_by_date(assignments);
+        let dates: Vec<NaiveDate> = data.keys().copied().collect();
 
         Self {
             selected_cell: None,
             data,
             dates,
-            places,
+            columns: slot_columns(assignments),
         }
     }
 
@@ -115,11 +101,8 @@ impl TableState {
             return None;
         }
 
-        // Convert places to a vector for indexing
-        let places_vec: Vec<String> = self.places.iter().cloned().collect();
-
         // Check if position is valid
-        if pos.row > self.dates.len() || pos.column > places_vec.len() {
+        if pos.row > self.dates.len() || pos.column > self.columns.len() {
             return None;
         }
 
@@ -130,10 +113,10 @@ impl TableState {
             None => return None,
         };
 
-        // Get the place from the column index
-        let place: String = match places_vec.get(pos.column - 1) {
+        // Get the place and slot from the column index
+        let (place, slot) = match self.columns.get(pos.column - 1) {
             // -1 because column 0 is date
-            Some(place) => place.clone(),
+            Some(column) => column.clone(),
             None => return None,
         };
 
@@ -141,7 +124,8 @@ impl TableState {
         let person: String = match self
             .data
             .get(&date)
-            .and_then(|row: &BTreeMap<String, String>| row.get(&place))
+            .and_then(|row| row.get(&place))
+            .and_then(|persons| persons.get(slot))
         {
             Some(person) => person.clone(),
             None => return None,
@@ -163,27 +147,23 @@ fn groupmate_dim_style(_theme: &Theme, _status: button::Status) -> button::Style
     }
 }
 
-/// Create a table view from assignments
+/// Create a table view from assignments, unfilled slots are shown as red cells
+///
+/// locked cells, given as date, place and person, get a lock and a border
 pub fn create_table_from_assignments<'a>(
     assignments: &'a [Assignment],
+    unfilled: &'a [UnfilledSlot],
     selected_cell: Option<&'a CellPosition>,
     _hovered_cell: Option<&'a CellPosition>,
     highlighted_names: &'a [Option<String>; 4],
     hovered_groupmates: &HashSet<String>,
+    locked: &HashSet<(NaiveDate, String, String)>,
 ) -> Element<'a, Message> {
     let mut rows = Vec::new();
 
-    // First, organize assignments by date and place
-    let mut places: BTreeSet<String> = BTreeSet::new();
-    let mut data: BTreeMap<NaiveDate, BTreeMap<String, String>> = BTreeMap::new();
-
-    // Extract all places and organize data by date and place
-    for assignment in assignments {
-        places.insert(assignment.place.clone());
-        data.entry(assignment.date)
-            .or_default()
-            .insert(assignment.place.clone(), assignment.person.clone());
-    }
+    // First, organize assignments by date and place, one column per slot of a place
+    let columns = slot_columns(assignments);
+    let data = slots_by_date(assignments);
 
     // Create header row with places
     let mut header_row = row![];
@@ -197,9 +177,9 @@ pub fn create_table_from_assignments<'a>(
     );
 
     // Add place column headers
-    for place in &places {
+    for (place, slot) in &columns {
         header_row = header_row.push(
-            container(text(place.clone()).size(12))
+            container(text(slot_column_label(place, *slot)).size(12))
                 .padding(3)
                 .width(Fill)
                 .style(header_style),
@@ -222,9 +202,14 @@ pub fn create_table_from_assignments<'a>(
                 .style(header_style),
         );
 
-        // Add person cells for each place
-        for (col_idx, place) in places.iter().enumerate() {
-            let person: String = assignments_for_date.get(place).cloned().unwrap_or_default();
+        // Add person cells for each slot of each place
+        for (col_idx, (place, slot)) in columns.iter().enumerate() {
+            let person: Option<&String> = assignments_for_date
+                .get(place)
+                .and_then(|persons| persons.get(*slot));
+            // an empty person is an unfilled slot, a missing one a slot the place does not have that day
+            let is_unfilled = person.is_some_and(|person| person.is_empty());
+            let person = person.cloned().unwrap_or_default();
 
             // Create cell position for clickable cells
             let cell_position = CellPosition {
@@ -252,16 +237,29 @@ pub fn create_table_from_assignments<'a>(
                 .map(|a| a.base_person.as_str())
                 .unwrap_or(person.as_str());
             let is_groupmate = !person.is_empty() && hovered_groupmates.contains(base);
+            let is_locked =
+                !person.is_empty() && locked.contains(&(*date, place.clone(), base.to_string()));
+            let label = if is_locked {
+                format!("🔒 {person}")
+            } else {
+                person.clone()
+            };
 
             // Create clickable cell with appropriate style
             let cell_btn = if is_selected {
-                button(text(person.clone()).size(12))
+                button(text(label.clone()).size(12))
                     .width(Fill)
                     .padding(3)
                     .on_press(Message::CellClicked(cell_position))
                     .style(button::primary)
+            } else if is_unfilled {
+                button(text(label.clone()).size(12))
+                    .width(Fill)
+                    .padding(3)
+                    .on_press(Message::CellClicked(cell_position))
+                    .style(unfilled_cell_button_style)
             } else if let Some(slot) = highlight_slot {
-                button(text(person.clone()).size(12))
+                button(text(label.clone()).size(12))
                     .width(Fill)
                     .padding(3)
                     .on_press(Message::CellClicked(cell_position))
@@ -274,14 +272,20 @@ pub fn create_table_from_assignments<'a>(
                         2 => highlighted_cell_button_style_green,
                         _ => highlighted_cell_button_style_blue,
                     })
+            } else if is_locked {
+                button(text(label.clone()).size(12))
+                    .width(Fill)
+                    .padding(3)
+                    .on_press(Message::CellClicked(cell_position))
+                    .style(locked_cell_button_style)
             } else if is_groupmate {
-                button(text(person.clone()).size(12))
+                button(text(label.clone()).size(12))
                     .width(Fill)
                     .padding(3)
                     .on_press(Message::CellClicked(cell_position))
                     .style(groupmate_dim_style)
             } else {
-                button(text(person.clone()).size(12))
+                button(text(label.clone()).size(12))
                     .width(Fill)
                     .padding(3)
                     .on_press(Message::CellClicked(cell_position))
@@ -294,7 +298,18 @@ pub fn create_table_from_assignments<'a>(
                 .on_right_press(Message::CellRightClicked(cell_position))
                 .on_exit(Message::MouseLeft);
 
-            row_content = row_content.push(cell_with_hover);
+            if is_unfilled {
+                let reason = unfilled_reason(unfilled, *date, place);
+                row_content = row_content.push(tooltip(
+                    cell_with_hover,
+                    container(text(reason).size(12))
+                        .padding(5)
+                        .style(container::rounded_box),
+                    tooltip::Position::Bottom,
+                ));
+            } else {
+                row_content = row_content.push(cell_with_hover);
+            }
         }
 
         // Add the data row
@@ -320,6 +335,35 @@ pub fn cell_button_style(_theme: &Theme, status: button::Status) -> button::Styl
     )(_theme, status)
 }
 
+/// why a slot is unfilled, for the tooltip of its red cell
+pub fn unfilled_reason(unfilled: &[UnfilledSlot], date: NaiveDate, place: &str) -> String {
+    unfilled
+        .iter()
+        .find(|slot| slot.date == date && slot.place == place)
+        .map(|slot| format!("Nobody left: {}", slot.reason()))
+        .unwrap_or_else(|| "Nobody assigned".to_string())
+}
+
+// Style function for unfilled cells (red)
+pub fn unfilled_cell_button_style(_theme: &Theme, status: button::Status) -> button::Style {
+    colored_button_style(
+        iced::Color::from_rgb(1.0, 0.6, 0.6),
+        iced::Color::from_rgb(0.95, 0.5, 0.5),
+    )(_theme, status)
+}
+
+// Style function for locked cells (dark border)
+pub fn locked_cell_button_style(_theme: &Theme, status: button::Status) -> button::Style {
+    button::Style {
+        border: iced::Border {
+            radius: 2.0.into(),
+            width: 2.0,
+            color: iced::Color::from_rgb(0.3, 0.3, 0.3),
+        },
+        ..cell_button_style(_theme, status)
+    }
+}
+
 // Style function for highlighted cells (light gray)
 pub fn highlighted_cell_button_style_gray(_theme: &Theme, status: button::Status) -> button::Style {
     colored_button_style(
@@ -390,12 +434,47 @@ mod tests {
 
         // Check that the table state was created correctly
         assert_eq!(table_state.dates.len(), 2);
-        assert_eq!(table_state.places.len(), 2);
-        assert!(table_state.places.contains("Place A"));
-        assert!(table_state.places.contains("Place B"));
+        assert_eq!(
+            table_state.columns,
+            vec![("Place A".to_string(), 0), ("Place B".to_string(), 0)]
+        );
         assert!(table_state.selected_cell.is_none());
     }
 
+    #[test]
+    fn test_get_cell_info_with_multiple_slots() {
+        let date = create_test_date(2025, 9, 1);
+        let mut assignments = create_test_assignments();
+        assignments.push(Assignment {
+            date,
+            place: "Place A".to_string(),
+            person: "Person3".to_string(),
+            base_person: "Person3".to_string(),
+        });
+        let table_state = TableState::new(&assignments);
+
+        // columns: Place A, Place A #2, Place B
+        assert_eq!(table_state.columns.len(), 3);
+        assert_eq!(
+            table_state.get_cell_info(CellPosition { row: 1, column: 2 }),
+            Some((date, "Place A".to_string(), "Person3".to_string()))
+        );
+        assert_eq!(
+            table_state.get_cell_info(CellPosition { row: 2, column: 3 }),
+            Some((
+                create_test_date(2025, 9, 2),
+                "Place B".to_string(),
+                "Person2".to_string()
+            ))
+        );
+        // second slot is empty on the second date
+        assert!(
+            table_state
+                .get_cell_info(CellPosition { row: 2, column: 2 })
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_table_state_select_cell_first_selection() {
         let assignments = create_test_assignments();
@@ -417,7 +496,7 @@ mod tests {
             selected_cell: None,
             data: BTreeMap::new(),
             dates: Vec::new(),
-            places: BTreeSet::new(),
+            columns: Vec::new(),
         };
 
         // Test selecting a cell
@@ -492,46 +571,110 @@ mod tests {
         // Create a table with no selection or hover
         let element = create_table_from_assignments(
             &assignments,
+            &[],
             None,
             None,
             &highlighted_names,
             &HashSet::new(),
+            &HashSet::new(),
         );
 
         // We can't easily test the actual UI rendering, but we can ensure the function runs without panicking
         // and returns an Element
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
 
         // Create a table with selection
         let selected_cell = Some(CellPosition { row: 1, column: 1 });
         let element = create_table_from_assignments(
             &assignments,
+            &[],
             selected_cell.as_ref(),
             None,
             &highlighted_names,
             &HashSet::new(),
+            &HashSet::new(),
         );
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
 
         // Create a table with hover
         let hovered_cell = Some(CellPosition { row: 1, column: 1 });
         let element = create_table_from_assignments(
             &assignments,
+            &[],
             None,
             hovered_cell.as_ref(),
             &highlighted_names,
             &HashSet::new(),
+            &HashSet::new(),
         );
-        assert!(element.as_widget().children().len() > 0);
+        assert!(!element.as_widget().children().is_empty());
 
         let highlighted_names = [Some("Person1".to_string()), None, None, None];
         let element = create_table_from_assignments(
             &assignments,
+            &[],
             None,
             None,
             &highlighted_names,
             &HashSet::new(),
+            &HashSet::new(),
+        );
+        assert!(!element.as_widget().children().is_empty());
+    }
+
+    #[test]
+    fn test_unfilled_slots_get_a_reason() {
+        let date = create_test_date(2025, 9, 1);
+        let mut assignments = create_test_assignments();
+        assignments.push(Assignment::unfilled(date, "Place B".to_string()));
+        let unfilled = vec![UnfilledSlot {
+            date,
+            place: "Place B".to_string(),
+            excluded: vec![(
+                "Person1".to_string(),
+                crate::schedule::Exclusion::AlreadyAssigned,
+            )],
+        }];
+
+        assert_eq!(
+            unfilled_reason(&unfilled, date, "Place B"),
+            "Nobody left: already assigned: Person1"
         );
-        assert!(element.as_widget().children().len() > 0);
+        assert_eq!(unfilled_reason(&[], date, "Place B"), "Nobody assigned");
+
+        let element = create_table_from_assignments(
+            &assignments,
+            &unfilled,
+            None,
+            None,
+            &[None, None, None, None],
+            &HashSet::new(),
+            &HashSet::new(),
+        );
+        assert!(!element.as_widget().children().is_empty());
+    }
+
+    #[test]
+    fn test_locked_cells_are_rendered() {
+        let assignments = create_test_assignments();
+        let locked = HashSet::from([(
+            create_test_date(2025, 9, 1),
+            "Place A".to_string(),
+            "Person1".to_string(),
+        )]);
+
+        let element = create_table_from_assignments(
+            &assignments,
+            &[],
+            None,
+            None,
+            &[None, None, None, None],
+            &HashSet::new(),
+            &locked,
+        );
+        assert!(!element.as_widget().children().is_empty());
+
+        let style = locked_cell_button_style(&Theme::Light, button::Status::Active);
+        assert_eq!(style.border.width, 2.0);
     }
 }
@@ -7,7 +7,7 @@ fn test_select_cell_behavior() {
         selected_cell: None,
         data: BTreeMap::new(),
         dates: Vec::new(),
-        places: BTreeSet::new(),
+        columns: Vec::new(),
     };
     
     // Test selecting a cell
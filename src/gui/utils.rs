@@ -1,22 +1,299 @@
 use std::fs::File;
 use std::io::Write;
 
-use crate::config::load_config;
+use chrono::NaiveDate;
+
+use crate::config::{Config, Fixed, load_config};
 use crate::dates::get_weekdays;
-use crate::schedule::{Assignment, create_schedule};
+use crate::document::ScheduleDocument;
+use crate::history::{config_history_paths, load_history};
+use crate::ics::{roster_id, write_ics_dir};
+use crate::schedule::{
+    Absence, Assignment, OptimizeBudget, OptimizeReport, ScheduleOptions, SlotTrace, UnfilledSlot,
+    create_best_schedule, lock_assignments, optimize_schedule, random_seed, retain_assigned,
+};
+use crate::stats::ScheduleStats;
+
+/// A generated schedule together with the seed that created it
+#[derive(Debug, Clone)]
+pub struct GeneratedSchedule {
+    pub assignments: Vec<Assignment>,
+    /// the slots nobody could fill
+    pub unfilled: Vec<UnfilledSlot>,
+    /// why each person was chosen by the generator
+    pub trace: Vec<SlotTrace>,
+    pub seed: u64,
+    /// `ScheduleStats::score` of the final schedule
+    pub score: usize,
+    /// result of the optimisation pass, if one was requested
+    pub optimization: Option<OptimizeReport>,
+    /// fixed assignments of the config that a re-plan left out
+    pub dropped_fixed: Vec<Fixed>,
+}
+
+/// a config with the options to create and edit its schedules
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// the history of the config, the rules count its services
+    pub options: ScheduleOptions,
+}
+
+/// load a config with its history files
+pub fn load_config_with_history(config_path: &str) -> Result<LoadedConfig, String> {
+    let config = load_config(config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let history_paths = config_history_paths(config_path, &config);
+    let history = load_history(&history_paths, &config)
+        .map_err(|e| format!("Failed to load history: {e}"))?;
+    Ok(LoadedConfig {
+        config,
+        options: ScheduleOptions {
+            history,
+            ..Default::default()
+        },
+    })
+}
+
+/// the config with the seed to use, its schedule dates and its history
+fn load_schedule_input(
+    config_path: &str,
+    seed: Option<u64>,
+) -> Result<(Config, Vec<NaiveDate>, ScheduleOptions), String> {
+    let LoadedConfig {
+        mut config,
+        mut options,
+    } = load_config_with_history(config_path)?;
+    let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+    config.rules.seed = Some(seed.or(config.rules.seed).unwrap_or_else(random_seed));
+    options.explain = true;
+    Ok((config, dates, options))
+}
 
 /// Generate a schedule from a config file
-pub async fn generate_schedule(config_path: String) -> Result<Vec<Assignment>, String> {
-    match load_config(&config_path) {
-        Ok(config) => {
-            let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
-            let (assignments, _) = create_schedule(&dates, &config);
-            Ok(assignments)
+///
+/// The given seed overrides the seed of the config, a random seed is used if neither is set.
+/// The history files of the config are taken into account.
+/// With several attempts the fairest schedule wins, `progress` gets the number of finished attempts.
+/// With a budget, the schedule is improved by the optimisation pass afterwards.
+/// The locked assignments are kept, only the other slots are generated.
+///
+/// This blocks until the schedule is ready, run it on a blocking thread
+pub fn generate_schedule(
+    config_path: &str,
+    seed: Option<u64>,
+    budget: Option<OptimizeBudget>,
+    attempts: usize,
+    locked: &[Assignment],
+    progress: &(dyn Fn(usize) + Sync),
+) -> Result<GeneratedSchedule, String> {
+    let (config, dates, options) = load_schedule_input(config_path, seed)?;
+    let mut config = lock_assignments(&config, locked);
+    let best = create_best_schedule(&dates, &config, &options, attempts, progress)
+        .map_err(|e| format!("Failed to create schedule: {e}"))?;
+    config.rules.seed = Some(best.seed);
+    let mut assignments = best.assignments;
+    let optimization = match budget {
+        Some(budget) => Some(
+            optimize_schedule(&dates, &config, &options, &mut assignments, &budget)
+                .map_err(|e| format!("Failed to optimize schedule: {e}"))?,
+        ),
+        None => None,
+    };
+    let mut trace = best.trace;
+    retain_assigned(&mut trace, &assignments);
+    Ok(GeneratedSchedule {
+        score: ScheduleStats::new(&config, &assignments).score(),
+        assignments,
+        unfilled: best.unfilled,
+        trace,
+        seed: best.seed,
+        optimization,
+        dropped_fixed: Vec::new(),
+    })
+}
+
+/// Re-plan the current schedule from the start of an absence on
+///
+/// The assignments before the absence and the locked ones stay, see `schedule::replan_schedule`.
+/// This blocks until the schedule is ready, run it on a blocking thread
+pub fn replan_schedule(
+    config_path: &str,
+    seed: Option<u64>,
+    assignments: &[Assignment],
+    locked: &[Assignment],
+    absence: &Absence,
+) -> Result<GeneratedSchedule, String> {
+    let (config, dates, options) = load_schedule_input(config_path, seed)?;
+    let replanned =
+        crate::schedule::replan_schedule(&dates, &config, &options, assignments, locked, absence)
+            .map_err(|e| format!("Failed to re-plan schedule: {e}"))?;
+    let schedule = replanned.schedule;
+    Ok(GeneratedSchedule {
+        score: ScheduleStats::new(&config, &schedule.assignments).score(),
+        assignments: schedule.assignments,
+        unfilled: schedule.unfilled,
+        trace: schedule.trace,
+        seed: config.rules.seed.unwrap_or_default(),
+        optimization: None,
+        dropped_fixed: replanned.dropped,
+    })
+}
+
+/// Write the schedule as iCalendar files, one for everyone and one per person, into
+/// `calendar_dir` of the config, returns the directory
+pub fn export_calendar(config_path: &str, assignments: &[Assignment]) -> Result<String, String> {
+    let config = load_config(config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let dir = crate::gui::calendar_dir(config_path);
+    write_ics_dir(
+        std::path::Path::new(&dir),
+        &roster_id(std::path::Path::new(config_path)),
+        assignments,
+        &config.places.shifts,
+        true,
+    )
+    .map_err(|e| format!("Failed to write calendar: {e}"))?;
+    Ok(dir)
+}
+
+/// A schedule read back from a saved CSV file or JSON document
+#[derive(Debug, Clone)]
+pub struct OpenedSchedule {
+    pub path: String,
+    pub assignments: Vec<Assignment>,
+    /// the seed stored in the summary of the file, if any
+    pub seed: Option<u64>,
+    /// true if a JSON document was saved with another version of the config
+    pub config_changed: bool,
+}
+
+/// Read a schedule saved by the GUI or the CLI, the config recovers the extra task icons of a CSV
+pub fn open_schedule(config_path: &str, path: &str) -> Result<OpenedSchedule, String> {
+    let config_source =
+        std::fs::read_to_string(config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+
+    if path.ends_with(".json") {
+        let document = ScheduleDocument::from_json(&content)
+            .map_err(|e| format!("Failed to parse {path}: {e}"))?;
+        return Ok(OpenedSchedule {
+            path: path.to_string(),
+            assignments: document.assignments(),
+            seed: document.seed,
+            config_changed: !document.matches_config(&config_source),
+        });
+    }
+
+    let config = load_config(config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let assignments = crate::csv::csv_to_assignments(&content, &config)
+        .map_err(|e| format!("Failed to parse {path}: {e}"))?;
+    let seed = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("seed: "))
+        .and_then(|seed| seed.trim().parse().ok());
+    Ok(OpenedSchedule {
+        path: path.to_string(),
+        assignments,
+        seed,
+        config_changed: false,
+    })
+}
+
+/// Write the schedule as JSON document for the given config
+pub async fn save_document(
+    config_path: String,
+    filename: String,
+    assignments: Vec<Assignment>,
+    seed: Option<u64>,
+) -> Result<(), String> {
+    let config_source =
+        std::fs::read_to_string(&config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let config = load_config(&config_path).map_err(|e| format!("Failed to load config: {e}"))?;
+    let json = ScheduleDocument::new(&config_source, &config, seed, &assignments)
+        .to_json()
+        .map_err(|e| format!("Failed to create JSON: {e}"))?;
+    std::fs::write(&filename, json).map_err(|e| format!("Failed to write file: {e}"))
+}
+
+/// Parse the absence entered by the user, an empty end date means a single day
+pub fn parse_absence(person: Option<&str>, from: &str, to: &str) -> Result<Absence, String> {
+    let person = person.ok_or("Select the absent person")?;
+    let date = |input: &str| {
+        input
+            .trim()
+            .parse::<NaiveDate>()
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", input.trim()))
+    };
+    let from = date(from)?;
+    let to = if to.trim().is_empty() {
+        from
+    } else {
+        date(to)?
+    };
+    Ok(Absence {
+        person: person.to_string(),
+        from,
+        to,
+    })
+}
+
+/// Parse the number of attempts entered by the user, an empty input means a single attempt
+pub fn parse_attempts(input: &str) -> Result<usize, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(1);
+    }
+    match input.parse::<usize>() {
+        Ok(attempts) if attempts > 0 => Ok(attempts),
+        _ => Err(format!(
+            "Invalid number of attempts '{input}', expected a number greater than 0"
+        )),
+    }
+}
+
+/// Parse the optimisation budget entered by the user, an empty input means no optimisation
+///
+/// A number is the number of moves to try, a number with an `s` suffix is a time limit in seconds
+pub fn parse_budget(input: &str) -> Result<Option<OptimizeBudget>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || {
+        format!(
+            "Invalid optimization budget '{input}', expected moves like 2000 or seconds like 5s"
+        )
+    };
+    match input.strip_suffix('s') {
+        Some(seconds) => {
+            let seconds = seconds.trim().parse::<u64>().map_err(|_| invalid())?;
+            Ok(Some(OptimizeBudget {
+                iterations: usize::MAX,
+                time: Some(std::time::Duration::from_secs(seconds)),
+            }))
+        }
+        None => {
+            let iterations = input.parse::<usize>().map_err(|_| invalid())?;
+            Ok(Some(OptimizeBudget {
+                iterations,
+                time: None,
+            }))
         }
-        Err(e) => Err(format!("Failed to load config: {e}")),
     }
 }
 
+/// Parse the seed entered by the user, an empty input means no seed
+pub fn parse_seed(input: &str) -> Result<Option<u64>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    input
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| format!("Invalid seed '{input}', expected a positive number"))
+}
+
 /// Save schedule and summary to a file
 pub async fn save_file(
     filename: String,
@@ -54,8 +331,8 @@ mod tests {
     use std::io;
     use tempfile::NamedTempFile;
 
-    #[tokio::test]
-    async fn test_generate_schedule_success() {
+    #[test]
+    fn test_generate_schedule_success() {
         // Create a test config file
         let temp_dir = tempfile::tempdir().unwrap();
         let config_path = temp_dir.path().join("test_config.toml");
@@ -83,19 +360,295 @@ mod tests {
         std::fs::write(&config_path, config_content).unwrap();
 
         // Test the function
-        let result = generate_schedule(config_path.to_string_lossy().to_string()).await;
+        let config_path = config_path.to_string_lossy().to_string();
+        let result = generate_schedule(&config_path, None, None, 1, &[], &|_| {});
         assert!(result.is_ok());
-        let assignments = result.unwrap();
-        assert!(!assignments.is_empty());
+        let generated = result.unwrap();
+        assert!(!generated.assignments.is_empty());
+        assert_eq!(generated.trace.len(), generated.assignments.len());
+        // the only person can not fill both places on a date
+        assert_eq!(generated.unfilled.len(), generated.assignments.len() / 2);
+
+        // The same seed creates the same schedule again
+        let again =
+            generate_schedule(&config_path, Some(generated.seed), None, 1, &[], &|_| {}).unwrap();
+        assert_eq!(again.seed, generated.seed);
+        let people = |g: &GeneratedSchedule| -> Vec<String> {
+            g.assignments.iter().map(|a| a.person.clone()).collect()
+        };
+        assert_eq!(people(&again), people(&generated));
     }
 
-    #[tokio::test]
-    async fn test_generate_schedule_invalid_config() {
+    #[test]
+    fn test_generate_schedule_invalid_config() {
         // Test with non-existent config file
-        let result = generate_schedule("non_existent_config.toml".to_string()).await;
+        let result = generate_schedule("non_existent_config.toml", None, None, 1, &[], &|_| {});
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_generate_schedule_with_optimization() {
+        let config_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let budget = parse_budget("200").unwrap();
+        let generated = generate_schedule(
+            config_path.to_str().unwrap(),
+            Some(3),
+            budget,
+            1,
+            &[],
+            &|_| {},
+        )
+        .unwrap();
+
+        let report = generated.optimization.unwrap();
+        assert_eq!(report.iterations, 200);
+        assert!(report.after <= report.before);
+    }
+
+    #[test]
+    fn test_load_config_with_history() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("roster.toml");
+        let config = std::fs::read_to_string(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml"),
+        )
+        .unwrap();
+        std::fs::write(
+            &config_path,
+            format!("history = [\"august.csv\"]\n{config}"),
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("august.csv"),
+            "date,Office,Transport\n2025-08-28,Emily Smith,Olivia Jones\n",
+        )
+        .unwrap();
+
+        let loaded = load_config_with_history(&config_path.to_string_lossy()).unwrap();
+
+        assert_eq!(loaded.config.places.places, vec!["Office", "Transport"]);
+        let history: Vec<&str> = loaded
+            .options
+            .history
+            .iter()
+            .map(|a| a.base_person.as_str())
+            .collect();
+        assert_eq!(history, vec!["Emily Smith", "Olivia Jones"]);
+        assert!(load_config_with_history("missing.toml").is_err());
+    }
+
+    #[test]
+    fn test_generate_schedule_with_attempts() {
+        let config_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let finished = std::sync::Mutex::new(Vec::new());
+        let generated =
+            generate_schedule(config_path.to_str().unwrap(), Some(3), None, 5, &[], &|n| {
+                finished.lock().unwrap().push(n)
+            })
+            .unwrap();
+
+        let mut finished = finished.into_inner().unwrap();
+        finished.sort();
+        assert_eq!(finished, vec![1, 2, 3, 4, 5]);
+
+        // the winning seed creates the winner again in a single attempt
+        let again = generate_schedule(
+            config_path.to_str().unwrap(),
+            Some(generated.seed),
+            None,
+            1,
+            &[],
+            &|_| {},
+        )
+        .unwrap();
+        assert_eq!(again.score, generated.score);
+    }
+
+    #[test]
+    fn test_generate_schedule_keeps_locked_assignments() {
+        let config_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let config_path = config_path.to_string_lossy().to_string();
+        let generated = generate_schedule(&config_path, Some(3), None, 1, &[], &|_| {}).unwrap();
+        let locked = vec![
+            generated.assignments[0].clone(),
+            generated.assignments[5].clone(),
+        ];
+
+        let regenerated =
+            generate_schedule(&config_path, Some(4), None, 3, &locked, &|_| {}).unwrap();
+
+        assert_eq!(regenerated.assignments.len(), generated.assignments.len());
+        for a in &locked {
+            assert!(
+                regenerated.assignments.iter().any(|b| b.date == a.date
+                    && b.place == a.place
+                    && b.base_person == a.base_person)
+            );
+        }
+    }
+
+    #[test]
+    fn test_replan_schedule() {
+        let config_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let config_path = config_path.to_string_lossy().to_string();
+        let generated = generate_schedule(&config_path, Some(3), None, 1, &[], &|_| {}).unwrap();
+        let absence = parse_absence(Some("Olivia Jones"), "2025-09-11", "").unwrap();
+
+        let replanned =
+            replan_schedule(&config_path, Some(3), &generated.assignments, &[], &absence).unwrap();
+
+        assert_eq!(replanned.seed, 3);
+        assert_eq!(replanned.assignments.len(), generated.assignments.len());
+        assert!(
+            !replanned
+                .assignments
+                .iter()
+                .any(|a| a.base_person == "Olivia Jones" && a.date == absence.from)
+        );
+
+        let unknown = Absence {
+            person: "Nobody".to_string(),
+            ..absence
+        };
+        assert!(
+            replan_schedule(&config_path, Some(3), &generated.assignments, &[], &unknown).is_err()
+        );
+    }
+
+    #[test]
+    fn test_open_schedule() {
+        let config_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let config_path = config_path.to_string_lossy().to_string();
+        let generated = generate_schedule(&config_path, Some(3), None, 1, &[], &|_| {}).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("schedule.csv");
+        let csv = crate::csv::assignments_to_csv(&generated.assignments).unwrap();
+        std::fs::write(&path, format!("{csv}\nseed: 3\nEmily Smith, total: 2\n")).unwrap();
+
+        let opened = open_schedule(&config_path, &path.to_string_lossy()).unwrap();
+
+        assert_eq!(opened.seed, Some(3));
+        let cells = |assignments: &[Assignment]| -> Vec<(NaiveDate, String, String)> {
+            let mut cells: Vec<_> = assignments
+                .iter()
+                .map(|a| (a.date, a.place.clone(), a.person.clone()))
+                .collect();
+            cells.sort();
+            cells
+        };
+        assert_eq!(cells(&opened.assignments), cells(&generated.assignments));
+
+        assert!(open_schedule(&config_path, "missing.csv").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_and_open_document() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("roster.toml");
+        std::fs::copy(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml"),
+            &config_path,
+        )
+        .unwrap();
+        let config_path = config_path.to_string_lossy().to_string();
+        let generated = generate_schedule(&config_path, Some(3), None, 1, &[], &|_| {}).unwrap();
+        let path = temp_dir.path().join("roster.json");
+        let path = path.to_string_lossy().to_string();
+
+        save_document(
+            config_path.clone(),
+            path.clone(),
+            generated.assignments.clone(),
+            Some(3),
+        )
+        .await
+        .unwrap();
+        let opened = open_schedule(&config_path, &path).unwrap();
+
+        assert_eq!(opened.seed, Some(3));
+        assert!(!opened.config_changed);
+        assert_eq!(opened.assignments.len(), generated.assignments.len());
+        assert_eq!(
+            opened.assignments[0].person,
+            generated.assignments[0].person
+        );
+
+        let source = std::fs::read_to_string(&config_path).unwrap();
+        std::fs::write(&config_path, format!("{source}\n# changed\n")).unwrap();
+        assert!(open_schedule(&config_path, &path).unwrap().config_changed);
+    }
+
+    #[test]
+    fn test_export_calendar() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("roster.toml");
+        std::fs::copy(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml"),
+            &config_path,
+        )
+        .unwrap();
+        let config_path = config_path.to_string_lossy().to_string();
+        let generated = generate_schedule(&config_path, Some(3), None, 1, &[], &|_| {}).unwrap();
+
+        let dir = export_calendar(&config_path, &generated.assignments).unwrap();
+
+        let dir = std::path::Path::new(&dir);
+        assert_eq!(dir, temp_dir.path().join("roster_calendar"));
+        assert!(dir.join(crate::ics::COMBINED_FILE).is_file());
+        let person = &generated.assignments[0].base_person;
+        assert!(
+            dir.join(format!("{}.ics", person.to_lowercase().replace(' ', "-")))
+                .is_file()
+        );
+    }
+
+    #[test]
+    fn test_parse_absence() {
+        let absence = parse_absence(Some("Emily"), " 2025-09-10 ", "2025-09-12").unwrap();
+        assert_eq!(absence.person, "Emily");
+        assert_eq!(absence.from, NaiveDate::from_ymd_opt(2025, 9, 10).unwrap());
+        assert_eq!(absence.to, NaiveDate::from_ymd_opt(2025, 9, 12).unwrap());
+
+        let single_day = parse_absence(Some("Emily"), "2025-09-10", "").unwrap();
+        assert_eq!(single_day.from, single_day.to);
+
+        assert!(parse_absence(None, "2025-09-10", "").is_err());
+        assert!(parse_absence(Some("Emily"), "10.09.2025", "").is_err());
+    }
+
+    #[test]
+    fn test_parse_attempts() {
+        assert_eq!(parse_attempts(""), Ok(1));
+        assert_eq!(parse_attempts(" 20 "), Ok(20));
+        assert!(parse_attempts("0").is_err());
+        assert!(parse_attempts("many").is_err());
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        assert_eq!(parse_budget(""), Ok(None));
+        assert_eq!(parse_budget(" 500 ").unwrap().unwrap().iterations, 500);
+        let timed = parse_budget("5s").unwrap().unwrap();
+        assert_eq!(timed.time, Some(std::time::Duration::from_secs(5)));
+        assert_eq!(timed.iterations, usize::MAX);
+        assert!(parse_budget("fast").is_err());
+        assert!(parse_budget("-5s").is_err());
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed(""), Ok(None));
+        assert_eq!(parse_seed("  "), Ok(None));
+        assert_eq!(parse_seed(" 42 "), Ok(Some(42)));
+        assert!(parse_seed("abc").is_err());
+        assert!(parse_seed("-1").is_err());
+    }
+
     #[tokio::test]
     async fn test_save_file_success() {
         // Create temporary file for testing
@@ -147,7 +700,7 @@ mod tests {
         fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
             self.calls += 1;
             if self.calls == self.fail_on_call && !buf.is_empty() {
-                return Err(io::Error::new(io::ErrorKind::Other, "write failed"));
+                return Err(io::Error::other("write failed"));
             }
             Ok(buf.len())
         }
@@ -0,0 +1,209 @@
+//! read previous schedules to carry fairness over into the next period
+//!
+//! three formats are understood:
+//! * a schedule csv as written by `assignments_to_csv`, everything after the first empty line is ignored
+//! * a history file with the header `date,place,person` and one service per line
+//! * a JSON schedule document, see `document::ScheduleDocument`
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::config::Config;
+use crate::csv::csv_to_assignments;
+use crate::document::ScheduleDocument;
+use crate::extra_tasks::strip_extra_tasks;
+use crate::schedule::Assignment;
+
+/// parse a previous schedule or history file into assignments
+///
+/// icons of extra tasks are removed from `base_person`
+pub fn parse_history(content: &str, config: &Config) -> Result<Vec<Assignment>, Box<dyn Error>> {
+    if content.trim_start().starts_with('{') {
+        let document = ScheduleDocument::from_json(content)?;
+        return Ok(document
+            .assignments()
+            .into_iter()
+            .filter(|a| !a.is_unfilled())
+            .collect());
+    }
+
+    let header: Vec<&str> = content
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .collect();
+    if header != ["date", "place", "person"] {
+        let assignments = csv_to_assignments(content, config)?;
+        return Ok(assignments
+            .into_iter()
+            .filter(|a| !a.is_unfilled())
+            .collect());
+    }
+
+    let table = content
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .double_quote(false)
+        .escape(Some(b'\\'))
+        .from_reader(table.as_bytes());
+
+    let mut assignments = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let date = record.get(0).unwrap_or_default().trim();
+        let date: NaiveDate = date
+            .parse()
+            .map_err(|e| format!("invalid date '{date}': {e}"))?;
+        let place = record.get(1).unwrap_or_default().trim();
+        let person = record.get(2).unwrap_or_default().trim();
+        if !person.is_empty() {
+            assignments.push(Assignment {
+                date,
+                place: place.to_string(),
+                person: person.to_string(),
+                base_person: strip_extra_tasks(person, config),
+            });
+        }
+    }
+
+    Ok(assignments)
+}
+
+/// read and parse all given history files
+pub fn load_history(paths: &[PathBuf], config: &Config) -> Result<Vec<Assignment>, Box<dyn Error>> {
+    let mut assignments = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read history {}: {e}", path.display()))?;
+        let history = parse_history(&content, config)
+            .map_err(|e| format!("could not parse history {}: {e}", path.display()))?;
+        assignments.extend(history);
+    }
+    Ok(assignments)
+}
+
+/// the history files of a config, relative paths are resolved against the directory of the config file
+pub fn config_history_paths(config_path: &str, config: &Config) -> Vec<PathBuf> {
+    let dir = Path::new(config_path).parent().unwrap_or(Path::new(""));
+    config.history.iter().map(|path| dir.join(path)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    fn config() -> Config {
+        let mut config = test_config();
+        config.history = vec!["previous.csv".into()];
+        config
+    }
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    #[test]
+    fn parse_history_reads_schedule_csv_and_ignores_summary() {
+        let content = "\
+date,Office,Transport,Transport #2
+2025-09-04,Emily Smith 🪴,Olivia Jones,Daniel Brown
+2025-09-11,William Smith,Sophie Brown,
+
+seed: 42
+Emily Smith, total: 1, Thu: 1, different_place: 0
+";
+        let history = parse_history(content, &config()).unwrap();
+
+        let services: Vec<(NaiveDate, &str, &str)> = history
+            .iter()
+            .map(|a| (a.date, a.place.as_str(), a.base_person.as_str()))
+            .collect();
+        assert_eq!(
+            services,
+            vec![
+                (d(4), "Office", "Emily Smith"),
+                (d(4), "Transport", "Olivia Jones"),
+                (d(4), "Transport", "Daniel Brown"),
+                (d(11), "Office", "William Smith"),
+                (d(11), "Transport", "Sophie Brown"),
+            ]
+        );
+        assert_eq!(history[0].person, "Emily Smith 🪴");
+    }
+
+    #[test]
+    fn parse_history_reads_one_service_per_line() {
+        let content = "\
+date,place,person
+2025-09-04,Office,Emily Smith
+2025-09-11,Transport,Emily Smith 🪴
+
+seed: 42
+";
+        let history = parse_history(content, &config()).unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].date, d(11));
+        assert_eq!(history[1].place, "Transport");
+        assert_eq!(history[1].base_person, "Emily Smith");
+    }
+
+    #[test]
+    fn parse_history_reads_schedule_documents() {
+        let assignments = vec![
+            Assignment {
+                date: d(4),
+                place: "Office".to_string(),
+                person: "Emily Smith 🪴".to_string(),
+                base_person: "Emily Smith".to_string(),
+            },
+            Assignment::unfilled(d(11), "Office".to_string()),
+        ];
+        let json = ScheduleDocument::new("", &config(), Some(42), &assignments)
+            .to_json()
+            .unwrap();
+
+        let history = parse_history(&json, &config()).unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].base_person, "Emily Smith");
+        assert_eq!(history[0].person, "Emily Smith 🪴");
+    }
+
+    #[test]
+    fn parse_history_reports_invalid_content() {
+        assert!(parse_history("person,date\nEmily,2025-09-04\n", &config()).is_err());
+        assert!(parse_history("date,Office\nyesterday,Emily\n", &config()).is_err());
+    }
+
+    #[test]
+    fn load_history_resolves_paths_relative_to_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("previous.csv"),
+            "date,Office\n2025-09-04,Emily Smith\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("config.toml");
+        let config = config();
+
+        let paths = config_history_paths(config_path.to_str().unwrap(), &config);
+        assert_eq!(paths, vec![dir.path().join("previous.csv")]);
+
+        let history = load_history(&paths, &config).unwrap();
+        assert_eq!(history.len(), 1);
+
+        let missing = load_history(&[dir.path().join("missing.csv")], &config);
+        assert!(missing.unwrap_err().to_string().contains("missing.csv"));
+    }
+}
@@ -0,0 +1,1055 @@
+//! export assignments as iCalendar (RFC 5545) files for phone and desktop calendars and read the
+//! days of calendar files, e.g. holidays, back as date ranges
+//!
+//! every slot gets a UID from its roster, date, place and slot number. Importing a new version of
+//! the schedule updates the events instead of adding them again, even if the person of a slot
+//! changed. The roster, see `roster_id`, keeps the events of several configs apart
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday,
+};
+
+use crate::config::{CalendarFile, DateRange, Shift};
+use crate::schedule::Assignment;
+
+/// name of the file with all assignments written by `write_ics_dir`
+pub const COMBINED_FILE: &str = "schedule.ics";
+
+/// an assignment together with its stable UID
+struct Event<'a> {
+    uid: String,
+    assignment: &'a Assignment,
+}
+
+/// the roster of a config file for the UIDs, the file name without extension
+///
+/// e.g. "choir" for `rosters/choir.toml`
+pub fn roster_id(config_path: &Path) -> String {
+    config_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// all assignments of the schedule as one calendar
+///
+/// unfilled slots are left out. Places with a shift get events with times, all others all-day
+/// events. `roster` is part of the UIDs, `stamp` is the time of the export
+pub fn assignments_to_ics(
+    roster: &str,
+    assignments: &[Assignment],
+    shifts: &HashMap<String, Shift>,
+    stamp: DateTime<Utc>,
+) -> String {
+    calendar("Duty roster", &events(roster, assignments), shifts, stamp)
+}
+
+/// the assignments of one person (full name without extra task icons) as a calendar
+pub fn person_to_ics(
+    roster: &str,
+    assignments: &[Assignment],
+    person: &str,
+    shifts: &HashMap<String, Shift>,
+    stamp: DateTime<Utc>,
+) -> String {
+    let events: Vec<Event> = events(roster, assignments)
+        .into_iter()
+        .filter(|event| event.assignment.base_person == person)
+        .collect();
+    calendar(&format!("Duty roster {person}"), &events, shifts, stamp)
+}
+
+/// write `schedule.ics` with all assignments into `dir` and, if `per_person` is set, one file
+/// per person named after the person, e.g. `emily-smith.ics`
+///
+/// people whose names give the same file name get a number, e.g. `emily-smith-2.ics`. The
+/// directory is created if needed, the written files are returned
+pub fn write_ics_dir(
+    dir: &Path,
+    roster: &str,
+    assignments: &[Assignment],
+    shifts: &HashMap<String, Shift>,
+    per_person: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    let stamp = Utc::now();
+
+    let path = dir.join(COMBINED_FILE);
+    fs::write(
+        &path,
+        assignments_to_ics(roster, assignments, shifts, stamp),
+    )?;
+    let mut written = vec![path];
+
+    if per_person {
+        let mut people: Vec<&str> = assignments
+            .iter()
+            .filter(|a| !a.is_unfilled())
+            .map(|a| a.base_person.as_str())
+            .collect();
+        people.sort();
+        people.dedup();
+        let mut names: HashSet<String> = HashSet::from([COMBINED_FILE.to_string()]);
+        for person in people {
+            let base = slug(person);
+            let mut name = format!("{base}.ics");
+            let mut n = 1;
+            while names.contains(&name) {
+                n += 1;
+                name = format!("{base}-{n}.ics");
+            }
+            let path = dir.join(&name);
+            names.insert(name);
+            fs::write(
+                &path,
+                person_to_ics(roster, assignments, person, shifts, stamp),
+            )?;
+            written.push(path);
+        }
+    }
+
+    Ok(written)
+}
+
+/// the filled assignments with their UIDs, the n-th slot of a place on a date has number n
+fn events<'a>(roster: &str, assignments: &'a [Assignment]) -> Vec<Event<'a>> {
+    let roster = slug(roster);
+    let mut slots: HashMap<(NaiveDate, &str), usize> = HashMap::new();
+    let mut events = Vec::new();
+    for assignment in assignments {
+        let slot = slots
+            .entry((assignment.date, assignment.place.as_str()))
+            .or_default();
+        if !assignment.is_unfilled() {
+            events.push(Event {
+                uid: format!(
+                    "{roster}-{}-{}-{}@duty-roster",
+                    assignment.date.format("%Y%m%d"),
+                    slug(&assignment.place),
+                    slot
+                ),
+                assignment,
+            });
+        }
+        *slot += 1;
+    }
+    events
+}
+
+fn calendar(
+    name: &str,
+    events: &[Event],
+    shifts: &HashMap<String, Shift>,
+    stamp: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//duty-roster//duty-roster//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    for event in events {
+        let assignment = event.assignment;
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        match shifts.get(&assignment.place) {
+            Some(shift) => {
+                let start = assignment.date.and_time(shift.start);
+                let mut end = assignment.date.and_time(shift.end);
+                if shift.end <= shift.start {
+                    end = end + Days::new(1);
+                }
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            }
+            None => {
+                let end = assignment.date + Days::new(1);
+                lines.push(format!(
+                    "DTSTART;VALUE=DATE:{}",
+                    assignment.date.format("%Y%m%d")
+                ));
+                lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+            }
+        }
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape(&format!("{}: {}", assignment.place, assignment.person))
+        ));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// the start and end of an event as written in the file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Time {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Time {
+    fn date(self) -> NaiveDate {
+        match self {
+            Time::Date(date) => date,
+            Time::DateTime(time) => time.date(),
+        }
+    }
+
+    /// the same time `days` later
+    fn shifted(self, days: Days) -> Time {
+        match self {
+            Time::Date(date) => Time::Date(date + days),
+            Time::DateTime(time) => Time::DateTime(time + days),
+        }
+    }
+}
+
+/// the properties of a VEVENT needed to find its days
+#[derive(Debug, Clone, Default)]
+struct ParsedEvent {
+    summary: String,
+    categories: Vec<String>,
+    start: Option<Time>,
+    end: Option<Time>,
+    duration: Option<Duration>,
+    rrule: Option<String>,
+    /// dates of EXDATE, occurrences on these dates are left out
+    exdates: Vec<NaiveDate>,
+}
+
+impl ParsedEvent {
+    /// true if the event passes the SUMMARY and CATEGORIES filters of the calendar
+    fn matches(&self, calendar: &CalendarFile) -> bool {
+        let summary = self.summary.to_lowercase();
+        let summary_matches = calendar.summary.is_empty()
+            || calendar
+                .summary
+                .iter()
+                .any(|text| summary.contains(&text.to_lowercase()));
+        let category_matches = calendar.categories.is_empty()
+            || calendar.categories.iter().any(|wanted| {
+                self.categories
+                    .iter()
+                    .any(|category| category.eq_ignore_ascii_case(wanted.trim()))
+            });
+        summary_matches && category_matches
+    }
+
+    /// the days covered by an all-day event or an event of at least 24 hours
+    fn days(&self) -> Option<DateRange> {
+        match self.start? {
+            Time::Date(from) => {
+                // DTEND of an all-day event is the day after its last day
+                let end = match (self.end, self.duration) {
+                    (Some(Time::Date(end)), _) => end,
+                    (Some(Time::DateTime(end)), _) => end.date(),
+                    (None, Some(duration)) => from + Days::new(duration.num_days().max(1) as u64),
+                    (None, None) => from + Days::new(1),
+                };
+                let to = end.pred_opt()?.max(from);
+                Some(DateRange { from, to })
+            }
+            Time::DateTime(start) => {
+                let end = match (self.end, self.duration) {
+                    (Some(Time::DateTime(end)), _) => end,
+                    (Some(Time::Date(end)), _) => end.and_time(NaiveTime::MIN),
+                    (None, Some(duration)) => start + duration,
+                    (None, None) => start,
+                };
+                if end - start < Duration::days(1) {
+                    return None;
+                }
+                // an event ending at midnight does not cover the following day
+                let to = (end - Duration::seconds(1)).date();
+                Some(DateRange {
+                    from: start.date(),
+                    to,
+                })
+            }
+        }
+    }
+
+    /// the days of the event, a recurring event with all its occurrences that overlap `window`
+    ///
+    /// a recurrence that can not be expanded counts with its first occurrence
+    fn occurrences(&self, window: &DateRange) -> Vec<DateRange> {
+        let (Some(recurrence), Some(start)) = (self.recurrence(), self.start) else {
+            return self.days().into_iter().collect();
+        };
+        let first = start.date();
+        let last = recurrence
+            .until
+            .map_or(window.to, |until| until.min(window.to));
+
+        let mut ranges = Vec::new();
+        let mut count = 0;
+        for n in 0.. {
+            let Some(period) = recurrence.period(first, n).filter(|period| *period <= last) else {
+                break;
+            };
+            // e.g. the 31st in a month with 30 days, these are no occurrence
+            let Some(date) = recurrence.date_in(period, first) else {
+                continue;
+            };
+            if date > last || recurrence.count.is_some_and(|max| count >= max) {
+                break;
+            }
+            count += 1;
+            if self.exdates.contains(&date) {
+                continue;
+            }
+            let offset = Days::new((date - first).num_days() as u64);
+            let occurrence = ParsedEvent {
+                start: Some(start.shifted(offset)),
+                end: self.end.map(|end| end.shifted(offset)),
+                ..self.clone()
+            };
+            ranges.extend(
+                occurrence
+                    .days()
+                    .filter(|days| days.to >= window.from && days.from <= window.to),
+            );
+        }
+        ranges
+    }
+
+    /// the RRULE of the event, None without one or if it can not be expanded
+    fn recurrence(&self) -> Option<Recurrence> {
+        Recurrence::parse(self.rrule.as_deref()?, self.start?.date())
+    }
+}
+
+/// how often an event repeats
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// the parts of an RRULE that can be expanded: a frequency with interval, count and end
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    /// last possible date, included
+    until: Option<NaiveDate>,
+}
+
+impl Recurrence {
+    /// the recurrence of an RRULE value, None for other frequencies and for BY... parts that
+    /// select other days than the one of `start`
+    fn parse(rule: &str, start: NaiveDate) -> Option<Recurrence> {
+        let mut frequency = None;
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+        };
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=')?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => recurrence.count = Some(value.parse().ok()?),
+                "UNTIL" => recurrence.until = Some(parse_time(value).ok()?.date()),
+                "WKST" => {}
+                // exporters often repeat the day of the start, e.g. BYMONTH=10;BYMONTHDAY=3
+                "BYMONTH" if value.parse() == Ok(start.month()) => {}
+                "BYMONTHDAY" if value.parse() == Ok(start.day()) => {}
+                "BYDAY" if parse_weekday(value) == Some(start.weekday()) => {}
+                _ => return None,
+            }
+        }
+        recurrence.frequency = frequency?;
+        Some(recurrence)
+    }
+
+    /// the start of the n-th period: the day for daily and weekly, the first of the month else
+    fn period(&self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let step = n.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_days(Days::new(step.into())),
+            Frequency::Weekly => start.checked_add_days(Days::new(u64::from(step) * 7)),
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(step)),
+            Frequency::Yearly => start
+                .with_day(1)?
+                .checked_add_months(Months::new(step.checked_mul(12)?)),
+        }
+    }
+
+    /// the occurrence in the period, None if the month has no such day
+    fn date_in(&self, period: NaiveDate, start: NaiveDate) -> Option<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily | Frequency::Weekly => Some(period),
+            Frequency::Monthly | Frequency::Yearly => period.with_day(start.day()),
+        }
+    }
+}
+
+/// a weekday of BYDAY like TH, None for several days or days with a number like 1SU
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    const DAYS: [(&str, Weekday); 7] = [
+        ("MO", Weekday::Mon),
+        ("TU", Weekday::Tue),
+        ("WE", Weekday::Wed),
+        ("TH", Weekday::Thu),
+        ("FR", Weekday::Fri),
+        ("SA", Weekday::Sat),
+        ("SU", Weekday::Sun),
+    ];
+    DAYS.iter()
+        .find(|(code, _)| value.eq_ignore_ascii_case(code))
+        .map(|(_, weekday)| *weekday)
+}
+
+/// the days of the all-day and multi-day events of an iCalendar file that pass the filters of
+/// `calendar`
+///
+/// times are taken as written, time zones are not converted. Recurring events count with their
+/// occurrences that overlap `window`, see `unsupported_recurrences` for the rules that can not
+/// be expanded
+pub fn calendar_ranges(
+    content: &str,
+    calendar: &CalendarFile,
+    window: &DateRange,
+) -> Result<Vec<DateRange>, Box<dyn Error>> {
+    Ok(parse_events(content)?
+        .iter()
+        .filter(|event| event.matches(calendar))
+        .flat_map(|event| event.occurrences(window))
+        .collect())
+}
+
+/// the summaries of the matching events whose RRULE can not be expanded, they only count with
+/// their first occurrence
+///
+/// daily, weekly, monthly and yearly rules with INTERVAL, COUNT, UNTIL and EXDATE are expanded
+pub fn unsupported_recurrences(
+    content: &str,
+    calendar: &CalendarFile,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(parse_events(content)?
+        .into_iter()
+        .filter(|event| event.matches(calendar))
+        .filter(|event| event.rrule.is_some() && event.recurrence().is_none())
+        .map(|event| event.summary)
+        .collect())
+}
+
+fn parse_events(content: &str) -> Result<Vec<ParsedEvent>, Box<dyn Error>> {
+    if !content.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err("not an iCalendar file, BEGIN:VCALENDAR is missing".into());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<ParsedEvent> = None;
+    // components inside an event, e.g. VALARM, have their own properties
+    let mut nested = 0;
+    for line in unfold(content) {
+        let Some((name, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(ParsedEvent::default()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", "VEVENT") if nested == 0 => events.extend(current.take()),
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                let Some(event) = current.as_mut().filter(|_| nested == 0) else {
+                    continue;
+                };
+                match name.as_str() {
+                    "SUMMARY" => event.summary = unescape(value),
+                    "CATEGORIES" => event
+                        .categories
+                        .extend(split_list(value).iter().map(|c| unescape(c.trim()))),
+                    "DTSTART" => event.start = Some(parse_time(value)?),
+                    "DTEND" => event.end = Some(parse_time(value)?),
+                    "DURATION" => event.duration = Some(parse_duration(value)?),
+                    "RRULE" => event.rrule = Some(value.to_string()),
+                    "EXDATE" => {
+                        for date in value.split(',') {
+                            event.exdates.push(parse_time(date)?.date());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// content lines with the folded continuation lines joined
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// name (upper case) and value of a content line, the parameters are dropped
+fn split_property(line: &str) -> Option<(String, &str)> {
+    // the value starts at the first colon outside of a quoted parameter value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let name = head.split(';').next().unwrap_or(head);
+    Some((name.trim().to_ascii_uppercase(), value.trim_end()))
+}
+
+/// a DATE or DATE-TIME value, the VALUE parameter is not needed and the time zone is ignored
+fn parse_time(value: &str) -> Result<Time, Box<dyn Error>> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(Time::Date(date));
+    }
+    let value = value.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map(Time::DateTime)
+        .map_err(|e| format!("invalid date '{value}': {e}").into())
+}
+
+/// a DURATION value like P3D, P1W or PT36H
+fn parse_duration(value: &str) -> Result<Duration, Box<dyn Error>> {
+    let invalid = || format!("invalid duration '{value}'");
+    let rest = value.trim().trim_start_matches('+');
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            _ => {
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                duration += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return Err(invalid().into()),
+                };
+            }
+        }
+    }
+    Ok(duration)
+}
+
+/// the items of a comma separated list, escaped commas stay in the item
+fn split_list(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ',' if !escaped => {
+                items.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    items.push(&value[start..]);
+    items
+}
+
+/// undo `escape`
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// escape a TEXT value
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// end a content line with CRLF, lines longer than 75 octets continue on lines starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// lowercase letters and digits of a name, everything else becomes a single dash
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "unnamed".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+    use tempfile::TempDir;
+
+    fn assignment(day: u32, place: &str, person: &str) -> Assignment {
+        Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        }
+    }
+
+    fn stamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 8, 20, 9, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_all_day_events() {
+        let mut with_task = assignment(4, "Place A", "Emily Smith 🪴");
+        with_task.base_person = "Emily Smith".to_string();
+        let assignments = vec![
+            with_task,
+            assignment(4, "Place B", "Olivia Jones"),
+            Assignment::unfilled(
+                NaiveDate::from_ymd_opt(2025, 9, 5).unwrap(),
+                "Place A".into(),
+            ),
+        ];
+
+        let ics = assignments_to_ics("roster", &assignments, &HashMap::new(), stamp());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains(
+            "BEGIN:VEVENT\r\n\
+             UID:roster-20250904-place-a-0@duty-roster\r\n\
+             DTSTAMP:20250820T093000Z\r\n\
+             DTSTART;VALUE=DATE:20250904\r\n\
+             DTEND;VALUE=DATE:20250905\r\n\
+             SUMMARY:Place A: Emily Smith 🪴\r\n"
+        ));
+        assert!(ics.contains("UID:roster-20250904-place-b-0@duty-roster\r\n"));
+    }
+
+    #[test]
+    fn test_shift_times() {
+        let shifts = HashMap::from([(
+            "Place B".to_string(),
+            Shift {
+                start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            },
+        )]);
+
+        let ics = assignments_to_ics(
+            "roster",
+            &[assignment(30, "Place B", "Olivia Jones")],
+            &shifts,
+            stamp(),
+        );
+
+        assert!(ics.contains("DTSTART:20250930T220000\r\nDTEND:20251001T060000\r\n"));
+    }
+
+    #[test]
+    fn test_uid_is_stable_when_the_person_changes() {
+        let before = vec![
+            assignment(4, "Place B", "Olivia Jones"),
+            assignment(4, "Place B", "Liam Jones"),
+        ];
+        let after = vec![
+            assignment(4, "Place B", "Olivia Jones"),
+            assignment(4, "Place B", "Noah Brown"),
+        ];
+
+        let uids = |assignments: &[Assignment]| -> Vec<String> {
+            events("Choir", assignments)
+                .into_iter()
+                .map(|e| e.uid)
+                .collect()
+        };
+
+        assert_eq!(
+            uids(&before),
+            vec![
+                "choir-20250904-place-b-0@duty-roster",
+                "choir-20250904-place-b-1@duty-roster"
+            ]
+        );
+        assert_eq!(uids(&before), uids(&after));
+        // the same slot of another roster is another event
+        assert_eq!(
+            events("Band", &before)[0].uid,
+            "band-20250904-place-b-0@duty-roster"
+        );
+    }
+
+    #[test]
+    fn test_person_calendar_contains_only_their_services() {
+        let assignments = vec![
+            assignment(4, "Place A", "Emily Smith"),
+            assignment(5, "Place A", "William Smith"),
+            assignment(11, "Place A", "Emily Smith"),
+        ];
+
+        let ics = person_to_ics(
+            "roster",
+            &assignments,
+            "Emily Smith",
+            &HashMap::new(),
+            stamp(),
+        );
+
+        assert!(ics.contains("X-WR-CALNAME:Duty roster Emily Smith\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(!ics.contains("William"));
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+
+        let line = format!("SUMMARY:{}", "ä".repeat(50));
+        let folded = fold(&line);
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines.concat().replacen(' ', "", 1), line);
+    }
+
+    const HOLIDAYS: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+SUMMARY:Autumn holidays\r
+CATEGORIES:School,Holiday\r
+DTSTART;VALUE=DATE:20251027\r
+DTEND;VALUE=DATE:20251101\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:German Unity \r
+ Day\r
+CATEGORIES:Public\r
+DTSTART;VALUE=DATE:20251003\r
+BEGIN:VALARM\r
+SUMMARY:Reminder\r
+DTSTART:20251002T090000\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Team meeting\r
+DTSTART;TZID=\"Europe/Berlin\":20251008T100000\r
+DTEND;TZID=\"Europe/Berlin\":20251008T110000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Team trip\\, Berlin\r
+DTSTART:20251014T080000Z\r
+DURATION:P2DT10H\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn range(from: (u32, u32), to: (u32, u32)) -> DateRange {
+        DateRange {
+            from: NaiveDate::from_ymd_opt(2025, from.0, from.1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2025, to.0, to.1).unwrap(),
+        }
+    }
+
+    fn window() -> DateRange {
+        range((1, 1), (12, 31))
+    }
+
+    fn filtered(summary: &[&str], categories: &[&str]) -> CalendarFile {
+        CalendarFile {
+            path: "holidays.ics".to_string(),
+            summary: summary.iter().map(|s| s.to_string()).collect(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_calendar_ranges() {
+        let ranges = calendar_ranges(HOLIDAYS, &filtered(&[], &[]), &window()).unwrap();
+
+        // the one hour meeting is not a whole day
+        assert_eq!(
+            ranges,
+            vec![
+                range((10, 27), (10, 31)),
+                range((10, 3), (10, 3)),
+                range((10, 14), (10, 16)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calendar_ranges_filters_summary_and_categories() {
+        let ranges = |summary: &[&str], categories: &[&str]| {
+            calendar_ranges(HOLIDAYS, &filtered(summary, categories), &window()).unwrap()
+        };
+
+        assert_eq!(ranges(&[], &["holiday"]), vec![range((10, 27), (10, 31))]);
+        assert_eq!(ranges(&["unity day"], &[]), vec![range((10, 3), (10, 3))]);
+        assert_eq!(
+            ranges(&["trip, berlin"], &[]),
+            vec![range((10, 14), (10, 16))]
+        );
+        assert!(ranges(&["unity"], &["School"]).is_empty());
+    }
+
+    fn recurring(rule: &str, extra: &str) -> Vec<DateRange> {
+        let content = format!(
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Holiday\nDTSTART;VALUE=DATE:20231003\n\
+             DTEND;VALUE=DATE:20231005\nRRULE:{rule}\n{extra}END:VEVENT\nEND:VCALENDAR\n"
+        );
+        calendar_ranges(&content, &CalendarFile::default(), &window()).unwrap()
+    }
+
+    #[test]
+    fn test_recurring_events_within_the_window() {
+        // the two day holiday of each year, 2023 and 2024 are before the window
+        assert_eq!(recurring("FREQ=YEARLY", ""), vec![range((10, 3), (10, 4))]);
+        assert_eq!(
+            recurring("FREQ=YEARLY;BYMONTH=10;BYMONTHDAY=3", ""),
+            vec![range((10, 3), (10, 4))]
+        );
+        assert!(recurring("FREQ=YEARLY;COUNT=2", "").is_empty());
+        assert!(recurring("FREQ=YEARLY;UNTIL=20241231", "").is_empty());
+        assert!(recurring("FREQ=YEARLY", "EXDATE;VALUE=DATE:20251003\n").is_empty());
+
+        let weekly = recurring("FREQ=WEEKLY;INTERVAL=2;UNTIL=20250101T000000Z", "");
+        assert!(weekly.is_empty());
+        let daily = recurring("FREQ=DAILY;INTERVAL=100", "");
+        assert_eq!(
+            daily,
+            vec![
+                range((2, 14), (2, 15)),
+                range((5, 25), (5, 26)),
+                range((9, 2), (9, 3)),
+                range((12, 11), (12, 12)),
+            ]
+        );
+        assert_eq!(
+            recurring("FREQ=MONTHLY;INTERVAL=5", ""),
+            vec![
+                range((1, 3), (1, 4)),
+                range((6, 3), (6, 4)),
+                range((11, 3), (11, 4))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_days_of_month_are_skipped() {
+        let content = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;VALUE=DATE:20250131\n\
+                       RRULE:FREQ=MONTHLY;COUNT=3\nEND:VEVENT\nEND:VCALENDAR\n";
+
+        let ranges = calendar_ranges(content, &CalendarFile::default(), &window()).unwrap();
+
+        assert_eq!(
+            ranges,
+            vec![
+                range((1, 31), (1, 31)),
+                range((3, 31), (3, 31)),
+                range((5, 31), (5, 31))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_recurrences() {
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\nSUMMARY:Easter\nDTSTART;VALUE=DATE:20250420\n\
+                       RRULE:FREQ=YEARLY;BYEASTER=0\nEND:VEVENT\n\
+                       BEGIN:VEVENT\nSUMMARY:Team day\nDTSTART;VALUE=DATE:20250901\n\
+                       RRULE:FREQ=MONTHLY;BYDAY=1MO\nEND:VEVENT\n\
+                       BEGIN:VEVENT\nSUMMARY:New year\nDTSTART;VALUE=DATE:20250101\n\
+                       RRULE:FREQ=YEARLY\nEND:VEVENT\nEND:VCALENDAR\n";
+
+        assert_eq!(
+            unsupported_recurrences(content, &CalendarFile::default()).unwrap(),
+            vec!["Easter", "Team day"]
+        );
+        // they count with their first date
+        let ranges = calendar_ranges(content, &CalendarFile::default(), &window()).unwrap();
+        assert_eq!(ranges.len(), 3);
+    }
+
+    #[test]
+    fn test_exported_calendar_reads_back() {
+        let assignments = vec![
+            assignment(4, "Place A", "Emily Smith"),
+            assignment(5, "Place A", "Emily Smith"),
+        ];
+        let ics = person_to_ics(
+            "roster",
+            &assignments,
+            "Emily Smith",
+            &HashMap::new(),
+            stamp(),
+        );
+
+        let ranges = calendar_ranges(&ics, &filtered(&["place a"], &[]), &window()).unwrap();
+
+        assert_eq!(ranges, vec![range((9, 4), (9, 4)), range((9, 5), (9, 5))]);
+    }
+
+    #[test]
+    fn test_calendar_ranges_rejects_other_files() {
+        assert!(calendar_ranges("date,Place A\n", &CalendarFile::default(), &window()).is_err());
+        let broken = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:tomorrow\nEND:VEVENT\nEND:VCALENDAR\n";
+        assert!(calendar_ranges(broken, &CalendarFile::default(), &window()).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("P1W").unwrap(), Duration::weeks(1));
+        assert_eq!(
+            parse_duration("P1DT12H30M").unwrap(),
+            Duration::days(1) + Duration::hours(12) + Duration::minutes(30)
+        );
+        assert!(parse_duration("1D").is_err());
+        assert!(parse_duration("PXD").is_err());
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Emily Smith"), "emily-smith");
+        assert_eq!(slug("  Place B (north) "), "place-b-north");
+        assert_eq!(slug("🪴"), "unnamed");
+    }
+
+    #[test]
+    fn test_write_ics_dir() {
+        let dir = TempDir::new().unwrap();
+        let assignments = vec![
+            assignment(4, "Place A", "Emily Smith"),
+            assignment(4, "Place B", "Olivia Jones"),
+        ];
+
+        let written =
+            write_ics_dir(dir.path(), "roster", &assignments, &HashMap::new(), false).unwrap();
+        assert_eq!(written, vec![dir.path().join(COMBINED_FILE)]);
+
+        let written =
+            write_ics_dir(dir.path(), "roster", &assignments, &HashMap::new(), true).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                dir.path().join(COMBINED_FILE),
+                dir.path().join("emily-smith.ics"),
+                dir.path().join("olivia-jones.ics"),
+            ]
+        );
+        let emily = fs::read_to_string(dir.path().join("emily-smith.ics")).unwrap();
+        assert!(emily.contains("SUMMARY:Place A: Emily Smith\r\n"));
+    }
+
+    #[test]
+    fn test_write_ics_dir_numbers_people_with_the_same_file_name() {
+        let dir = TempDir::new().unwrap();
+        let assignments = vec![
+            assignment(4, "Place A", "Emily Smith"),
+            assignment(4, "Place B", "Emily-Smith"),
+            assignment(5, "Place A", "emily smith"),
+        ];
+
+        let written =
+            write_ics_dir(dir.path(), "roster", &assignments, &HashMap::new(), true).unwrap();
+
+        assert_eq!(
+            written,
+            vec![
+                dir.path().join(COMBINED_FILE),
+                dir.path().join("emily-smith.ics"),
+                dir.path().join("emily-smith-2.ics"),
+                dir.path().join("emily-smith-3.ics"),
+            ]
+        );
+        let second = fs::read_to_string(dir.path().join("emily-smith-2.ics")).unwrap();
+        assert!(second.contains("SUMMARY:Place B: Emily-Smith\r\n"));
+    }
+
+    #[test]
+    fn test_roster_id() {
+        assert_eq!(roster_id(Path::new("rosters/choir.toml")), "choir");
+        assert_eq!(roster_id(Path::new("choir")), "choir");
+    }
+}
@@ -4,8 +4,12 @@
 pub mod config;
 pub mod csv;
 pub mod dates;
+pub mod document;
 pub mod extra_tasks;
 pub mod gui;
+pub mod history;
+pub mod ics;
 pub mod schedule;
+pub mod stats;
 
 pub use schedule::PersonState;
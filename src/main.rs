@@ -2,14 +2,26 @@
 
 use clap::Parser;
 use duty_roster::{
-    PersonState,
-    config::load_config,
-    csv::assignments_to_csv,
+    config::{Config, Diagnostic, has_errors, load_config, validate_file},
+    csv::{assignments_to_csv, summary_to_csv},
     dates::get_weekdays,
+    document::ScheduleDocument,
     gui,
-    schedule::{Assignment, create_schedule},
+    history::{config_history_paths, load_history},
+    ics::{roster_id, write_ics_dir},
+    schedule::{
+        Assignment, OptimizeBudget, ScheduleOptions, create_best_schedule, optimize_schedule,
+        random_seed, retain_assigned, trace_to_json,
+    },
+    stats::ScheduleStats,
+};
+use std::{
+    error::Error,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
 };
-use std::{error::Error, fs::File, io::Write};
 
 /// Duty Roster - Generate and manage schedules for people and places/tasks
 ///
@@ -28,23 +40,154 @@ struct Args {
     /// run in CLI mode (no GUI)
     #[arg(short = 'C', long)]
     cli: bool,
+
+    /// seed for the random generator, overrides the seed of the config file
+    #[arg(short, long)]
+    seed: Option<u64>,
+
+    /// previous schedule csv or history file to carry fairness over, can be given several times
+    #[arg(long)]
+    history: Vec<PathBuf>,
+
+    /// only check the config file and list its problems
+    #[arg(long)]
+    validate: bool,
+
+    /// generate this many schedules with different seeds and keep the fairest one
+    #[arg(long, default_value_t = 1)]
+    attempts: usize,
+
+    /// improve the generated schedule by trying this many swaps
+    #[arg(long, default_value_t = 0)]
+    optimize: usize,
+
+    /// stop the optimisation after this many seconds, tries swaps until then if --optimize is not given
+    #[arg(long)]
+    optimize_seconds: Option<u64>,
+
+    /// write why each person was chosen to this JSON file
+    #[arg(long)]
+    explain: Option<PathBuf>,
+
+    /// write the schedule as iCalendar file (schedule.ics) into this directory
+    #[arg(long)]
+    ics_dir: Option<PathBuf>,
+
+    /// with --ics-dir, also write one iCalendar file per person
+    #[arg(long)]
+    ics_per_person: bool,
+
+    /// also write the schedule as JSON document with the statistics of each person
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// read this JSON schedule document instead of generating a schedule, it is written as csv
+    /// and, with --ics-dir, as iCalendar files
+    #[arg(long)]
+    open: Option<PathBuf>,
+}
+
+impl Args {
+    /// the budget of the optimisation pass, None if it is not requested
+    fn optimize_budget(&self) -> Option<OptimizeBudget> {
+        match (self.optimize, self.optimize_seconds) {
+            (0, None) => None,
+            (iterations, time) => Some(OptimizeBudget {
+                iterations: if iterations == 0 {
+                    usize::MAX
+                } else {
+                    iterations
+                },
+                time: time.map(Duration::from_secs),
+            }),
+        }
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
-    if args.cli {
+    if args.validate {
+        let diagnostics = validate_file(&args.config)?;
+        for diagnostic in &diagnostics {
+            println!("{}", format_diagnostic(&args.config, diagnostic));
+        }
+        if has_errors(&diagnostics) {
+            return Err(format!("{} contains errors", args.config).into());
+        }
+        println!("{} is valid", args.config);
+    } else if let Some(path) = &args.open {
+        export_document(&args, path)?;
+    } else if args.cli {
         // Run in CLI mode
         println!("Running in CLI mode...");
-        let config = load_config(&args.config)?;
+        let mut config = load_config(&args.config)?;
         let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
 
-        let (assignments, people) = create_schedule(&dates, &config);
+        let seed = args.seed.or(config.rules.seed).unwrap_or_else(random_seed);
+        config.rules.seed = Some(seed);
+        println!("using seed {seed}");
+
+        let mut history_paths = config_history_paths(&args.config, &config);
+        history_paths.extend(args.history.iter().cloned());
+        let history = load_history(&history_paths, &config)?;
+        if !history.is_empty() {
+            println!("loaded {} previous services", history.len());
+        }
+
+        let options = ScheduleOptions {
+            history,
+            explain: args.explain.is_some(),
+            ..Default::default()
+        };
+        let best = create_best_schedule(&dates, &config, &options, args.attempts, &|_| {})?;
+        if best.attempts > 1 {
+            println!(
+                "best of {} attempts: seed {}, objective {:.2}",
+                best.attempts, best.seed, best.objective
+            );
+        }
+        let seed = best.seed;
+        config.rules.seed = Some(seed);
+        let mut assignments = best.assignments;
+        let unfilled = best.unfilled;
+        if let Some(budget) = args.optimize_budget() {
+            let report = optimize_schedule(&dates, &config, &options, &mut assignments, &budget)?;
+            println!("optimized schedule: {report}");
+        }
+        if let Some(path) = &args.explain {
+            // slots moved by the optimisation pass are not explained
+            let mut trace = best.trace;
+            retain_assigned(&mut trace, &assignments);
+            std::fs::write(path, trace_to_json(&trace)?)?;
+            println!("stored explanation to {}", path.display());
+        }
+        println!("fairness:");
+        for line in ScheduleStats::new(&config, &assignments).lines() {
+            println!("  {line}");
+        }
+
+        if let Some(path) = &args.json {
+            let config_source = std::fs::read_to_string(&args.config)?;
+            let document = ScheduleDocument::new(&config_source, &config, Some(seed), &assignments);
+            std::fs::write(path, document.to_json()?)?;
+            println!("stored schedule document to {}", path.display());
+        }
+        store_ics(&args, &config, &assignments)?;
 
-        match store_csv(assignments, people, &args.out) {
+        // the summary covers this schedule only, without the services of the history
+        match store_csv(&assignments, &config, Some(seed), &args.out) {
             Ok(_) => println!("stored schedule to {}", args.out),
             Err(e) => println!("error: could not store results: {e:?}"),
         };
+
+        if !unfilled.is_empty() {
+            println!("unfilled slots:");
+            for slot in &unfilled {
+                println!("  {slot}");
+            }
+            return Err(format!("{} slots could not be filled", unfilled.len()).into());
+        }
     } else {
         // Run in GUI mode (default)
         println!("Starting GUI mode...");
@@ -54,30 +197,70 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// prefix a diagnostic with the file name, e.g. `config.toml:3:9: error: ...`
+fn format_diagnostic(filename: &str, diagnostic: &Diagnostic) -> String {
+    match diagnostic.span {
+        Some(_) => format!("{filename}:{diagnostic}"),
+        None => format!("{filename}: {diagnostic}"),
+    }
+}
+
+/// read a schedule document and write it like a generated schedule
+fn export_document(args: &Args, path: &Path) -> Result<(), Box<dyn Error>> {
+    let config_source = std::fs::read_to_string(&args.config)?;
+    let config = load_config(&args.config)?;
+    let document = ScheduleDocument::from_json(&std::fs::read_to_string(path)?)?;
+    if !document.matches_config(&config_source) {
+        println!(
+            "warning: {} was written for another version of {}",
+            path.display(),
+            args.config
+        );
+    }
+    let assignments = document.assignments();
+    println!("read {} slots from {}", assignments.len(), path.display());
+
+    store_ics(args, &config, &assignments)?;
+    store_csv(&assignments, &config, document.seed, &args.out)?;
+    println!("stored schedule to {}", args.out);
+    Ok(())
+}
+
+/// write the iCalendar files if --ics-dir is given
+fn store_ics(
+    args: &Args,
+    config: &Config,
+    assignments: &[Assignment],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = &args.ics_dir {
+        let written = write_ics_dir(
+            dir,
+            &roster_id(Path::new(&args.config)),
+            assignments,
+            &config.places.shifts,
+            args.ics_per_person,
+        )?;
+        println!(
+            "stored {} calendar files to {}",
+            written.len(),
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// write the schedule and its summary, see `summary_to_csv`
 fn store_csv(
-    assignments: Vec<Assignment>,
-    people: Vec<PersonState>,
+    assignments: &[Assignment],
+    config: &Config,
+    seed: Option<u64>,
     filename: &str,
 ) -> Result<(), Box<dyn Error>> {
     let mut file = File::create(filename)?;
 
-    file.write_all(assignments_to_csv(&assignments)?.as_bytes())
-        .unwrap_or_else(|e| panic!("could not write to file {filename}: {e:?}"));
-
+    file.write_all(assignments_to_csv(assignments)?.as_bytes())?;
     file.write_all(b"\n")?;
-
-    for person in people {
-        file.write_all(
-            format!("{}, total: {}", person.name(), person.total_services()).as_bytes(),
-        )?;
-
-        for (day, count) in person.weekday_counts() {
-            file.write_all(format!(", {day}: {count}").as_bytes())?;
-        }
-        file.write_all(
-            format!(", different_place: {}\n", person.different_place_services()).as_bytes(),
-        )?;
-    }
+    file.write_all(summary_to_csv(config, assignments, seed)?.as_bytes())?;
 
     Ok(())
 }
@@ -86,9 +269,7 @@ fn store_csv(
 mod tests {
     use super::*;
     use chrono::NaiveDate;
-    use duty_roster::schedule::GroupState;
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use duty_roster::PersonState;
 
     fn create_test_date(year: i32, month: u32, day: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(year, month, day).unwrap()
@@ -112,25 +293,15 @@ mod tests {
     }
 
     fn create_test_people() -> Vec<PersonState> {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-
         // Create a person with some services
-        let mut person1 = PersonState::new(
-            "Person1".to_string(),
-            "Place A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person1 = PersonState::new("Person1".to_string(), "Place A".to_string(), 0);
 
         // Add some services on different days
         person1.register_service(create_test_date(2025, 9, 1), "Place A".to_string()); // Monday
         person1.register_service(create_test_date(2025, 9, 3), "Place B".to_string()); // Wednesday
 
         // Create another person with different services
-        let mut person2 = PersonState::new(
-            "Person2".to_string(),
-            "Place B".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut person2 = PersonState::new("Person2".to_string(), "Place B".to_string(), 0);
 
         person2.register_service(create_test_date(2025, 9, 2), "Place A".to_string()); // Tuesday
 
@@ -186,34 +357,119 @@ mod tests {
         assert_eq!(people[1].different_place_services(), 1); // One service at a different place
     }
 
+    #[test]
+    fn test_format_diagnostic() {
+        let mut diagnostic = Diagnostic {
+            severity: duty_roster::config::Severity::Error,
+            path: "dates.to".to_string(),
+            message: "'to' is before 'from'".to_string(),
+            span: None,
+        };
+        assert_eq!(
+            format_diagnostic("config.toml", &diagnostic),
+            "config.toml: error: dates.to: 'to' is before 'from'"
+        );
+
+        let position = duty_roster::config::Position { line: 3, column: 6 };
+        diagnostic.span = Some(duty_roster::config::Span {
+            start: position,
+            end: position,
+        });
+        assert_eq!(
+            format_diagnostic("config.toml", &diagnostic),
+            "config.toml:3:6: error: dates.to: 'to' is before 'from'"
+        );
+    }
+
     #[test]
     fn test_args_parsing() {
         // Test default values
-        let args = Args::parse_from(&["duty-roster"]);
+        let args = Args::parse_from(["duty-roster"]);
         assert_eq!(args.config, "config.toml");
         assert_eq!(args.out, "schedule.csv");
         assert!(!args.cli);
+        assert_eq!(args.seed, None);
+        assert!(args.history.is_empty());
+        assert!(!args.validate);
+        assert_eq!(args.attempts, 1);
+        assert_eq!(args.explain, None);
+        assert_eq!(args.ics_dir, None);
+        assert!(!args.ics_per_person);
+        assert_eq!(args.json, None);
 
         // Test with custom values
-        let args = Args::parse_from(&[
+        let args = Args::parse_from([
             "duty-roster",
             "--config",
             "custom.toml",
             "--out",
             "output.csv",
             "--cli",
+            "--seed",
+            "1234",
+            "--history",
+            "q2.csv",
+            "--history",
+            "q3.csv",
         ]);
         assert_eq!(args.config, "custom.toml");
         assert_eq!(args.out, "output.csv");
         assert!(args.cli);
+        assert_eq!(args.seed, Some(1234));
+        assert!(Args::parse_from(["duty-roster", "--validate"]).validate);
+        assert_eq!(
+            Args::parse_from(["duty-roster", "--attempts", "20"]).attempts,
+            20
+        );
+        assert_eq!(
+            Args::parse_from(["duty-roster", "--explain", "why.json"]).explain,
+            Some(PathBuf::from("why.json"))
+        );
+        let calendar = Args::parse_from(["duty-roster", "--ics-dir", "cal", "--ics-per-person"]);
+        assert_eq!(calendar.ics_dir, Some(PathBuf::from("cal")));
+        assert!(calendar.ics_per_person);
+        assert_eq!(
+            Args::parse_from(["duty-roster", "--json", "roster.json"]).json,
+            Some(PathBuf::from("roster.json"))
+        );
+        assert_eq!(
+            Args::parse_from(["duty-roster", "--open", "roster.json"]).open,
+            Some(PathBuf::from("roster.json"))
+        );
+        assert_eq!(
+            args.history,
+            vec![PathBuf::from("q2.csv"), PathBuf::from("q3.csv")]
+        );
 
         // Test with short options
-        let args =
-            Args::parse_from(&["duty-roster", "-c", "custom.toml", "-o", "output.csv", "-C"]);
+        let args = Args::parse_from(["duty-roster", "-c", "custom.toml", "-o", "output.csv", "-C"]);
         assert_eq!(args.config, "custom.toml");
         assert_eq!(args.out, "output.csv");
         assert!(args.cli);
     }
+
+    #[test]
+    fn test_optimize_budget() {
+        assert_eq!(Args::parse_from(["duty-roster"]).optimize_budget(), None);
+
+        let args = Args::parse_from(["duty-roster", "--optimize", "500"]);
+        assert_eq!(
+            args.optimize_budget(),
+            Some(OptimizeBudget {
+                iterations: 500,
+                time: None
+            })
+        );
+
+        let args = Args::parse_from(["duty-roster", "--optimize-seconds", "3"]);
+        assert_eq!(
+            args.optimize_budget(),
+            Some(OptimizeBudget {
+                iterations: usize::MAX,
+                time: Some(Duration::from_secs(3))
+            })
+        );
+    }
 }
 
 #[test]
@@ -229,54 +485,46 @@ fn test_main_function_signature() {
     // If this compiles, it means the signatures match
     let _: fn() -> Result<(), Box<dyn std::error::Error>> = mock_main;
     let _: fn() -> Result<(), Box<dyn std::error::Error>> = main;
+}
+
+#[cfg(test)]
+fn test_config() -> Config {
+    load_config("test/schedule.toml").unwrap()
+}
 
-    // Just assert true to have a passing test
-    assert!(true);
+#[cfg(test)]
+fn test_assignment(day: u32, place: &str, person: &str) -> Assignment {
+    Assignment {
+        date: chrono::NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+        place: place.to_string(),
+        person: person.to_string(),
+        base_person: person.to_string(),
+    }
 }
 
 #[test]
 fn test_store_csv_with_real_file() {
-    use chrono::NaiveDate;
-    use duty_roster::schedule::GroupState;
-    use std::cell::RefCell;
-    use std::rc::Rc;
     use tempfile::NamedTempFile;
 
     // Create a temporary file for testing
     let temp_file = NamedTempFile::new().unwrap();
     let file_path = temp_file.path().to_string_lossy().to_string();
 
-    // Create test assignments
-    let date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
-    let assignments = vec![Assignment {
-        date,
-        place: "Place A".to_string(),
-        person: "Person1".to_string(),
-        base_person: "Person1".to_string(),
-    }];
-
-    // Create test people
-    let group_state = Rc::new(RefCell::new(GroupState::default()));
-    let mut person1 = PersonState::new(
-        "Person1".to_string(),
-        "Place A".to_string(),
-        Rc::clone(&group_state),
-    );
-    person1.register_service(date, "Place A".to_string());
-    let people = vec![person1];
+    let assignments = vec![test_assignment(4, "Office", "Emily Smith")];
 
     // Test the function
-    let result = store_csv(assignments, people, &file_path);
+    let result = store_csv(&assignments, &test_config(), Some(42), &file_path);
     assert!(result.is_ok());
 
-    // Verify file content
+    // The schedule, the seed and the summary table are separated by empty lines
     let content = std::fs::read_to_string(file_path).unwrap();
-    // The CSV header might be different depending on the implementation
-    // Just check for key elements that should be present
-    assert!(content.contains("Person1"));
-    assert!(content.contains("total:"));
-    assert!(content.contains("Mon:"));
-    assert!(content.contains("different_place:"));
+    let blocks: Vec<&str> = content.split("\n\n").collect();
+    assert_eq!(blocks[0], "date,Office\n2025-09-04,Emily Smith");
+    assert_eq!(blocks[1], "seed: 42");
+    assert!(blocks[2].starts_with(
+        "person,group,home_place,total,Thu,Fri,Office,Transport,different_place,🪴,🪟\n"
+    ));
+    assert!(blocks[2].contains("\nEmily Smith,Smith,Office,1,1,0,1,0,0,0,0\n"));
 }
 
 #[test]
@@ -285,7 +533,6 @@ fn test_store_csv_invalid_path() {
 
     // Test with an invalid file path
     let assignments = vec![];
-    let people = vec![];
 
     // Create a path that should not exist
     let invalid_path = PathBuf::from("/invalid/path/that/should/not/exist").join(format!(
@@ -294,7 +541,12 @@ fn test_store_csv_invalid_path() {
     ));
 
     // Try to store to an invalid path
-    let result = store_csv(assignments, people, &invalid_path.to_string_lossy());
+    let result = store_csv(
+        &assignments,
+        &test_config(),
+        Some(42),
+        &invalid_path.to_string_lossy(),
+    );
 
     // Should return an error
     assert!(result.is_err());
@@ -302,72 +554,28 @@ fn test_store_csv_invalid_path() {
 
 #[test]
 fn test_store_csv_with_place_counts() {
-    use chrono::NaiveDate;
-    use duty_roster::schedule::GroupState;
-    use std::cell::RefCell;
-    use std::rc::Rc;
     use tempfile::NamedTempFile;
 
     // Create a temporary file for testing - keep the file handle alive
     let temp_file = NamedTempFile::new().unwrap();
     let file_path = temp_file.path().to_string_lossy().to_string();
 
-    // Create test assignments at multiple places
-    // Use same date for both assignments so CSV has proper structure
-    let date1 = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
-
-    let assignments = vec![
-        Assignment {
-            date: date1,
-            place: "Place A".to_string(),
-            person: "Person1".to_string(),
-            base_person: "Person1".to_string(),
-        },
-        Assignment {
-            date: date1,
-            place: "Place B".to_string(),
-            person: "Person1".to_string(),
-            base_person: "Person1".to_string(),
-        },
-    ];
-
-    let date2 = NaiveDate::from_ymd_opt(2025, 9, 3).unwrap();
-
-    // Create test people with services at multiple places
-    let group_state = Rc::new(RefCell::new(GroupState::default()));
-    let mut person1 = PersonState::new(
-        "Person1".to_string(),
-        "Place A".to_string(),
-        Rc::clone(&group_state),
-    );
-    person1.register_service(date1, "Place A".to_string());
-    person1.register_service(date1, "Place B".to_string());
-    person1.register_service(date2, "Place A".to_string());
-
-    // Verify place_counts before storing
-    let place_counts = person1.place_counts();
-    assert_eq!(*place_counts.get("Place A").unwrap(), 2);
-    assert_eq!(*place_counts.get("Place B").unwrap(), 1);
-
-    let people = vec![person1];
+    // Emily works at her own place on Thursday and at the other one on Friday
+    let mut with_task = test_assignment(4, "Office", "Emily Smith 🪴");
+    with_task.base_person = "Emily Smith".to_string();
+    let assignments = vec![with_task, test_assignment(12, "Transport", "Emily Smith")];
 
     // Close the temp file so store_csv can create a new one
     drop(temp_file);
 
-    // Test the function
-    let result = store_csv(assignments, people, &file_path);
+    let result = store_csv(&assignments, &test_config(), Some(42), &file_path);
     if let Err(ref e) = result {
         panic!("store_csv failed: {:?}", e);
     }
-    assert!(result.is_ok());
 
-    // Verify file content includes place counts information
+    // Verify the counts per weekday, place and extra task
     let content = std::fs::read_to_string(&file_path).unwrap();
-    assert!(content.contains("Person1"));
-    assert!(content.contains("total: 3"));
-    assert!(content.contains("Mon:"));
-    assert!(content.contains("Wed:"));
-    assert!(content.contains("different_place: 1"));
+    assert!(content.contains("\nEmily Smith,Smith,Office,2,1,1,1,1,1,1,0\n"));
 
     // Clean up
     let _ = std::fs::remove_file(&file_path);
@@ -375,66 +583,74 @@ fn test_store_csv_with_place_counts() {
 
 #[test]
 fn test_store_csv_multiple_people_with_places() {
-    use chrono::NaiveDate;
-    use duty_roster::schedule::GroupState;
-    use std::cell::RefCell;
-    use std::rc::Rc;
     use tempfile::NamedTempFile;
 
     // Create a temporary file for testing
     let temp_file = NamedTempFile::new().unwrap();
     let file_path = temp_file.path().to_string_lossy().to_string();
 
-    let date1 = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
-
-    // Use same date for all assignments so CSV has proper structure
     let assignments = vec![
-        Assignment {
-            date: date1,
-            place: "Place A".to_string(),
-            person: "Alice".to_string(),
-            base_person: "Alice".to_string(),
-        },
-        Assignment {
-            date: date1,
-            place: "Place B".to_string(),
-            person: "Bob".to_string(),
-            base_person: "Bob".to_string(),
-        },
+        test_assignment(4, "Office", "Emily Smith"),
+        test_assignment(4, "Transport", "Olivia Jones"),
     ];
 
-    // Create multiple people
-    let group_state = Rc::new(RefCell::new(GroupState::default()));
-
-    let mut alice = PersonState::new(
-        "Alice".to_string(),
-        "Place A".to_string(),
-        Rc::clone(&group_state),
-    );
-    alice.register_service(date1, "Place A".to_string());
-
-    let mut bob = PersonState::new(
-        "Bob".to_string(),
-        "Place B".to_string(),
-        Rc::clone(&group_state),
-    );
-    bob.register_service(date1, "Place B".to_string());
-
-    let people = vec![alice, bob];
-
     // Close the temp file so store_csv can create a new one
     drop(temp_file);
 
-    // Test the function
-    let result = store_csv(assignments, people, &file_path);
+    let result = store_csv(&assignments, &test_config(), Some(42), &file_path);
     assert!(result.is_ok());
 
-    // Verify file content
+    // Every person of the config gets a row in the order of the config, without services as well
     let content = std::fs::read_to_string(&file_path).unwrap();
-    assert!(content.contains("Alice"));
-    assert!(content.contains("Bob"));
-    assert!(content.contains("total: 1"));
+    let people: Vec<&str> = content
+        .split("\n\n")
+        .nth(2)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    assert_eq!(
+        people,
+        vec![
+            "Emily Smith",
+            "William Smith",
+            "Olivia Jones",
+            "Daniel Brown",
+            "Sophie Brown"
+        ]
+    );
+    assert!(content.contains("\nOlivia Jones,Jones,Transport,1,1,0,0,1,0,0,0\n"));
+    assert!(content.contains("\nWilliam Smith,Smith,Office,0,0,0,0,0,0,0,0\n"));
 
     // Clean up
     let _ = std::fs::remove_file(&file_path);
 }
+
+#[test]
+fn test_export_document() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_source = std::fs::read_to_string("test/schedule.toml").unwrap();
+    let mut with_task = test_assignment(4, "Office", "Emily Smith 🪴");
+    with_task.base_person = "Emily Smith".to_string();
+    let document = ScheduleDocument::new(&config_source, &test_config(), Some(7), &[with_task]);
+    let path = temp_dir.path().join("roster.json");
+    std::fs::write(&path, document.to_json().unwrap()).unwrap();
+    let out = temp_dir.path().join("roster.csv");
+    let args = Args::parse_from([
+        "duty-roster",
+        "--config",
+        "test/schedule.toml",
+        "--out",
+        out.to_str().unwrap(),
+        "--ics-dir",
+        temp_dir.path().join("calendar").to_str().unwrap(),
+    ]);
+
+    export_document(&args, &path).unwrap();
+
+    let content = std::fs::read_to_string(&out).unwrap();
+    assert!(content.starts_with("date,Office\n2025-09-04,Emily Smith 🪴\n\nseed: 7\n"));
+    assert!(temp_dir.path().join("calendar/schedule.ics").is_file());
+    assert!(export_document(&args, &temp_dir.path().join("missing.json")).is_err());
+}
@@ -1,28 +1,77 @@
 //! core business logic, parse configuration and create the schedule
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
-use crate::config::{Config, Rule};
+use crate::config::{Config, SortMode};
 use chrono::NaiveDate;
-use rand::rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
+pub mod best_of;
+mod error;
+pub(crate) mod fixed;
+pub mod optimize;
 pub mod person_state;
-pub use person_state::GroupState;
+pub mod replan;
+pub mod rules;
+pub mod trace;
+pub mod unfilled;
+pub mod violation;
+pub use best_of::{BestSchedule, create_best_schedule};
+pub use error::ScheduleError;
+pub use optimize::{OptimizeBudget, OptimizeReport, optimize_schedule};
 pub use person_state::PersonState;
+pub use person_state::Service;
+pub use person_state::{GroupState, group_states};
+pub use replan::{Absence, Replanned, lock_assignments, replan_schedule};
+use rules::ResolvedRules;
+pub use rules::{PlaceCap, RuleContext, RuleRegistry, SchedulingRule};
+pub use trace::{Candidate, RuleKey, SlotTrace, retain_assigned, trace_to_json};
+pub use unfilled::{Exclusion, UnfilledSlot};
+pub use violation::{Violation, replace_violations, swap_violations};
 
 pub(crate) fn create_people(config: &Config) -> Vec<PersonState> {
     let mut people: Vec<PersonState> = vec![];
 
-    for group in &config.group {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
+    for (idx, group) in config.group.iter().enumerate() {
         for member in &group.members {
-            people.push(PersonState::new(
+            let mut person = PersonState::new(
                 format!("{} {}", member.name, group.name),
                 group.place.clone(),
-                Rc::clone(&group_state),
-            ));
+                idx,
+            );
+            let mut unavailable = member.unavailable.clone();
+            unavailable.extend(&group.unavailable);
+            person.set_unavailable(unavailable);
+            people.push(person);
+        }
+    }
+
+    people
+}
+
+/// rebuild the state of all people of a config from existing assignments
+///
+/// every date that occurs in the assignments counts as a schedule date for the blocked days statistic
+pub fn people_from_assignments(config: &Config, assignments: &[Assignment]) -> Vec<PersonState> {
+    let mut people = create_people(config);
+
+    let dates: BTreeSet<NaiveDate> = assignments.iter().map(|a| a.date).collect();
+    for date in &dates {
+        for person in people.iter_mut().filter(|p| !p.is_available(*date)) {
+            person.register_blocked_day();
+        }
+    }
+
+    for a in assignments {
+        let idx = people
+            .iter()
+            .position(|p| p.name() == a.person)
+            .or_else(|| people.iter().position(|p| p.name() == a.base_person));
+        if let Some(idx) = idx {
+            people[idx].register_service(a.date, a.place.clone());
         }
     }
 
@@ -30,7 +79,9 @@ pub(crate) fn create_people(config: &Config) -> Vec<PersonState> {
 }
 
 /// Assignment captures a date, task(place) and person to do the job
-#[derive(Debug, Clone)]
+///
+/// a slot nobody could fill is kept as an assignment with an empty person
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Assignment {
     pub date: NaiveDate,
     pub place: String,
@@ -38,103 +89,856 @@ pub struct Assignment {
     pub base_person: String,
 }
 
+impl Assignment {
+    /// an empty slot, see `UnfilledSlot` for the reason
+    pub fn unfilled(date: NaiveDate, place: String) -> Self {
+        Self {
+            date,
+            place,
+            person: String::new(),
+            base_person: String::new(),
+        }
+    }
+
+    /// true if nobody could be assigned to this slot
+    pub fn is_unfilled(&self) -> bool {
+        self.base_person.is_empty()
+    }
+}
+
+/// the result of `create_schedule`
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// one assignment per slot, unfilled slots have an empty person
+    pub assignments: Vec<Assignment>,
+    pub people: Vec<PersonState>,
+    /// the services of each group, indexed by `PersonState::group`
+    pub groups: Vec<GroupState>,
+    /// the slots nobody could fill, in the order of the assignments
+    pub unfilled: Vec<UnfilledSlot>,
+    /// with `ScheduleOptions::explain` the decision for each generated assignment, in their order
+    pub trace: Vec<SlotTrace>,
+}
+
+/// a column of the schedule table: a place and the index of one of its slots
+///
+/// places needing more than one person per day get one column per slot
+pub fn slot_columns(assignments: &[Assignment]) -> Vec<(String, usize)> {
+    let mut slots: BTreeMap<String, usize> = BTreeMap::new();
+    for people in slots_by_date(assignments).values() {
+        for (place, persons) in people {
+            let count = slots.entry(place.clone()).or_default();
+            *count = (*count).max(persons.len());
+        }
+    }
+
+    slots
+        .into_iter()
+        .flat_map(|(place, count)| (0..count).map(move |slot| (place.clone(), slot)))
+        .collect()
+}
+
+/// organize assignments by date and place, the persons of one place keep the order of the assignments
+pub fn slots_by_date(
+    assignments: &[Assignment],
+) -> BTreeMap<NaiveDate, BTreeMap<String, Vec<String>>> {
+    let mut data: BTreeMap<NaiveDate, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for a in assignments {
+        data.entry(a.date)
+            .or_default()
+            .entry(a.place.clone())
+            .or_default()
+            .push(a.person.clone());
+    }
+    data
+}
+
+/// header of a slot column, the first slot is named like the place, further slots get a number
+pub fn slot_column_label(place: &str, slot: usize) -> String {
+    if slot == 0 {
+        place.to_string()
+    } else {
+        format!("{place} #{}", slot + 1)
+    }
+}
+
+/// place of a slot column header, the inverse of `slot_column_label`
+pub fn slot_column_place(label: &str) -> &str {
+    slot_column(label).0
+}
+
+/// place and slot of a slot column header, the inverse of `slot_column_label`
+pub fn slot_column(label: &str) -> (&str, usize) {
+    match label
+        .rsplit_once(" #")
+        .and_then(|(place, slot)| Some((place, slot.parse::<usize>().ok()?)))
+    {
+        Some((place, slot)) if slot > 1 => (place, slot - 1),
+        _ => (label, 0),
+    }
+}
+
+/// draw a new random seed, e.g. to store it before creating a schedule with it
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+/// additional input for `create_schedule_with`
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleOptions {
+    /// services of previous periods, only services before `config.dates.from` are taken into account
+    pub history: Vec<Assignment>,
+    /// rules that can be referenced from `[rules]`, contains the built-in rules by default
+    pub rules: RuleRegistry,
+    /// record why each person was chosen in `Schedule::trace`
+    pub explain: bool,
+}
+
+/// register services of previous periods, so that fairness holds across consecutive schedules
+fn register_history(people: &mut [PersonState], history: &[Assignment], before: NaiveDate) {
+    let mut past: Vec<&Assignment> = history.iter().filter(|a| a.date < before).collect();
+    past.sort_by_key(|a| a.date);
+
+    for a in past {
+        let person = people
+            .iter_mut()
+            .find(|p| p.name() == a.base_person || p.name() == a.person);
+        if let Some(person) = person {
+            person.register_service(a.date, a.place.clone());
+        }
+    }
+}
+
+/// why a person can not fill a slot, None if the person is a candidate
+pub(crate) fn exclusion(
+    person: &PersonState,
+    assigned_today: &HashSet<String>,
+    rules: &ResolvedRules,
+    context: &RuleContext,
+) -> Option<Exclusion> {
+    if !person.is_available(context.date) {
+        Some(Exclusion::Unavailable)
+    } else if assigned_today.contains(&person.name()) {
+        Some(Exclusion::AlreadyAssigned)
+    } else {
+        rules
+            .rejected_by(person, context)
+            .map(|rule| Exclusion::Filter(rule.to_string()))
+    }
+}
+
+/// the candidates from the best to the worst with the key of each sort rule
+fn ranked_candidates(
+    candidates: &[&PersonState],
+    rules: &ResolvedRules,
+    context: &RuleContext,
+) -> Vec<Candidate> {
+    let scores =
+        (rules.mode == SortMode::Weighted).then(|| rules.weighted_scores(candidates, context));
+    rules
+        .ranking(candidates, context)
+        .into_iter()
+        .map(|idx| Candidate {
+            person: candidates[idx].name(),
+            keys: rules
+                .sort
+                .iter()
+                .map(|rule| RuleKey {
+                    rule: rule.name().to_string(),
+                    key: rule.sort_key(candidates[idx], context),
+                })
+                .collect(),
+            score: scores.as_ref().map(|scores| scores[idx]),
+        })
+        .collect()
+}
+
 /// parse the configuration and assign someone on the given dates for the defined tasks(places)
-pub fn create_schedule(
+///
+/// the order of people is shuffled with `config.rules.seed`, a random seed is used if none is given
+///
+/// fixed assignments of the config are placed first on their date and count like any other service,
+/// conflicting fixed assignments are reported as error
+///
+/// a slot is left empty if no person is available and allowed by the filter rules,
+/// `Schedule::unfilled` tells which person was left out for which reason
+pub fn create_schedule(dates: &Vec<NaiveDate>, config: &Config) -> Result<Schedule, ScheduleError> {
+    create_schedule_with(dates, config, &ScheduleOptions::default())
+}
+
+/// like `create_schedule`, the people start with the services of `options.history`
+///
+/// the returned people include the services of the history
+pub fn create_schedule_with(
     dates: &Vec<NaiveDate>,
     config: &Config,
-) -> (Vec<Assignment>, Vec<PersonState>) {
+    options: &ScheduleOptions,
+) -> Result<Schedule, ScheduleError> {
+    let rules = options.rules.resolve(&config.rules)?;
     let mut people = create_people(config);
+    register_history(&mut people, &options.history, config.dates.from);
+    let mut groups = group_states(&people);
+    let mut place_cap = PlaceCap::new(&people);
+    let fixed = fixed::fixed_by_date(config, dates, &people)?;
+    let mut rng = StdRng::seed_from_u64(config.rules.seed.unwrap_or_else(random_seed));
 
     let mut assignments = Vec::new();
-    let filter_same_workid = config.rules.filter.contains(&Rule::FilterSamePlace);
-    let filter_diff_cap = config.rules.filter.contains(&Rule::FilterDifferentPlaceCap);
-
-    // Dynamic different-place cap: people from places larger than the smallest group
-    // are eligible to do cross-place work. The cap starts at min_eligible_count + 1,
-    // so no one gets a second cross-place assignment until everyone has had at least one.
-    // Precompute which places are "eligible" (above the minimum group size).
-    let diff_place_eligible_places: std::collections::HashSet<String> = if filter_diff_cap {
-        let mut place_sizes: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::new();
-        for p in &people {
-            *place_sizes.entry(p.place()).or_default() += 1;
-        }
-        let min_size = place_sizes.values().copied().min().unwrap_or(0);
-        place_sizes
-            .into_iter()
-            .filter(|(_, size)| *size > min_size)
-            .map(|(place, _)| place)
-            .collect()
-    } else {
-        std::collections::HashSet::new()
-    };
+    let mut unfilled = Vec::new();
+    let mut trace = Vec::new();
 
     for date in dates {
         if config.dates.exceptions.contains(date) {
             continue;
         }
 
-        let mut rng = rng();
         people.shuffle(&mut rng);
 
-        for place_id in &config.places.places {
-            // Dynamic cap: min cross-place count among eligible people + 1.
-            // No one gets their Nth cross-place assignment until everyone has had N-1.
-            let dynamic_cap: usize = if filter_diff_cap {
-                let min_among_eligible = people
-                    .iter()
-                    .filter(|p| diff_place_eligible_places.contains(&p.place()))
-                    .map(|p| p.different_place_services())
-                    .min()
-                    .unwrap_or(0);
-                min_among_eligible + 1
-            } else {
-                usize::MAX
-            };
-
-            let mut candidates: Vec<&mut PersonState> = people
-                .iter_mut()
-                .filter(|p| !filter_same_workid || &p.place() == place_id)
-                .filter(|p| {
-                    !filter_diff_cap
-                        || p.place() == *place_id
-                        || !diff_place_eligible_places.contains(&p.place())
-                        || p.different_place_services() < dynamic_cap
-                })
-                .collect();
+        for person in people.iter_mut().filter(|p| !p.is_available(*date)) {
+            person.register_blocked_day();
+        }
 
-            // Sort by precomputed tuple keys
-            candidates.sort_by_key(|p| p.sort_key(*date, place_id, &config.rules));
+        // nobody fills more than one slot per date
+        let mut assigned_today: HashSet<String> = HashSet::new();
 
-            if let Some(chosen) = candidates.first_mut() {
-                let name = chosen.name();
+        let fixed_today = fixed.get(date).map(Vec::as_slice).unwrap_or_default();
+        for slot in fixed_today {
+            if let Some(person) = people.iter_mut().find(|p| p.name() == slot.person) {
+                person.register_service(*date, slot.place.clone());
+                groups[person.group()].register(*date);
+            }
+            assigned_today.insert(slot.person.clone());
+        }
+
+        for place_id in &config.places.places {
+            let fixed_here: Vec<&fixed::FixedSlot> = fixed_today
+                .iter()
+                .filter(|slot| &slot.place == place_id)
+                .collect();
+            for slot in &fixed_here {
                 assignments.push(Assignment {
                     date: *date,
                     place: place_id.clone(),
-                    person: name.clone(),
-                    base_person: name,
+                    person: slot.person.clone(),
+                    base_person: slot.person.clone(),
                 });
-                chosen.register_service(*date, place_id.clone());
+                if options.explain {
+                    trace.push(SlotTrace::fixed(
+                        *date,
+                        place_id.clone(),
+                        slot.person.clone(),
+                    ));
+                }
+            }
+
+            for _slot in fixed_here.len()..config.places.headcount(place_id, *date) {
+                place_cap.update(&people);
+                let context = RuleContext {
+                    date: *date,
+                    place: place_id,
+                    people: &people,
+                    groups: &groups,
+                    place_cap: &place_cap,
+                    assignments: &assignments,
+                };
+
+                let mut indices = Vec::new();
+                let mut candidates = Vec::new();
+                let mut excluded = Vec::new();
+                for (idx, person) in people.iter().enumerate() {
+                    match exclusion(person, &assigned_today, &rules, &context) {
+                        None => {
+                            indices.push(idx);
+                            candidates.push(person);
+                        }
+                        Some(exclusion) => excluded.push((person.name(), exclusion)),
+                    }
+                }
+
+                // the first of the best candidates in the shuffled order wins
+                let chosen = rules
+                    .best_candidate(&candidates, &context)
+                    .map(|best| indices[best]);
+
+                if options.explain {
+                    trace.push(SlotTrace {
+                        date: *date,
+                        place: place_id.clone(),
+                        person: chosen.map(|idx| people[idx].name()).unwrap_or_default(),
+                        fixed: false,
+                        candidates: ranked_candidates(&candidates, &rules, &context),
+                        excluded: excluded.clone(),
+                    });
+                }
+
+                if let Some(idx) = chosen {
+                    let name = people[idx].name();
+                    assigned_today.insert(name.clone());
+                    assignments.push(Assignment {
+                        date: *date,
+                        place: place_id.clone(),
+                        person: name.clone(),
+                        base_person: name,
+                    });
+                    people[idx].register_service(*date, place_id.clone());
+                    groups[people[idx].group()].register(*date);
+                } else {
+                    unfilled.push(UnfilledSlot {
+                        date: *date,
+                        place: place_id.clone(),
+                        excluded,
+                    });
+                    assignments.push(Assignment::unfilled(*date, place_id.clone()));
+                }
             }
         }
     }
 
-    (assignments, people)
+    Ok(Schedule {
+        assignments,
+        people,
+        groups,
+        unfilled,
+        trace,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{config::load_config, dates::get_weekdays, schedule::create_schedule};
+    use crate::{
+        config::load_config,
+        dates::get_weekdays,
+        schedule::{Schedule, create_schedule},
+    };
+    use chrono::{Datelike, NaiveDate, Weekday};
     use std::collections::HashMap;
     use std::path::Path;
 
+    fn config_with_holidays() -> crate::config::Config {
+        toml::from_str(
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = []
+            weekdays = ["Mon", "Thu", "Fri"]
+
+            [places]
+            places = ["Place A"]
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+
+            [[group.members]]
+            name = "Alice"
+            unavailable = { dates = ["2025-09-04"], ranges = [{ from = "2025-09-15", to = "2025-09-19" }] }
+
+            [[group.members]]
+            name = "Bob"
+            unavailable = { weekdays = ["Mon"] }
+
+            [[group]]
+            name = "Doe"
+            place = "Place A"
+            unavailable = { dates = ["2025-09-05"] }
+
+            [[group.members]]
+            name = "Charlie"
+
+            [rules]
+            sort = ["sortByLeastServices"]
+            filter = []
+        "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn create_schedule_skips_unavailable_people() {
+        let config = config_with_holidays();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
+
+        assert_eq!(assignments.len(), dates.len());
+        for a in &assignments {
+            let person = people.iter().find(|p| p.name() == a.person).unwrap();
+            assert!(person.is_available(a.date), "{} on {}", a.person, a.date);
+        }
+        assert!(
+            !assignments
+                .iter()
+                .any(|a| a.person == "Bob Maier" && a.date.weekday() == Weekday::Mon)
+        );
+        assert!(
+            !assignments.iter().any(|a| a.person == "Charlie Doe"
+                && a.date == NaiveDate::from_ymd_opt(2025, 9, 5).unwrap())
+        );
+    }
+
+    #[test]
+    fn create_schedule_keeps_the_group_table_in_sync() {
+        let config = config_with_holidays();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule { people, groups, .. } = create_schedule(&dates, &config).unwrap();
+
+        assert_eq!(groups.len(), config.group.len());
+        for (group, rebuilt) in groups.iter().zip(super::group_states(&people)) {
+            assert_eq!(group.last_service(), rebuilt.last_service());
+        }
+        assert!(groups.iter().all(|g| g.last_service().is_some()));
+    }
+
+    #[test]
+    fn create_schedule_counts_blocked_days() {
+        let config = config_with_holidays();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule { people, .. } = create_schedule(&dates, &config).unwrap();
+        let blocked = |name: &str| {
+            people
+                .iter()
+                .find(|p| p.name() == name)
+                .unwrap()
+                .blocked_days()
+        };
+
+        // 2025-09-04 and Mon 15th, Thu 18th, Fri 19th
+        assert_eq!(blocked("Alice Maier"), 4);
+        // five Mondays in September 2025
+        assert_eq!(blocked("Bob Maier"), 5);
+        assert_eq!(blocked("Charlie Doe"), 1);
+    }
+
+    #[test]
+    fn create_schedule_fills_every_slot_of_a_place() {
+        let config: crate::config::Config = toml::from_str(
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = []
+            weekdays = ["Mon", "Fri"]
+
+            [places]
+            places = ["Place A", "Place B"]
+
+            [places.headcount]
+            "Place B" = 2
+
+            [places.weekday_headcount."Place B"]
+            Fri = 3
+
+            [[group]]
+            name = "Maier"
+            place = "Place A"
+
+            [[group.members]]
+            name = "Alice"
+
+            [[group.members]]
+            name = "Bob"
+
+            [[group.members]]
+            name = "Charlie"
+
+            [[group.members]]
+            name = "Dave"
+
+            [[group.members]]
+            name = "Eve"
+
+            [rules]
+            sort = ["sortByLeastServices"]
+            filter = []
+        "#,
+        )
+        .unwrap();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule { assignments, .. } = create_schedule(&dates, &config).unwrap();
+
+        for date in &dates {
+            let on_date: Vec<_> = assignments.iter().filter(|a| a.date == *date).collect();
+            let place_b = on_date.iter().filter(|a| a.place == "Place B").count();
+            let expected_b = if date.weekday() == Weekday::Fri { 3 } else { 2 };
+            assert_eq!(place_b, expected_b, "wrong headcount on {date}");
+            assert_eq!(on_date.len(), expected_b + 1);
+
+            let people: std::collections::HashSet<_> = on_date.iter().map(|a| &a.person).collect();
+            assert_eq!(people.len(), on_date.len(), "double booking on {date}");
+        }
+    }
+
+    #[test]
+    fn create_schedule_with_same_seed_is_reproducible() {
+        let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test/schedule.toml");
+        let mut config = load_config(config_path.to_str().unwrap()).unwrap();
+        config.rules.seed = Some(7);
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let names = |assignments: Vec<super::Assignment>| -> Vec<String> {
+            assignments.into_iter().map(|a| a.person).collect()
+        };
+        let Schedule {
+            assignments: first, ..
+        } = create_schedule(&dates, &config).unwrap();
+        let Schedule {
+            assignments: second,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
+
+        assert_eq!(names(first), names(second));
+    }
+
+    #[test]
+    fn create_schedule_in_weighted_mode_fills_every_slot() {
+        let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test/config.toml");
+        let mut config = load_config(config_path.to_str().unwrap()).unwrap();
+        config.rules.mode = crate::config::SortMode::Weighted;
+        config
+            .rules
+            .weights
+            .insert("sortByLastService".to_string(), 3.0);
+        config.rules.seed = Some(7);
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
+
+        assert_eq!(
+            assignments.len(),
+            (dates.len() - config.dates.exceptions.len()) * config.places.places.len()
+        );
+        // Alice and Bob share Place A
+        let totals: Vec<usize> = people
+            .iter()
+            .filter(|p| p.place() == "Place A")
+            .map(|p| p.total_services())
+            .collect();
+        assert_eq!(totals.len(), 2);
+        assert!(
+            totals[0].abs_diff(totals[1]) <= 1,
+            "unfair totals {totals:?}"
+        );
+    }
+
+    #[test]
+    fn create_schedule_reports_unfilled_slots() {
+        let mut config = config_with_holidays();
+        config.places.places.push("Place B".to_string());
+        config.rules.filter = vec![crate::config::Rule::FilterSamePlace];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let Schedule {
+            assignments,
+            unfilled,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
+
+        // nobody belongs to Place B, every slot there stays empty
+        let empty: Vec<_> = assignments.iter().filter(|a| a.is_unfilled()).collect();
+        assert_eq!(empty.len(), dates.len());
+        assert!(
+            empty
+                .iter()
+                .all(|a| a.place == "Place B" && a.person.is_empty())
+        );
+        assert_eq!(unfilled.len(), dates.len());
+
+        let monday = unfilled
+            .iter()
+            .find(|slot| slot.date == NaiveDate::from_ymd_opt(2025, 9, 1).unwrap())
+            .unwrap();
+        assert_eq!(monday.place, "Place B");
+        let exclusion = |person: &str| {
+            monday
+                .excluded
+                .iter()
+                .find(|(name, _)| name == person)
+                .map(|(_, exclusion)| exclusion.clone())
+                .unwrap()
+        };
+        // Bob is off on Mondays, the one of Alice and Charlie at Place A is already assigned
+        let at_place_a = assignments
+            .iter()
+            .find(|a| a.date == monday.date && a.place == "Place A")
+            .unwrap();
+        let other = if at_place_a.person == "Alice Maier" {
+            "Charlie Doe"
+        } else {
+            "Alice Maier"
+        };
+        assert_eq!(exclusion("Bob Maier"), super::Exclusion::Unavailable);
+        assert_eq!(
+            exclusion(&at_place_a.person),
+            super::Exclusion::AlreadyAssigned
+        );
+        assert_eq!(
+            exclusion(other),
+            super::Exclusion::Filter("filterSamePlace".to_string())
+        );
+    }
+
+    #[test]
+    fn create_schedule_explains_every_assignment() {
+        let mut config = config_with_holidays();
+        let thursday = NaiveDate::from_ymd_opt(2025, 9, 11).unwrap();
+        config.fixed = vec![crate::config::Fixed {
+            date: thursday,
+            place: "Place A".to_string(),
+            person: "Charlie".to_string(),
+        }];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = super::ScheduleOptions {
+            explain: true,
+            ..Default::default()
+        };
+
+        let Schedule {
+            assignments, trace, ..
+        } = super::create_schedule_with(&dates, &config, &options).unwrap();
+
+        assert_eq!(trace.len(), assignments.len());
+        for (a, t) in assignments.iter().zip(&trace) {
+            assert_eq!((a.date, &a.place, &a.person), (t.date, &t.place, &t.person));
+            if t.fixed {
+                assert_eq!(a.date, thursday);
+                assert!(t.candidates.is_empty());
+                continue;
+            }
+            // the chosen person is the best candidate and has the smallest keys
+            assert_eq!(t.candidates[0].person, a.person);
+            assert!(
+                t.candidates
+                    .windows(2)
+                    .all(|w| w[0].keys[0].key <= w[1].keys[0].key)
+            );
+            assert!(
+                t.candidates
+                    .iter()
+                    .all(|c| c.keys[0].rule == "sortByLeastServices")
+            );
+            assert_eq!(t.candidates.len() + t.excluded.len(), 3);
+            if a.date.weekday() == Weekday::Mon {
+                assert!(
+                    t.excluded
+                        .contains(&("Bob Maier".to_string(), super::Exclusion::Unavailable))
+                );
+            }
+        }
+
+        // without explain nothing is recorded
+        let Schedule { trace, .. } = create_schedule(&dates, &config).unwrap();
+        assert!(trace.is_empty());
+    }
+
+    #[test]
+    fn create_schedule_places_fixed_assignments_first() {
+        let mut config = config_with_holidays();
+        let thursday = NaiveDate::from_ymd_opt(2025, 9, 11).unwrap();
+        config.fixed = vec![crate::config::Fixed {
+            date: thursday,
+            place: "Place A".to_string(),
+            person: "Charlie".to_string(),
+        }];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        for seed in 0..10 {
+            config.rules.seed = Some(seed);
+            let Schedule {
+                assignments,
+                people,
+                ..
+            } = create_schedule(&dates, &config).unwrap();
+
+            let on_date: Vec<_> = assignments.iter().filter(|a| a.date == thursday).collect();
+            assert_eq!(on_date.len(), 1);
+            assert_eq!(on_date[0].person, "Charlie Doe");
+            assert_eq!(
+                assignments.len(),
+                people.iter().map(|p| p.total_services()).sum::<usize>()
+            );
+        }
+    }
+
+    #[test]
+    fn create_schedule_reports_conflicting_fixed_assignments() {
+        let mut config = config_with_holidays();
+        config.fixed = vec![crate::config::Fixed {
+            date: NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+            place: "Place A".to_string(),
+            person: "Alice".to_string(),
+        }];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let result = create_schedule(&dates, &config);
+
+        assert!(matches!(result, Err(super::ScheduleError::Unavailable(_))));
+    }
+
+    #[test]
+    fn create_schedule_with_history_prefers_people_with_less_services() {
+        let config = config_with_holidays();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let past = |day: u32, person: &str| super::Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 8, day).unwrap(),
+            place: "Place A".to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        };
+        let history: Vec<super::Assignment> = (1..=20)
+            .map(|day| past(day, "Charlie Doe"))
+            .chain([
+                // ignored, not before the schedule starts
+                super::Assignment {
+                    date: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+                    ..past(1, "Alice Maier")
+                },
+                // ignored, not part of the config
+                past(21, "Zoe Former"),
+            ])
+            .collect();
+        let options = super::ScheduleOptions {
+            history,
+            ..Default::default()
+        };
+
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = super::create_schedule_with(&dates, &config, &options).unwrap();
+
+        // Charlie only works when Alice and Bob are both unavailable
+        let charlie_dates: Vec<NaiveDate> = assignments
+            .iter()
+            .filter(|a| a.person == "Charlie Doe")
+            .map(|a| a.date)
+            .collect();
+        assert_eq!(
+            charlie_dates,
+            vec![NaiveDate::from_ymd_opt(2025, 9, 15).unwrap()]
+        );
+        let charlie = people.iter().find(|p| p.name() == "Charlie Doe").unwrap();
+        assert_eq!(charlie.total_services(), 21);
+        let alice = people.iter().find(|p| p.name() == "Alice Maier").unwrap();
+        assert_eq!(
+            alice.total_services(),
+            assignments
+                .iter()
+                .filter(|a| a.person == "Alice Maier")
+                .count()
+        );
+    }
+
+    struct NotOnFridays;
+
+    impl super::SchedulingRule for NotOnFridays {
+        fn name(&self) -> &str {
+            "notOnFridays"
+        }
+
+        fn allows(&self, candidate: &super::PersonState, context: &super::RuleContext) -> bool {
+            candidate.name() != "Alice Maier" || context.date.weekday() != Weekday::Fri
+        }
+    }
+
+    #[test]
+    fn create_schedule_with_custom_rule() {
+        let mut config = config_with_holidays();
+        config.rules.filter = vec![crate::config::Rule::Custom("notOnFridays".to_string())];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        assert_eq!(
+            create_schedule(&dates, &config).err(),
+            Some(super::ScheduleError::UnknownRule(
+                "notOnFridays".to_string()
+            ))
+        );
+
+        let mut options = super::ScheduleOptions::default();
+        options.rules.register(NotOnFridays);
+        let Schedule { assignments, .. } =
+            super::create_schedule_with(&dates, &config, &options).unwrap();
+
+        assert!(
+            !assignments
+                .iter()
+                .any(|a| a.person == "Alice Maier" && a.date.weekday() == Weekday::Fri)
+        );
+    }
+
+    #[test]
+    fn slot_columns_have_one_column_per_slot() {
+        let assignment = |day: u32, place: &str, person: &str| super::Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        };
+        let assignments = vec![
+            assignment(1, "B", "Alice"),
+            assignment(1, "A", "Bob"),
+            assignment(2, "B", "Bob"),
+            assignment(2, "B", "Charlie"),
+        ];
+
+        assert_eq!(
+            super::slot_columns(&assignments),
+            vec![
+                ("A".to_string(), 0),
+                ("B".to_string(), 0),
+                ("B".to_string(), 1)
+            ]
+        );
+        assert_eq!(super::slot_column_label("B", 0), "B");
+        assert_eq!(super::slot_column_label("B", 1), "B #2");
+        assert_eq!(super::slot_column_place("B #2"), "B");
+        assert_eq!(super::slot_column_place("B"), "B");
+        assert_eq!(super::slot_column_place("Room #1"), "Room #1");
+        assert_eq!(super::slot_column("B #3"), ("B", 2));
+        assert_eq!(super::slot_column("B"), ("B", 0));
+    }
+
+    #[test]
+    fn people_from_assignments_restores_services_and_blocked_days() {
+        let config = config_with_holidays();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
+
+        let rebuilt = super::people_from_assignments(&config, &assignments);
+
+        for person in &people {
+            let other = rebuilt.iter().find(|p| p.name() == person.name()).unwrap();
+            assert_eq!(person.total_services(), other.total_services());
+            assert_eq!(person.blocked_days(), other.blocked_days());
+        }
+    }
+
     #[test]
     fn create_schedule_should_provide_reasonable_schedule() {
         let config_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test/config.toml");
         let config = load_config(config_path.to_str().unwrap()).unwrap();
         let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
 
-        let (assignments, people) = create_schedule(&dates, &config);
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
 
         assert_eq!(
             assignments.len(),
@@ -143,7 +947,7 @@ mod tests {
 
         assert_eq!(
             assignments.len(),
-            people.iter().map(|p| p.total_services()).sum()
+            people.iter().map(|p| p.total_services()).sum::<usize>()
         );
 
         assert_eq!(
@@ -151,7 +955,7 @@ mod tests {
             people
                 .iter()
                 .map(|p| p.weekday_counts().values().copied().sum::<usize>())
-                .sum()
+                .sum::<usize>()
         );
     }
 
@@ -161,7 +965,11 @@ mod tests {
         let config = load_config(config_path.to_str().unwrap()).unwrap();
         let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
 
-        let (assignments, people) = create_schedule(&dates, &config);
+        let Schedule {
+            assignments,
+            people,
+            ..
+        } = create_schedule(&dates, &config).unwrap();
 
         let mut expected: HashMap<(String, String), usize> = HashMap::new();
         for a in &assignments {
@@ -0,0 +1,262 @@
+//! generate several schedules with different seeds and keep the fairest one
+//!
+//! the attempts are ranked by `optimize::objective` with the services of the history, the same
+//! measure the optimisation pass improves afterwards
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use chrono::NaiveDate;
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::optimize::{objective, people_with_history};
+use super::{
+    Assignment, ScheduleError, ScheduleOptions, SlotTrace, UnfilledSlot, create_schedule_with,
+    random_seed,
+};
+use crate::config::Config;
+
+/// the fairest of several generated schedules
+#[derive(Debug, Clone)]
+pub struct BestSchedule {
+    pub assignments: Vec<Assignment>,
+    /// the slots of the winning attempt nobody could fill
+    pub unfilled: Vec<UnfilledSlot>,
+    /// the decisions of the winning attempt, if `ScheduleOptions::explain` is set
+    pub trace: Vec<SlotTrace>,
+    /// seed of the winning attempt, generating with it again gives the same schedule
+    pub seed: u64,
+    /// `optimize::objective` of the winning attempt, including the history
+    pub objective: f64,
+    /// number of generated schedules
+    pub attempts: usize,
+}
+
+/// the seeds of the attempts, the first attempt uses the given seed itself
+pub fn attempt_seeds(seed: u64, attempts: usize) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seeds = vec![seed];
+    while seeds.len() < attempts {
+        seeds.push(rng.random());
+    }
+    seeds
+}
+
+/// generate `attempts` schedules on all available cores and return the one with the lowest
+/// objective
+///
+/// an attempt with fewer unfilled slots always wins over one with a lower objective. The seeds of
+/// the attempts are derived from `config.rules.seed`, so the result is reproducible.
+/// A tie is won by the earlier attempt. `progress` is called with the number of finished attempts
+pub fn create_best_schedule(
+    dates: &Vec<NaiveDate>,
+    config: &Config,
+    options: &ScheduleOptions,
+    attempts: usize,
+    progress: &(dyn Fn(usize) + Sync),
+) -> Result<BestSchedule, ScheduleError> {
+    let seeds = attempt_seeds(
+        config.rules.seed.unwrap_or_else(random_seed),
+        attempts.max(1),
+    );
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(seeds.len());
+
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(seed) = seeds.get(idx) else {
+                        break;
+                    };
+
+                    let mut config = config.clone();
+                    config.rules.seed = Some(*seed);
+                    let result = create_schedule_with(dates, &config, options).map(|schedule| {
+                        let people = people_with_history(&config, options, &schedule.assignments);
+                        let objective = objective(&people, &config);
+                        (schedule, objective)
+                    });
+
+                    results.lock().unwrap().push((idx, result));
+                    progress(finished.fetch_add(1, Ordering::Relaxed) + 1);
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(idx, _)| *idx);
+
+    let mut best: Option<BestSchedule> = None;
+    for (idx, result) in results {
+        let (schedule, objective) = result?;
+        let unfilled = schedule.unfilled.len();
+        if best.as_ref().is_none_or(|best| {
+            unfilled < best.unfilled.len()
+                || (unfilled == best.unfilled.len() && objective < best.objective)
+        }) {
+            best = Some(BestSchedule {
+                assignments: schedule.assignments,
+                unfilled: schedule.unfilled,
+                trace: schedule.trace,
+                seed: seeds[idx],
+                objective,
+                attempts: seeds.len(),
+            });
+        }
+    }
+
+    Ok(best.expect("at least one attempt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::dates::get_weekdays;
+
+    fn config() -> Config {
+        let mut config = test_config();
+        config.rules.seed = Some(11);
+        config
+    }
+
+    /// the objective of the attempt with this seed, including the history of `options`
+    fn attempt_objective(config: &Config, options: &ScheduleOptions, seed: u64) -> f64 {
+        let mut attempt = config.clone();
+        attempt.rules.seed = Some(seed);
+        let dates = get_weekdays(
+            &attempt.dates.from,
+            &attempt.dates.to,
+            &attempt.dates.weekdays,
+        );
+        let assignments = create_schedule_with(&dates, &attempt, options)
+            .unwrap()
+            .assignments;
+        objective(
+            &people_with_history(&attempt, options, &assignments),
+            &attempt,
+        )
+    }
+
+    #[test]
+    fn attempt_seeds_start_with_the_given_seed() {
+        let seeds = attempt_seeds(11, 4);
+        assert_eq!(seeds.len(), 4);
+        assert_eq!(seeds[0], 11);
+        assert_eq!(seeds, attempt_seeds(11, 4));
+        assert_eq!(attempt_seeds(11, 0), vec![11]);
+    }
+
+    #[test]
+    fn best_schedule_is_the_fairest_attempt_and_reproducible() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = ScheduleOptions::default();
+        let calls = AtomicUsize::new(0);
+
+        let best = create_best_schedule(&dates, &config, &options, 8, &|_| {
+            calls.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        assert_eq!(best.attempts, 8);
+        assert_eq!(calls.load(Ordering::Relaxed), 8);
+        assert_eq!(
+            best.objective,
+            attempt_objective(&config, &options, best.seed)
+        );
+
+        // no attempt is fairer than the winner
+        for seed in attempt_seeds(11, 8) {
+            assert!(attempt_objective(&config, &options, seed) >= best.objective);
+        }
+
+        // the seed of the winner creates the same schedule again
+        let mut again = config.clone();
+        again.rules.seed = Some(best.seed);
+        let assignments = create_schedule_with(&dates, &again, &options)
+            .unwrap()
+            .assignments;
+        let people = |assignments: &[Assignment]| -> Vec<String> {
+            assignments.iter().map(|a| a.person.clone()).collect()
+        };
+        assert_eq!(people(&assignments), people(&best.assignments));
+    }
+
+    #[test]
+    fn history_decides_the_best_attempt() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let service = |day: u32, place: &str, person: &str| Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 8, day).unwrap(),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        };
+        let options = ScheduleOptions {
+            history: vec![
+                service(7, "Transport", "William Smith"),
+                service(8, "Office", "William Smith"),
+                service(22, "Transport", "Daniel Brown"),
+                service(29, "Office", "Emily Smith"),
+            ],
+            ..Default::default()
+        };
+
+        let best = create_best_schedule(&dates, &config, &options, 8, &|_| {}).unwrap();
+
+        // ranked on this period alone, another attempt would have won
+        let without_history = |seed: u64| {
+            let mut attempt = config.clone();
+            attempt.rules.seed = Some(seed);
+            let assignments = create_schedule_with(&dates, &attempt, &options)
+                .unwrap()
+                .assignments;
+            let people = people_with_history(&attempt, &ScheduleOptions::default(), &assignments);
+            objective(&people, &attempt)
+        };
+        let seeds = attempt_seeds(11, 8);
+        let fairest_alone = seeds
+            .iter()
+            .copied()
+            .reduce(|a, b| {
+                if without_history(b) < without_history(a) {
+                    b
+                } else {
+                    a
+                }
+            })
+            .unwrap();
+        assert_ne!(best.seed, fairest_alone);
+        assert!(attempt_objective(&config, &options, fairest_alone) > best.objective);
+        for seed in seeds {
+            assert!(attempt_objective(&config, &options, seed) >= best.objective);
+        }
+    }
+
+    #[test]
+    fn best_schedule_reports_errors() {
+        let mut config = config();
+        config.fixed = vec![crate::config::Fixed {
+            date: config.dates.from,
+            place: "Office".to_string(),
+            person: "Nobody".to_string(),
+        }];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+
+        let result = create_best_schedule(&dates, &config, &ScheduleOptions::default(), 3, &|_| {});
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::config::Fixed;
+
+/// reasons why no schedule can be created for a config
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleError {
+    /// the person of a fixed assignment is not a member of any group
+    UnknownPerson(Fixed),
+    /// the person of a fixed assignment matches members of several groups
+    AmbiguousPerson(Fixed),
+    /// the place of a fixed assignment is not listed in `places.places`
+    UnknownPlace(Fixed),
+    /// the date of a fixed assignment is before `dates.from` or after `dates.to`
+    DateOutOfRange(Fixed),
+    /// the date of a fixed assignment is an exception or not one of the scheduled weekdays
+    NotAScheduleDate(Fixed),
+    /// the person of a fixed assignment is unavailable on that date
+    Unavailable(Fixed),
+    /// the person of a fixed assignment already has a fixed assignment on that date
+    DoubleBooking(Fixed),
+    /// a place has more fixed assignments on a date than people are needed there
+    TooManyForPlace(Fixed),
+    /// a rule of `[rules]` is neither built in nor registered
+    UnknownRule(String),
+    /// an absence to re-plan for names an unknown person or ends before it starts
+    InvalidAbsence(String),
+    /// a slot to swap is not an index of the assignments
+    UnknownSlot(usize),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (fixed, problem) = match self {
+            ScheduleError::InvalidAbsence(problem) => {
+                return write!(f, "invalid absence: {problem}");
+            }
+            ScheduleError::UnknownSlot(slot) => {
+                return write!(f, "unknown slot {slot}, the schedule has fewer slots");
+            }
+            ScheduleError::UnknownRule(name) => {
+                return write!(
+                    f,
+                    "unknown rule '{name}', custom rules have to be registered"
+                );
+            }
+            ScheduleError::UnknownPerson(fixed) => (fixed, "unknown person"),
+            ScheduleError::AmbiguousPerson(fixed) => {
+                (fixed, "person is ambiguous, use the full name")
+            }
+            ScheduleError::UnknownPlace(fixed) => (fixed, "unknown place"),
+            ScheduleError::DateOutOfRange(fixed) => (fixed, "date is outside of the schedule"),
+            ScheduleError::NotAScheduleDate(fixed) => {
+                (fixed, "no schedule is created on this date")
+            }
+            ScheduleError::Unavailable(fixed) => (fixed, "person is unavailable on this date"),
+            ScheduleError::DoubleBooking(fixed) => (fixed, "person is already fixed on this date"),
+            ScheduleError::TooManyForPlace(fixed) => {
+                (fixed, "place needs fewer people on this date")
+            }
+        };
+        write!(
+            f,
+            "fixed assignment of {} to {} on {}: {problem}",
+            fixed.person, fixed.place, fixed.date
+        )
+    }
+}
+
+impl std::error::Error for ScheduleError {}
@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+
+use crate::config::{Config, Fixed};
+
+use super::{PersonState, ScheduleError};
+
+/// a fixed assignment resolved against the people of a config
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FixedSlot {
+    pub place: String,
+    /// full name of the person
+    pub person: String,
+}
+
+/// check the fixed assignments of the config and group them by date
+///
+/// the order of fixed assignments within a date follows the order in the config
+pub(crate) fn fixed_by_date(
+    config: &Config,
+    dates: &[NaiveDate],
+    people: &[PersonState],
+) -> Result<BTreeMap<NaiveDate, Vec<FixedSlot>>, ScheduleError> {
+    let mut by_date: BTreeMap<NaiveDate, Vec<FixedSlot>> = BTreeMap::new();
+
+    for fixed in &config.fixed {
+        if fixed.date < config.dates.from || fixed.date > config.dates.to {
+            return Err(ScheduleError::DateOutOfRange(fixed.clone()));
+        }
+        if !dates.contains(&fixed.date) || config.dates.exceptions.contains(&fixed.date) {
+            return Err(ScheduleError::NotAScheduleDate(fixed.clone()));
+        }
+        if !config.places.places.contains(&fixed.place) {
+            return Err(ScheduleError::UnknownPlace(fixed.clone()));
+        }
+
+        let person = resolve_person(config, fixed)?;
+        let state = people.iter().find(|p| p.name() == person);
+        if state.is_some_and(|p| !p.is_available(fixed.date)) {
+            return Err(ScheduleError::Unavailable(fixed.clone()));
+        }
+
+        let slots = by_date.entry(fixed.date).or_default();
+        if slots.iter().any(|slot| slot.person == person) {
+            return Err(ScheduleError::DoubleBooking(fixed.clone()));
+        }
+        let taken = slots
+            .iter()
+            .filter(|slot| slot.place == fixed.place)
+            .count();
+        if taken >= config.places.headcount(&fixed.place, fixed.date) {
+            return Err(ScheduleError::TooManyForPlace(fixed.clone()));
+        }
+
+        slots.push(FixedSlot {
+            place: fixed.place.clone(),
+            person,
+        });
+    }
+
+    Ok(by_date)
+}
+
+/// find the full name of the person of a fixed assignment
+pub(crate) fn resolve_person(config: &Config, fixed: &Fixed) -> Result<String, ScheduleError> {
+    let mut by_member: HashMap<&str, Vec<String>> = HashMap::new();
+    for group in &config.group {
+        for member in &group.members {
+            let full_name = format!("{} {}", member.name, group.name);
+            if full_name == fixed.person {
+                return Ok(full_name);
+            }
+            by_member.entry(&member.name).or_default().push(full_name);
+        }
+    }
+
+    match by_member.remove(fixed.person.as_str()) {
+        Some(mut names) if names.len() == 1 => Ok(names.remove(0)),
+        Some(_) => Err(ScheduleError::AmbiguousPerson(fixed.clone())),
+        None => Err(ScheduleError::UnknownPerson(fixed.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::get_weekdays;
+    use crate::schedule::create_people;
+
+    fn config(fixed: &str) -> Config {
+        toml::from_str(&format!(
+            r#"
+            [dates]
+            from = "2025-09-01"
+            to = "2025-09-30"
+            exceptions = ["2025-09-11"]
+            weekdays = ["Thu"]
+
+            [places]
+            places = ["Place A", "Place B"]
+
+            [[group]]
+            name = "Smith"
+            place = "Place A"
+
+            [[group.members]]
+            name = "Emily"
+            unavailable = {{ dates = ["2025-09-25"] }}
+
+            [[group.members]]
+            name = "William"
+
+            [[group]]
+            name = "Jones"
+            place = "Place B"
+
+            [[group.members]]
+            name = "William"
+
+            [rules]
+            sort = []
+            filter = []
+
+            {fixed}
+        "#
+        ))
+        .unwrap()
+    }
+
+    fn resolve(fixed: &str) -> Result<BTreeMap<NaiveDate, Vec<FixedSlot>>, ScheduleError> {
+        let config = config(fixed);
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let people = create_people(&config);
+        fixed_by_date(&config, &dates, &people)
+    }
+
+    fn fixed(date: &str, place: &str, person: &str) -> String {
+        format!("[[fixed]]\ndate = \"{date}\"\nplace = \"{place}\"\nperson = \"{person}\"\n")
+    }
+
+    #[test]
+    fn resolves_member_names_and_full_names() {
+        let by_date = resolve(&format!(
+            "{}{}",
+            fixed("2025-09-04", "Place A", "Emily"),
+            fixed("2025-09-04", "Place B", "William Jones")
+        ))
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 9, 4).unwrap();
+        assert_eq!(
+            by_date[&date],
+            vec![
+                FixedSlot {
+                    place: "Place A".to_string(),
+                    person: "Emily Smith".to_string()
+                },
+                FixedSlot {
+                    place: "Place B".to_string(),
+                    person: "William Jones".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_conflicts() {
+        let error = |fixed: String| resolve(&fixed).unwrap_err();
+
+        assert!(matches!(
+            error(fixed("2025-09-04", "Place A", "Nobody")),
+            ScheduleError::UnknownPerson(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-09-04", "Place A", "William")),
+            ScheduleError::AmbiguousPerson(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-09-04", "Place C", "Emily")),
+            ScheduleError::UnknownPlace(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-10-02", "Place A", "Emily")),
+            ScheduleError::DateOutOfRange(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-09-11", "Place A", "Emily")),
+            ScheduleError::NotAScheduleDate(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-09-05", "Place A", "Emily")),
+            ScheduleError::NotAScheduleDate(_)
+        ));
+        assert!(matches!(
+            error(fixed("2025-09-25", "Place A", "Emily")),
+            ScheduleError::Unavailable(_)
+        ));
+        assert!(matches!(
+            error(format!(
+                "{}{}",
+                fixed("2025-09-04", "Place A", "Emily"),
+                fixed("2025-09-04", "Place B", "Emily Smith")
+            )),
+            ScheduleError::DoubleBooking(_)
+        ));
+        assert!(matches!(
+            error(format!(
+                "{}{}",
+                fixed("2025-09-04", "Place A", "Emily"),
+                fixed("2025-09-04", "Place A", "William Smith")
+            )),
+            ScheduleError::TooManyForPlace(_)
+        ));
+    }
+
+    #[test]
+    fn error_message_names_the_fixed_assignment() {
+        let error = resolve(&fixed("2025-09-04", "Place A", "Nobody")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "fixed assignment of Nobody to Place A on 2025-09-04: unknown person"
+        );
+    }
+}
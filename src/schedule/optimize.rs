@@ -0,0 +1,403 @@
+//! improve a generated schedule by local search
+//!
+//! the greedy generator decides date by date, early choices can leave imbalances at the end of
+//! the period. The optimisation pass tries random moves and keeps every move that lowers the
+//! fairness objective of `[objective]` (hill climbing). A move either swaps the people of two
+//! slots or replaces the person of one slot. Fixed assignments are never moved, and every moved
+//! person has to be available and allowed by the filter rules at the new slot.
+//!
+//! the objective differs from `ScheduleStats::score` on purpose: it includes the services of the
+//! history and sums squared deviations from the mean, so every move that evens out the load
+//! counts. The score only looks at spreads (most minus fewest) of this schedule, which most
+//! single moves do not change, and hill climbing on it would stall.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::rules::ResolvedRules;
+use super::{
+    Assignment, GroupState, PersonState, PlaceCap, RuleContext, ScheduleError, ScheduleOptions,
+    create_people, group_states, people_from_assignments, random_seed,
+};
+use crate::config::{Config, Objective};
+
+/// improvements smaller than this are rounding noise
+const EPSILON: f64 = 1e-9;
+
+/// how long the optimisation pass may search
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OptimizeBudget {
+    /// number of moves to try
+    pub iterations: usize,
+    /// stop early once this time has passed
+    pub time: Option<Duration>,
+}
+
+/// the fairness objective before and after the optimisation pass, smaller is fairer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptimizeReport {
+    pub before: f64,
+    pub after: f64,
+    /// number of moves tried
+    pub iterations: usize,
+    /// number of moves kept
+    pub improvements: usize,
+}
+
+impl fmt::Display for OptimizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "objective {:.2} -> {:.2} ({} of {} moves kept)",
+            self.before, self.after, self.improvements, self.iterations
+        )
+    }
+}
+
+/// the fairness objective of the given people, smaller is fairer
+pub fn objective(people: &[PersonState], config: &Config) -> f64 {
+    let weights: &Objective = &config.objective;
+    let mut value = weights.total * squared_deviation(people.iter().map(|p| p.total_services()));
+    for weekday in &config.dates.weekdays {
+        value +=
+            weights.weekday * squared_deviation(people.iter().map(|p| p.weekday_count(*weekday)));
+    }
+    value += weights.different_place
+        * squared_deviation(people.iter().map(|p| p.different_place_services()));
+    value
+}
+
+/// sum of the squared deviations from the mean
+fn squared_deviation(values: impl Iterator<Item = usize>) -> f64 {
+    let values: Vec<f64> = values.map(|v| v as f64).collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum()
+}
+
+/// the people with the services of the history before the period and of the assignments
+pub(crate) fn people_with_history(
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &[Assignment],
+) -> Vec<PersonState> {
+    let mut all: Vec<Assignment> = options
+        .history
+        .iter()
+        .filter(|a| a.date < config.dates.from)
+        .cloned()
+        .collect();
+    all.sort_by_key(|a| a.date);
+    all.extend(assignments.iter().cloned());
+    people_from_assignments(config, &all)
+}
+
+/// improve the assignments of `create_schedule_with` in place
+///
+/// the same config, dates and options have to be given as for the generation, the random moves
+/// are drawn with `config.rules.seed`
+pub fn optimize_schedule(
+    dates: &[NaiveDate],
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &mut [Assignment],
+    budget: &OptimizeBudget,
+) -> Result<OptimizeReport, ScheduleError> {
+    let start = Instant::now();
+    let rules = options.rules.resolve(&config.rules)?;
+    let people = create_people(config);
+    let locked: HashSet<(NaiveDate, String, String)> =
+        super::fixed::fixed_by_date(config, dates, &people)?
+            .into_iter()
+            .flat_map(|(date, slots)| {
+                slots
+                    .into_iter()
+                    .map(move |slot| (date, slot.place, slot.person))
+            })
+            .collect();
+    let movable: Vec<usize> = (0..assignments.len())
+        .filter(|&idx| {
+            let a = &assignments[idx];
+            !a.is_unfilled() && !locked.contains(&(a.date, a.place.clone(), a.base_person.clone()))
+        })
+        .collect();
+
+    let mut rng = StdRng::seed_from_u64(config.rules.seed.unwrap_or_else(random_seed));
+    let mut services = Services::new(people_with_history(config, options, assignments));
+    let mut current = objective(&services.people, config);
+    let mut report = OptimizeReport {
+        before: current,
+        after: current,
+        iterations: 0,
+        improvements: 0,
+    };
+    if movable.is_empty() || people.is_empty() {
+        return Ok(report);
+    }
+
+    while report.iterations < budget.iterations
+        && budget.time.is_none_or(|time| start.elapsed() < time)
+    {
+        report.iterations += 1;
+
+        let first = movable[rng.random_range(0..movable.len())];
+        let change = if rng.random_bool(0.5) {
+            let second = movable[rng.random_range(0..movable.len())];
+            vec![
+                (first, assignments[second].base_person.clone()),
+                (second, assignments[first].base_person.clone()),
+            ]
+        } else {
+            let person = &people[rng.random_range(0..people.len())];
+            vec![(first, person.name())]
+        };
+        if !is_move(&change, assignments, &services) {
+            continue;
+        }
+        let before: Vec<(usize, String)> = change
+            .iter()
+            .map(|(idx, _)| (*idx, assignments[*idx].base_person.clone()))
+            .collect();
+
+        // the moved people are judged against the other slots, like a manual change
+        services.apply(&before, assignments, false);
+        let valid = services.violations(&change, assignments, &rules).is_empty();
+        if valid {
+            services.apply(&change, assignments, true);
+            let value = objective(&services.people, config);
+            if value < current - EPSILON {
+                for (idx, person) in &change {
+                    assignments[*idx].person = person.clone();
+                    assignments[*idx].base_person = person.clone();
+                }
+                current = value;
+                report.improvements += 1;
+                continue;
+            }
+            services.apply(&change, assignments, false);
+        }
+        services.apply(&before, assignments, true);
+    }
+
+    report.after = current;
+    Ok(report)
+}
+
+/// the people and groups with the services of the history and the schedule, updated move by move
+struct Services {
+    people: Vec<PersonState>,
+    groups: Vec<GroupState>,
+    place_cap: PlaceCap,
+    index: HashMap<String, usize>,
+}
+
+impl Services {
+    fn new(people: Vec<PersonState>) -> Self {
+        Self {
+            groups: group_states(&people),
+            place_cap: PlaceCap::new(&people),
+            index: people
+                .iter()
+                .enumerate()
+                .map(|(idx, p)| (p.name(), idx))
+                .collect(),
+            people,
+        }
+    }
+
+    /// register (or unregister) the service of each person at its slot
+    fn apply(&mut self, change: &[(usize, String)], assignments: &[Assignment], register: bool) {
+        for (idx, person) in change {
+            let Some(&p) = self.index.get(person) else {
+                continue;
+            };
+            let slot = &assignments[*idx];
+            let group = self.people[p].group();
+            if register {
+                self.people[p].register_service(slot.date, slot.place.clone());
+                self.groups[group].register(slot.date);
+            } else {
+                self.people[p].unregister_service(slot.date, slot.place.clone());
+                self.groups[group].unregister(slot.date);
+            }
+        }
+        self.place_cap.update(&self.people);
+    }
+
+    /// the violations of the change, the changed slots have to be unregistered
+    fn violations(
+        &self,
+        change: &[(usize, String)],
+        assignments: &[Assignment],
+        rules: &ResolvedRules,
+    ) -> Vec<super::Violation> {
+        let context = RuleContext {
+            date: NaiveDate::MIN,
+            place: "",
+            people: &self.people,
+            groups: &self.groups,
+            place_cap: &self.place_cap,
+            assignments,
+        };
+        super::violation::violations_in(change, &context, rules)
+    }
+}
+
+/// a change gives each slot another person of the config, each slot at most once
+fn is_move(change: &[(usize, String)], assignments: &[Assignment], services: &Services) -> bool {
+    let changed: HashSet<usize> = change.iter().map(|(idx, _)| *idx).collect();
+    changed.len() == change.len()
+        && change
+            .iter()
+            .any(|(idx, person)| assignments[*idx].base_person != *person)
+        // a person unknown to the config can not be moved
+        && change
+            .iter()
+            .all(|(_, person)| services.index.contains_key(person))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::dates::get_weekdays;
+    use crate::schedule::create_schedule_with;
+
+    fn config() -> Config {
+        let mut config = test_config();
+        config.rules.seed = Some(3);
+        config
+    }
+
+    fn budget(iterations: usize) -> OptimizeBudget {
+        OptimizeBudget {
+            iterations,
+            time: None,
+        }
+    }
+
+    #[test]
+    fn objective_is_zero_for_equal_people() {
+        let config = config();
+        let people = create_people(&config);
+        assert_eq!(objective(&people, &config), 0.0);
+
+        let mut people = people;
+        let place = people[0].place();
+        people[0].register_service(config.dates.from, place);
+        assert!(objective(&people, &config) > 0.0);
+    }
+
+    #[test]
+    fn optimize_schedule_never_gets_worse_and_keeps_the_rules() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = ScheduleOptions::default();
+        let mut assignments = create_schedule_with(&dates, &config, &options)
+            .unwrap()
+            .assignments;
+        let slots: Vec<(NaiveDate, String)> = assignments
+            .iter()
+            .map(|a| (a.date, a.place.clone()))
+            .collect();
+
+        let report =
+            optimize_schedule(&dates, &config, &options, &mut assignments, &budget(500)).unwrap();
+
+        assert_eq!(report.iterations, 500);
+        assert!(report.after < report.before, "{report}");
+        assert_eq!(
+            report.after,
+            objective(&people_from_assignments(&config, &assignments), &config)
+        );
+
+        // the slots stay the same, only the people move
+        let after: Vec<(NaiveDate, String)> = assignments
+            .iter()
+            .map(|a| (a.date, a.place.clone()))
+            .collect();
+        assert_eq!(slots, after);
+
+        let people = create_people(&config);
+        for a in &assignments {
+            let person = people.iter().find(|p| p.name() == a.person).unwrap();
+            assert!(person.is_available(a.date), "{} on {}", a.person, a.date);
+            assert_eq!(
+                assignments
+                    .iter()
+                    .filter(|b| b.date == a.date && b.person == a.person)
+                    .count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn optimize_schedule_respects_filters() {
+        let mut config = config();
+        config.rules.filter = vec![crate::config::Rule::FilterSamePlace];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = ScheduleOptions::default();
+        let mut assignments = create_schedule_with(&dates, &config, &options)
+            .unwrap()
+            .assignments;
+
+        optimize_schedule(&dates, &config, &options, &mut assignments, &budget(500)).unwrap();
+
+        let people = create_people(&config);
+        for a in &assignments {
+            let person = people.iter().find(|p| p.name() == a.person).unwrap();
+            assert_eq!(person.place(), a.place);
+        }
+    }
+
+    #[test]
+    fn optimize_schedule_keeps_fixed_assignments() {
+        let mut config = config();
+        let date = NaiveDate::from_ymd_opt(2025, 9, 4).unwrap();
+        config.fixed = vec![crate::config::Fixed {
+            date,
+            place: "Transport".to_string(),
+            person: "Emily".to_string(),
+        }];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = ScheduleOptions::default();
+        let mut assignments = create_schedule_with(&dates, &config, &options)
+            .unwrap()
+            .assignments;
+
+        optimize_schedule(&dates, &config, &options, &mut assignments, &budget(500)).unwrap();
+
+        assert!(
+            assignments
+                .iter()
+                .any(|a| a.date == date && a.place == "Transport" && a.person == "Emily Smith")
+        );
+    }
+
+    #[test]
+    fn optimize_schedule_without_budget_changes_nothing() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let options = ScheduleOptions::default();
+        let mut assignments = create_schedule_with(&dates, &config, &options)
+            .unwrap()
+            .assignments;
+        let people: Vec<String> = assignments.iter().map(|a| a.person.clone()).collect();
+
+        let report =
+            optimize_schedule(&dates, &config, &options, &mut assignments, &budget(0)).unwrap();
+
+        assert_eq!(report.before, report.after);
+        assert_eq!(report.iterations, 0);
+        let after: Vec<String> = assignments.iter().map(|a| a.person.clone()).collect();
+        assert_eq!(people, after);
+    }
+}
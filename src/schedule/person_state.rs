@@ -1,16 +1,57 @@
 use chrono::Datelike;
 use chrono::NaiveDate;
 use chrono::Weekday;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::config::Rule;
-use crate::config::Rules;
+use crate::config::Unavailable;
 
-#[derive(Debug, Default)]
+/// services of all members of a group
+///
+/// the schedule keeps one per group, indexed by `PersonState::group`
+#[derive(Clone, Debug, Default)]
 pub struct GroupState {
-    last_service: Option<NaiveDate>, // last date served in this group
+    services: BTreeMap<NaiveDate, usize>, // date → number of members serving
+}
+
+impl GroupState {
+    /// register a service of a member, the counterpart of `PersonState::register_service`
+    pub fn register(&mut self, date: NaiveDate) {
+        *self.services.entry(date).or_default() += 1;
+    }
+
+    /// unregister a service of a member, the counterpart of `PersonState::unregister_service`
+    pub fn unregister(&mut self, date: NaiveDate) {
+        if let Some(count) = self.services.get_mut(&date) {
+            *count -= 1;
+            if *count == 0 {
+                self.services.remove(&date);
+            }
+        }
+    }
+
+    /// last date served by any member of this group
+    pub fn last_service(&self) -> Option<NaiveDate> {
+        self.services.keys().next_back().copied()
+    }
+}
+
+/// the services of each group from the services of its members, indexed by `PersonState::group`
+pub fn group_states(people: &[PersonState]) -> Vec<GroupState> {
+    let len = people.iter().map(|p| p.group + 1).max().unwrap_or(0);
+    let mut groups = vec![GroupState::default(); len];
+    for person in people {
+        for service in &person.services {
+            groups[person.group].register(service.date);
+        }
+    }
+    groups
+}
+
+/// a single service of a person
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Service {
+    pub date: NaiveDate,
+    pub place: String,
 }
 
 #[derive(Clone, Debug)]
@@ -19,124 +60,95 @@ pub struct PersonState {
     place: String,
 
     // tracking
+    services: Vec<Service>, // ordered by date
     total_services: usize,
-    last_service: Option<NaiveDate>,
     weekday_counts: HashMap<Weekday, usize>, // weekday → count
     place_counts: HashMap<String, usize>,    // place → count
-    group_state: Rc<RefCell<GroupState>>,
+    group: usize,                            // index of the group in the config
     different_place_services: usize,
+
+    // availability
+    unavailable: Unavailable,
+    blocked_days: usize, // schedule dates this person could not be assigned to
 }
 
 impl PersonState {
-    pub fn new(name: String, place: String, group_state: Rc<RefCell<GroupState>>) -> Self {
+    pub fn new(name: String, place: String, group: usize) -> Self {
         Self {
             name,
             place,
+            services: Vec::new(),
             total_services: 0,
-            last_service: None,
             weekday_counts: HashMap::new(),
             place_counts: HashMap::new(),
-            group_state,
+            group,
             different_place_services: 0,
+            unavailable: Unavailable::default(),
+            blocked_days: 0,
         }
     }
 
+    /// Set the dates on which this person can not be scheduled
+    pub fn set_unavailable(&mut self, unavailable: Unavailable) {
+        self.unavailable = unavailable;
+    }
+
+    /// true if this person can be scheduled on the given date
+    pub fn is_available(&self, date: NaiveDate) -> bool {
+        !self.unavailable.contains(date)
+    }
+
+    /// Count a schedule date on which this person was excluded because of unavailability
+    pub fn register_blocked_day(&mut self) {
+        self.blocked_days += 1;
+    }
+
+    /// Register a service for this person, services may be registered in any order
     pub fn register_service(&mut self, date: NaiveDate, place: String) {
+        let service = Service {
+            date,
+            place: place.clone(),
+        };
+        let idx = self.services.partition_point(|s| *s <= service);
+        self.services.insert(idx, service);
+
         self.total_services += 1;
-        self.last_service = Some(date);
         *self.weekday_counts.entry(date.weekday()).or_default() += 1;
         *self.place_counts.entry(place.clone()).or_default() += 1;
-        *self.group_state.borrow_mut() = GroupState {
-            last_service: Some(date),
-        };
         if place != self.place {
             self.different_place_services += 1;
         }
     }
 
-    /// Unregister a service for this person
+    /// Unregister a service for this person, the exact inverse of `register_service`
     ///
-    /// This is used when swapping assignments between people
+    /// This is used when swapping assignments between people, unknown services are ignored
     pub fn unregister_service(&mut self, date: NaiveDate, place: String) {
-        if self.total_services > 0 {
-            self.total_services -= 1;
-        }
+        let service = Service { date, place };
+        let Ok(idx) = self.services.binary_search(&service) else {
+            return;
+        };
+        let Service { date, place } = self.services.remove(idx);
 
-        // Update weekday counts
-        if let Some(count) = self.weekday_counts.get_mut(&date.weekday())
-            && *count > 0
-        {
+        self.total_services -= 1;
+
+        if let Some(count) = self.weekday_counts.get_mut(&date.weekday()) {
             *count -= 1;
             if *count == 0 {
                 self.weekday_counts.remove(&date.weekday());
             }
         }
 
-        // Update place counts
-        if let Some(count) = self.place_counts.get_mut(&place)
-            && *count > 0
-        {
+        if let Some(count) = self.place_counts.get_mut(&place) {
             *count -= 1;
             if *count == 0 {
                 self.place_counts.remove(&place);
             }
         }
 
-        // Update different place services
-        if place != self.place && self.different_place_services > 0 {
+        if place != self.place {
             self.different_place_services -= 1;
         }
-
-        // Update last service date if needed
-        if self.last_service == Some(date) {
-            // Find the next most recent service date
-            // For simplicity, we'll just set it to None
-            // In a more complete implementation, we would track all service dates
-            self.last_service = None;
-        }
-
-        // Note: We don't update the group_state here because that would affect other people
-        // in the same group. In a real implementation, we might want to recalculate the
-        // group's last service date based on all members.
-    }
-
-    /// Convert a person into a sortable key tuple according to rules
-    pub(super) fn sort_key(&self, date: NaiveDate, place_id: &str, rules: &Rules) -> Vec<i64> {
-        rules
-            .sort
-            .iter()
-            .map(|rule| {
-                match rule {
-                    Rule::SortByLeastServices => self.total_services as i64,
-                    Rule::SortByOwnPlace => {
-                        if self.place == place_id { 0 } else { 1 } // smaller = preferred
-                    }
-                    Rule::SortByDifferentPlaceServices => {
-                        if self.place != place_id {
-                            self.different_place_services as i64
-                        } else {
-                            i64::MIN / self.different_place_services.max(1) as i64
-                        }
-                    }
-                    Rule::SortByLastService => {
-                        match self.last_service {
-                            Some(d) => (d.num_days_from_ce() / 7) as i64, // earlier last service is smaller, however calculate only on a weekly basis to not overule rules like SortByDifferentPlaceServices
-                            None => i64::MIN,
-                        }
-                    }
-                    Rule::SortByLessServicesAtSameWeekday => {
-                        *self.weekday_counts.get(&date.weekday()).unwrap_or(&0) as i64
-                    }
-                    Rule::SortByMaxDistanceInGroup => {
-                        match self.group_state.borrow().last_service {
-                            Some(d) => d.num_days_from_ce() as i64, // earlier last service is smaller
-                            None => i64::MIN,
-                        }
-                    }
-                    Rule::FilterSamePlace | Rule::FilterDifferentPlaceCap => 0,
-                }
-            })
-            .collect()
     }
 
     pub fn total_services(&self) -> usize {
@@ -151,10 +163,38 @@ impl PersonState {
         self.place_counts.clone()
     }
 
+    pub fn weekday_count(&self, weekday: Weekday) -> usize {
+        self.weekday_counts.get(&weekday).copied().unwrap_or(0)
+    }
+
+    /// all services of this person, ordered by date
+    pub fn services(&self) -> &[Service] {
+        &self.services
+    }
+
+    /// date of the latest service of this person
+    pub fn last_service(&self) -> Option<NaiveDate> {
+        self.services.last().map(|s| s.date)
+    }
+
+    /// index of the group of this person in the config
+    pub fn group(&self) -> usize {
+        self.group
+    }
+
+    /// date of the latest service of anyone in the group of this person
+    pub fn group_last_service(&self, groups: &[GroupState]) -> Option<NaiveDate> {
+        groups.get(self.group).and_then(GroupState::last_service)
+    }
+
     pub fn different_place_services(&self) -> usize {
         self.different_place_services
     }
 
+    pub fn blocked_days(&self) -> usize {
+        self.blocked_days
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -167,58 +207,104 @@ impl PersonState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Rule, Rules};
+    use crate::schedule::rules::{PlaceCap, RuleContext, RuleRegistry, builtin};
     use chrono::NaiveDate;
 
+    /// the sort keys of the built-in `rules` for `p`, as the scheduler compares them
+    fn sort_key(
+        p: &PersonState,
+        date: NaiveDate,
+        place: &str,
+        rules: &Rules,
+        groups: &[GroupState],
+    ) -> Vec<i64> {
+        let context = RuleContext {
+            date,
+            place,
+            people: std::slice::from_ref(p),
+            groups,
+            place_cap: &PlaceCap::default(),
+            assignments: &[],
+        };
+        RuleRegistry::default()
+            .resolve(rules)
+            .unwrap()
+            .sort_key(p, &context)
+    }
+
     fn d(y: i32, m: u32, d: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(y, m, d).unwrap()
     }
 
     #[test]
     fn new_person_has_clean_state() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
         assert_eq!(p.name, "Alice");
         assert_eq!(p.place, "A");
         assert_eq!(p.total_services, 0);
-        assert!(p.last_service.is_none());
-        assert!(p.group_state.borrow().last_service.is_none());
+        assert!(p.last_service().is_none());
+        assert!(p.group_last_service(&group_states(&[])).is_none());
         assert!(p.weekday_counts.is_empty());
+        assert_eq!(p.blocked_days, 0);
+    }
+
+    #[test]
+    fn is_available_respects_unavailable_dates() {
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
+        assert!(p.is_available(d(2025, 9, 4)));
+
+        p.set_unavailable(Unavailable {
+            dates: vec![d(2025, 9, 4)],
+            ranges: vec![crate::config::DateRange {
+                from: d(2025, 10, 1),
+                to: d(2025, 10, 14),
+            }],
+            weekdays: vec![Weekday::Fri],
+            ..Default::default()
+        });
+
+        assert!(!p.is_available(d(2025, 9, 4)));
+        assert!(!p.is_available(d(2025, 9, 5))); // Friday
+        assert!(!p.is_available(d(2025, 10, 1)));
+        assert!(!p.is_available(d(2025, 10, 14)));
+        assert!(p.is_available(d(2025, 10, 15)));
+        assert!(p.is_available(d(2025, 9, 3)));
     }
 
     #[test]
     fn register_service_updates_counters() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
         let date = d(2023, 9, 6); // Wednesday
         p.register_service(date, "B".to_string());
 
         assert_eq!(p.total_services, 1);
-        assert_eq!(p.last_service, Some(date));
-        assert_eq!(p.group_state.borrow().last_service, Some(date));
+        assert_eq!(p.last_service(), Some(date));
+        assert_eq!(
+            p.group_last_service(&group_states(std::slice::from_ref(&p))),
+            Some(date)
+        );
         assert_eq!(*p.weekday_counts.get(&Weekday::Wed).unwrap(), 1);
         assert_eq!(p.different_place_services, 0);
     }
 
     #[test]
-    fn register_service_updates_group_state_for_all_people() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut bob = PersonState::new("Bob".to_string(), "B".to_string(), Rc::clone(&group_state));
-        let alex = PersonState::new("Alex".to_string(), "B".to_string(), Rc::clone(&group_state));
+    fn register_service_updates_group_last_service_for_all_people() {
+        let mut bob = PersonState::new("Bob".to_string(), "B".to_string(), 0);
+        let alex = PersonState::new("Alex".to_string(), "B".to_string(), 0);
+        let carol = PersonState::new("Carol".to_string(), "B".to_string(), 1);
 
         let date = d(2023, 9, 6); // Wednesday
         bob.register_service(date, "B".to_string());
 
-        assert_eq!(alex.group_state.borrow().last_service, Some(date));
+        let groups = group_states(&[bob, alex.clone(), carol.clone()]);
+        assert_eq!(alex.group_last_service(&groups), Some(date));
+        assert_eq!(carol.group_last_service(&groups), None);
     }
 
     #[test]
     fn register_service_increments_weekday_counts() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
         let wed1 = d(2023, 9, 6);
         let wed2 = d(2023, 9, 13);
 
@@ -230,8 +316,7 @@ mod tests {
 
     #[test]
     fn register_service_increments_different_place_services() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
         let wed1 = d(2023, 9, 6);
         let wed2 = d(2023, 9, 13);
 
@@ -243,8 +328,7 @@ mod tests {
 
     #[test]
     fn register_service_increments_different_place_services2() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
         let wed1 = d(2023, 9, 6);
         let wed2 = d(2023, 9, 13);
 
@@ -256,12 +340,7 @@ mod tests {
 
     #[test]
     fn unregister_service_decrements_total_services() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
         let date = d(2023, 9, 6);
 
         p.register_service(date, "A".to_string());
@@ -273,12 +352,7 @@ mod tests {
 
     #[test]
     fn unregister_service_decrements_weekday_counts() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
         let wed = d(2023, 9, 6); // Wednesday
 
         p.register_service(wed, "A".to_string());
@@ -290,12 +364,7 @@ mod tests {
 
     #[test]
     fn unregister_service_decrements_different_place_services() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
         let date = d(2023, 9, 6);
 
         p.register_service(date, "B".to_string()); // Different place
@@ -307,106 +376,181 @@ mod tests {
 
     #[test]
     fn unregister_service_updates_last_service() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
         let date = d(2023, 9, 6);
 
         p.register_service(date, "A".to_string());
-        assert_eq!(p.last_service, Some(date));
+        assert_eq!(p.last_service(), Some(date));
 
         p.unregister_service(date, "A".to_string());
-        assert_eq!(p.last_service, None);
+        assert_eq!(p.last_service(), None);
     }
 
     #[test]
-    fn sort_key_least_services() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Charlie".to_string(),
-            "C".to_string(),
-            Rc::clone(&group_state),
+    fn unregister_service_restores_previous_last_service() {
+        let mut alice = PersonState::new("Alice".to_string(), "A".to_string(), 0);
+        let mut bob = PersonState::new("Bob".to_string(), "A".to_string(), 0);
+
+        let mut groups = vec![GroupState::default()];
+
+        alice.register_service(d(2023, 9, 6), "A".to_string());
+        groups[0].register(d(2023, 9, 6));
+        bob.register_service(d(2023, 9, 13), "A".to_string());
+        groups[0].register(d(2023, 9, 13));
+        alice.register_service(d(2023, 9, 20), "B".to_string());
+        groups[0].register(d(2023, 9, 20));
+        assert_eq!(bob.group_last_service(&groups), Some(d(2023, 9, 20)));
+
+        alice.unregister_service(d(2023, 9, 20), "B".to_string());
+        groups[0].unregister(d(2023, 9, 20));
+        assert_eq!(alice.last_service(), Some(d(2023, 9, 6)));
+        assert_eq!(alice.group_last_service(&groups), Some(d(2023, 9, 13)));
+
+        bob.unregister_service(d(2023, 9, 13), "A".to_string());
+        groups[0].unregister(d(2023, 9, 13));
+        assert_eq!(alice.group_last_service(&groups), Some(d(2023, 9, 6)));
+    }
+
+    #[test]
+    fn register_and_unregister_are_inverses() {
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
+        p.register_service(d(2023, 9, 6), "B".to_string());
+        let before = format!("{p:?}");
+
+        p.register_service(d(2023, 9, 13), "C".to_string());
+        p.unregister_service(d(2023, 9, 13), "C".to_string());
+
+        assert_eq!(format!("{p:?}"), before);
+        assert_eq!(p.different_place_services(), 0);
+        assert_eq!(p.place_counts().get("C"), None);
+    }
+
+    #[test]
+    fn unregister_unknown_service_changes_nothing() {
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
+        p.register_service(d(2023, 9, 6), "B".to_string());
+
+        p.unregister_service(d(2023, 9, 6), "C".to_string());
+        p.unregister_service(d(2023, 9, 13), "B".to_string());
+
+        assert_eq!(p.total_services(), 1);
+        assert_eq!(p.last_service(), Some(d(2023, 9, 6)));
+    }
+
+    #[test]
+    fn person_state_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PersonState>();
+        assert_send_sync::<GroupState>();
+        assert_send_sync::<crate::config::Config>();
+        assert_send_sync::<crate::schedule::Assignment>();
+    }
+
+    #[test]
+    fn services_are_ordered_by_date() {
+        let mut p = PersonState::new("Bob".to_string(), "B".to_string(), 0);
+
+        p.register_service(d(2023, 9, 13), "C".to_string());
+        p.register_service(d(2023, 9, 6), "B".to_string());
+
+        assert_eq!(
+            p.services(),
+            [
+                Service {
+                    date: d(2023, 9, 6),
+                    place: "B".to_string()
+                },
+                Service {
+                    date: d(2023, 9, 13),
+                    place: "C".to_string()
+                },
+            ]
         );
+        assert_eq!(p.last_service(), Some(d(2023, 9, 13)));
+    }
+
+    #[test]
+    fn sort_key_least_services() {
+        let mut p = PersonState::new("Charlie".to_string(), "C".to_string(), 0);
         let date = d(2023, 9, 6);
 
         // no services yet
         let rules = Rules {
             filter: vec![],
             sort: vec![Rule::SortByLeastServices],
+            ..Default::default()
         };
-        assert_eq!(p.sort_key(date, "C", &rules), vec![0]);
+        assert_eq!(sort_key(&p, date, "C", &rules, &[]), vec![0]);
 
         // after one service
         p.register_service(date, "C".to_string());
-        assert_eq!(p.sort_key(date, "C", &rules), vec![1]);
+        assert_eq!(sort_key(&p, date, "C", &rules, &[]), vec![1]);
 
         // after two services
         p.register_service(date, "C".to_string());
-        assert_eq!(p.sort_key(date, "C", &rules), vec![2]);
+        assert_eq!(sort_key(&p, date, "C", &rules, &[]), vec![2]);
     }
 
     #[test]
     fn sort_key_own_place_preferred() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let p = PersonState::new("Dana".to_string(), "X".to_string(), Rc::clone(&group_state));
+        let p = PersonState::new("Dana".to_string(), "X".to_string(), 0);
         let rules = Rules {
             filter: vec![],
             sort: vec![Rule::SortByOwnPlace],
+            ..Default::default()
         };
         let date = d(2023, 9, 6);
 
-        assert_eq!(p.sort_key(date, "X", &rules), vec![0]);
-        assert_eq!(p.sort_key(date, "Y", &rules), vec![1]);
+        assert_eq!(sort_key(&p, date, "X", &rules, &[]), vec![0]);
+        assert_eq!(sort_key(&p, date, "Y", &rules, &[]), vec![1]);
     }
 
     #[test]
     fn sort_key_last_service_earlier_is_smaller() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Eve".to_string(), "Z".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Eve".to_string(), "Z".to_string(), 0);
         let rules = Rules {
             filter: vec![],
             sort: vec![Rule::SortByLastService],
+            ..Default::default()
         };
         let date1 = d(2023, 9, 1);
         let date2 = d(2023, 9, 10);
 
         p.register_service(date1, "Z".to_string());
-        let key1 = p.sort_key(date2, "Z", &rules)[0];
+        let key1 = sort_key(&p, date2, "Z", &rules, &[])[0];
 
         p.register_service(date2, "Z".to_string());
-        let key2 = p.sort_key(date2, "Z", &rules)[0];
+        let key2 = sort_key(&p, date2, "Z", &rules, &[])[0];
 
         assert!(key1 < key2); // earlier service gives smaller value
     }
 
     #[test]
     fn sort_key_max_distance_in_group_prefers_longer_gap() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Frank".to_string(),
-            "F".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Frank".to_string(), "F".to_string(), 0);
         let rules = Rules {
             filter: vec![],
             sort: vec![Rule::SortByMaxDistanceInGroup],
+            ..Default::default()
         };
         let start = d(2023, 1, 1);
 
-        let key0 = p.sort_key(start, "F", &rules)[0];
+        let key = |p: &PersonState| {
+            let groups = group_states(std::slice::from_ref(p));
+            sort_key(p, start, "F", &rules, &groups)[0]
+        };
+
+        let key0 = key(&p);
 
         let work1 = d(2022, 12, 13);
         p.register_service(work1, "F".to_string());
-        let key1 = p.sort_key(start, "F", &rules)[0];
+        let key1 = key(&p);
 
         assert!(key0 < key1);
 
         let work2 = d(2022, 12, 15);
         p.register_service(work2, "F".to_string());
-        let key2 = p.sort_key(start, "F", &rules)[0];
+        let key2 = key(&p);
 
         assert!(key1 < key2);
     }
@@ -416,31 +560,30 @@ mod tests {
         let rules = Rules {
             filter: vec![],
             sort: vec![Rule::SortByMaxDistanceInGroup],
+            ..Default::default()
         };
 
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut stefan = PersonState::new(
-            "Frank".to_string(),
-            "F".to_string(),
-            Rc::clone(&group_state),
-        );
-        let martina = PersonState::new(
-            "Martina".to_string(),
-            "F".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut stefan = PersonState::new("Frank".to_string(), "F".to_string(), 0);
+        let martina = PersonState::new("Martina".to_string(), "F".to_string(), 0);
+        let petraq = PersonState::new("Petraq".to_string(), "F".to_string(), 1);
         stefan.register_service(d(2025, 9, 4), "F".to_string());
-        let key_stefan = stefan.sort_key(d(2025, 9, 11), "F", &rules)[0];
 
-        let key_martina = martina.sort_key(d(2025, 9, 11), "F", &rules)[0];
+        let people = [stefan, martina, petraq];
+        let groups = group_states(&people);
+        let context = RuleContext {
+            date: d(2025, 9, 11),
+            place: "F",
+            people: &people,
+            groups: &groups,
+            place_cap: &PlaceCap::default(),
+            assignments: &[],
+        };
+        let key = |p: &PersonState| builtin(&rules.sort[0]).unwrap().sort_key(p, &context);
+        let (key_stefan, key_martina, key_petraq) =
+            (key(&people[0]), key(&people[1]), key(&people[2]));
 
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let petraq = PersonState::new(
-            "Petraq".to_string(),
-            "F".to_string(),
-            Rc::clone(&group_state2),
-        );
-        let key_petraq = petraq.sort_key(d(2025, 9, 11), "F", &rules)[0];
+        // martina shares the group of stefan, who served recently
+        assert_eq!(key_stefan, key_martina);
 
         assert!(key_petraq < key_stefan);
         assert!(key_petraq < key_martina);
@@ -451,17 +594,11 @@ mod tests {
         let date = d(2023, 9, 10); // Sunday
 
         // Person A: 1 service in own place
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut a = PersonState::new(
-            "Alice".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut a = PersonState::new("Alice".to_string(), "G".to_string(), 0);
         a.register_service(d(2023, 9, 1), "G".to_string());
 
         // Person B: 2 services, not in own place
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), Rc::clone(&group_state2));
+        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), 1);
         b.register_service(d(2023, 9, 2), "H".to_string());
         b.register_service(d(2023, 9, 3), "H".to_string());
 
@@ -469,10 +606,11 @@ mod tests {
         let rules = Rules {
             sort: vec![Rule::SortByLeastServices, Rule::SortByOwnPlace],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_a = a.sort_key(date, "G", &rules);
-        let key_b = b.sort_key(date, "G", &rules);
+        let key_a = sort_key(&a, date, "G", &rules, &[]);
+        let key_b = sort_key(&b, date, "G", &rules, &[]);
 
         // Alice has fewer services, is at place, and has place distance
         // Bob has more services, not at place
@@ -484,17 +622,11 @@ mod tests {
         let date = d(2023, 9, 10); // Sunday
 
         // Person A: 1 service in own place
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut a = PersonState::new(
-            "Alice".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut a = PersonState::new("Alice".to_string(), "G".to_string(), 0);
         a.register_service(d(2023, 9, 1), "G".to_string());
 
         // Person B: 2 services, not in own place
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), Rc::clone(&group_state2));
+        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), 1);
         b.register_service(d(2023, 9, 2), "H".to_string());
         b.register_service(d(2023, 9, 3), "H".to_string());
 
@@ -502,10 +634,11 @@ mod tests {
         let rules = Rules {
             sort: vec![Rule::SortByOwnPlace, Rule::SortByLeastServices],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_a = a.sort_key(date, "H", &rules);
-        let key_b = b.sort_key(date, "H", &rules);
+        let key_a = sort_key(&a, date, "H", &rules, &[]);
+        let key_b = sort_key(&b, date, "H", &rules, &[]);
 
         // Alice is at place, Bob isn’t → Alice should win immediately
         assert!(key_b < key_a, "Bob should sort before Alice");
@@ -516,31 +649,22 @@ mod tests {
         let date = d(2023, 9, 10);
 
         // Xavier: at place, but very recent place service
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut x = PersonState::new(
-            "Xavier".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut x = PersonState::new("Xavier".to_string(), "G".to_string(), 0);
         x.register_service(d(2023, 9, 9), "G".to_string()); // just 1 day ago
 
         // Yara: not at place, but longer service distance
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let mut y = PersonState::new(
-            "Yara".to_string(),
-            "H".to_string(),
-            Rc::clone(&group_state2),
-        );
+        let mut y = PersonState::new("Yara".to_string(), "H".to_string(), 1);
         y.register_service(d(2023, 8, 1), "H".to_string()); // long ago
 
         // Rules: own place → max distance at place → fewest services
         let rules = Rules {
             sort: vec![Rule::SortByOwnPlace, Rule::SortByLastService],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_x = x.sort_key(date, "G", &rules);
-        let key_y = y.sort_key(date, "G", &rules);
+        let key_x = sort_key(&x, date, "G", &rules, &[]);
+        let key_y = sort_key(&y, date, "G", &rules, &[]);
 
         // Even though Y has better place distance, X is in the right place
         // Since SortByOwnPlace comes first, Xavier must win
@@ -553,10 +677,11 @@ mod tests {
         let flipped_rules = Rules {
             sort: vec![Rule::SortByLastService, Rule::SortByOwnPlace],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_x2 = x.sort_key(date, "G", &flipped_rules);
-        let key_y2 = y.sort_key(date, "G", &flipped_rules);
+        let key_x2 = sort_key(&x, date, "G", &flipped_rules, &[]);
+        let key_y2 = sort_key(&y, date, "G", &flipped_rules, &[]);
 
         // Now Yara should win because distance is more important than place
         assert!(
@@ -570,27 +695,22 @@ mod tests {
         let date = d(2023, 9, 10); // Sunday
 
         // Person A: 1 service in own place
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut a = PersonState::new(
-            "Alice".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut a = PersonState::new("Alice".to_string(), "G".to_string(), 0);
         a.register_service(d(2023, 9, 1), "G".to_string());
 
         // Person B: 2 services, not in own place
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), Rc::clone(&group_state2));
+        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), 1);
         b.register_service(d(2023, 9, 1), "G".to_string());
 
         // Rules: fewest services → own place → longest distance at place
         let rules = Rules {
             sort: vec![Rule::SortByDifferentPlaceServices],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_a = a.sort_key(date, "I", &rules);
-        let key_b = b.sort_key(date, "I", &rules);
+        let key_a = sort_key(&a, date, "I", &rules, &[]);
+        let key_b = sort_key(&b, date, "I", &rules, &[]);
 
         // Alice has fewer services in a different place, prefer Bob
         assert!(key_a < key_b, "Alice should sort before Bob");
@@ -601,27 +721,22 @@ mod tests {
         let date = d(2023, 9, 10); // Sunday
 
         // Person A: 1 service in own place
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut a = PersonState::new(
-            "Alice".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut a = PersonState::new("Alice".to_string(), "G".to_string(), 0);
         a.register_service(d(2023, 9, 1), "G".to_string());
 
         // Person B: 2 services, not in own place
-        let group_state2 = Rc::new(RefCell::new(GroupState::default()));
-        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), Rc::clone(&group_state2));
+        let mut b = PersonState::new("Bob".to_string(), "H".to_string(), 1);
         b.register_service(d(2023, 9, 1), "G".to_string());
 
         // Rules: fewest services → own place → longest distance at place
         let rules = Rules {
             sort: vec![Rule::SortByDifferentPlaceServices],
             filter: vec![],
+            ..Default::default()
         };
 
-        let key_a = a.sort_key(date, "G", &rules);
-        let key_b = b.sort_key(date, "G", &rules);
+        let key_a = sort_key(&a, date, "G", &rules, &[]);
+        let key_b = sort_key(&b, date, "G", &rules, &[]);
 
         // Alice has fewer services in a different place, usually we would prefer Bob
         // but as we need a service for Alice place, prefer Alice
@@ -630,12 +745,7 @@ mod tests {
 
     #[test]
     fn place_counts_tracks_services_by_place() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Alice".to_string(),
-            "A".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Alice".to_string(), "A".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "A".to_string());
         p.register_service(d(2023, 9, 2), "B".to_string());
@@ -650,8 +760,7 @@ mod tests {
 
     #[test]
     fn place_counts_decrements_on_unregister() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Bob".to_string(), "X".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Bob".to_string(), "X".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "X".to_string());
         p.register_service(d(2023, 9, 2), "Y".to_string());
@@ -668,12 +777,7 @@ mod tests {
 
     #[test]
     fn place_counts_removes_zero_counts() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Charlie".to_string(),
-            "P".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Charlie".to_string(), "P".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "Q".to_string());
         assert_eq!(*p.place_counts().get("Q").unwrap(), 1);
@@ -684,8 +788,7 @@ mod tests {
 
     #[test]
     fn place_counts_empty_for_new_person() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let p = PersonState::new("Dave".to_string(), "D".to_string(), Rc::clone(&group_state));
+        let p = PersonState::new("Dave".to_string(), "D".to_string(), 0);
 
         let place_counts = p.place_counts();
         assert!(place_counts.is_empty());
@@ -693,8 +796,7 @@ mod tests {
 
     #[test]
     fn place_counts_multiple_services_same_place() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Eve".to_string(), "E".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Eve".to_string(), "E".to_string(), 0);
 
         // Register 5 services at the same place
         for i in 1..=5 {
@@ -708,12 +810,7 @@ mod tests {
 
     #[test]
     fn place_counts_with_many_different_places() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Frank".to_string(),
-            "F".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Frank".to_string(), "F".to_string(), 0);
 
         // Register services at 5 different places
         p.register_service(d(2023, 9, 1), "Place1".to_string());
@@ -733,12 +830,7 @@ mod tests {
 
     #[test]
     fn place_counts_unregister_nonexistent_place() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Grace".to_string(),
-            "G".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Grace".to_string(), "G".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "G".to_string());
 
@@ -752,12 +844,7 @@ mod tests {
 
     #[test]
     fn place_counts_unregister_when_zero() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new(
-            "Henry".to_string(),
-            "H".to_string(),
-            Rc::clone(&group_state),
-        );
+        let mut p = PersonState::new("Henry".to_string(), "H".to_string(), 0);
 
         // Don't register any services
         // Try to unregister - should handle gracefully
@@ -769,8 +856,7 @@ mod tests {
 
     #[test]
     fn place_counts_unregister_multiple_times() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Iris".to_string(), "I".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Iris".to_string(), "I".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "I".to_string());
         p.register_service(d(2023, 9, 2), "I".to_string());
@@ -792,8 +878,7 @@ mod tests {
 
     #[test]
     fn place_counts_mixed_register_and_unregister() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Jack".to_string(), "J".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Jack".to_string(), "J".to_string(), 0);
 
         // Register services at two places
         p.register_service(d(2023, 9, 1), "J".to_string());
@@ -818,8 +903,7 @@ mod tests {
 
     #[test]
     fn place_counts_getter_returns_clone() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Kate".to_string(), "K".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Kate".to_string(), "K".to_string(), 0);
 
         p.register_service(d(2023, 9, 1), "K".to_string());
 
@@ -837,8 +921,7 @@ mod tests {
 
     #[test]
     fn place_counts_with_special_place_names() {
-        let group_state = Rc::new(RefCell::new(GroupState::default()));
-        let mut p = PersonState::new("Leo".to_string(), "L".to_string(), Rc::clone(&group_state));
+        let mut p = PersonState::new("Leo".to_string(), "L".to_string(), 0);
 
         // Test with special characters and spaces
         p.register_service(d(2023, 9, 1), "Place-A".to_string());
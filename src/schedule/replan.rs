@@ -0,0 +1,353 @@
+//! re-plan a schedule around assignments that have to stay
+//!
+//! after an absence, the assignments before it are kept and count as served, so the new
+//! assignments stay fair relative to what everyone has already done. Locked assignments are kept
+//! as if they were fixed in the config
+
+use chrono::NaiveDate;
+
+use super::{Assignment, Schedule, ScheduleError, ScheduleOptions, create_schedule_with};
+use crate::config::{Config, DateRange, Fixed};
+
+/// a person who can not serve from `from` to `to`, both dates are included
+#[derive(Debug, Clone, PartialEq)]
+pub struct Absence {
+    /// full name ("Emily Smith") or a member name that is unique across all groups ("Emily")
+    pub person: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// a copy of the config with the locked assignments added to its fixed assignments
+///
+/// locked assignments outside the dates of the config, unfilled slots and assignments the config
+/// already fixes are left out. Generating with the returned config only fills the other slots
+pub fn lock_assignments(config: &Config, locked: &[Assignment]) -> Config {
+    let mut locked_config = config.clone();
+    for a in locked {
+        if a.date < config.dates.from || a.date > config.dates.to || a.is_unfilled() {
+            continue;
+        }
+        let fixed = Fixed {
+            date: a.date,
+            place: a.place.clone(),
+            person: a.base_person.clone(),
+        };
+        let already_fixed = locked_config.fixed.iter().any(|other| {
+            other.date == fixed.date
+                && other.place == fixed.place
+                && super::fixed::resolve_person(config, other).as_ref() == Ok(&fixed.person)
+        });
+        if !already_fixed {
+            locked_config.fixed.push(fixed);
+        }
+    }
+    locked_config
+}
+
+/// the result of `replan_schedule`
+#[derive(Debug, Clone)]
+pub struct Replanned {
+    pub schedule: Schedule,
+    /// the fixed assignments of the config for the absent person during the absence, they were
+    /// planned again like any other slot
+    pub dropped: Vec<Fixed>,
+}
+
+/// keep the past and the locked assignments, regenerate everything from `absence.from` on
+///
+/// assignments before `absence.from` are kept unchanged. Locked assignments and fixed assignments
+/// of the config from then on are kept, except the ones of the absent person during the absence.
+/// `Schedule::assignments` contains the kept and the regenerated assignments ordered by date,
+/// `unfilled` and `trace` only cover the regenerated slots
+pub fn replan_schedule(
+    dates: &[NaiveDate],
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &[Assignment],
+    locked: &[Assignment],
+    absence: &Absence,
+) -> Result<Replanned, ScheduleError> {
+    if absence.to < absence.from {
+        return Err(ScheduleError::InvalidAbsence(format!(
+            "{} is before {}",
+            absence.to, absence.from
+        )));
+    }
+    let person = super::fixed::resolve_person(
+        config,
+        &Fixed {
+            date: absence.from,
+            place: String::new(),
+            person: absence.person.clone(),
+        },
+    )
+    .map_err(|_| ScheduleError::InvalidAbsence(format!("unknown person '{}'", absence.person)))?;
+    let absent =
+        |a: &Assignment| a.base_person == person && a.date >= absence.from && a.date <= absence.to;
+
+    let start = absence.from.max(config.dates.from);
+    let mut replan = config.clone();
+    replan.dates.from = start;
+    for group in &mut replan.group {
+        for member in &mut group.members {
+            if format!("{} {}", member.name, group.name) == person {
+                member.unavailable.ranges.push(DateRange {
+                    from: absence.from,
+                    to: absence.to,
+                });
+            }
+        }
+    }
+
+    // the fixed assignments of the config and the locked ones of the future stay
+    let (dropped, fixed): (Vec<Fixed>, Vec<Fixed>) = replan
+        .fixed
+        .drain(..)
+        .filter(|fixed| fixed.date >= start)
+        .partition(|fixed| {
+            fixed.date <= absence.to
+                && super::fixed::resolve_person(config, fixed).as_ref() == Ok(&person)
+        });
+    replan.fixed = fixed;
+    let locked: Vec<Assignment> = locked.iter().filter(|a| !absent(a)).cloned().collect();
+    let replan = lock_assignments(&replan, &locked);
+
+    let past: Vec<Assignment> = assignments
+        .iter()
+        .filter(|a| a.date < start)
+        .cloned()
+        .collect();
+    let mut history: Vec<Assignment> = options
+        .history
+        .iter()
+        .filter(|a| a.date < config.dates.from)
+        .cloned()
+        .collect();
+    history.extend(past.iter().filter(|a| !a.is_unfilled()).cloned());
+    let options = ScheduleOptions {
+        history,
+        rules: options.rules.clone(),
+        explain: options.explain,
+    };
+
+    let dates: Vec<NaiveDate> = dates.iter().copied().filter(|d| *d >= start).collect();
+    let mut schedule = create_schedule_with(&dates, &replan, &options)?;
+
+    let mut all = past;
+    all.append(&mut schedule.assignments);
+    schedule.assignments = all;
+    Ok(Replanned { schedule, dropped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+    use crate::dates::get_weekdays;
+    use crate::schedule::create_schedule;
+
+    fn config() -> Config {
+        let mut config = test_config();
+        config.rules.seed = Some(5);
+        config
+    }
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    #[test]
+    fn replan_keeps_the_past_and_respects_the_absence() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let before = create_schedule(&dates, &config).unwrap().assignments;
+        let absence = Absence {
+            person: "Emily".to_string(),
+            from: d(10),
+            to: d(23),
+        };
+
+        let after = replan_schedule(
+            &dates,
+            &config,
+            &ScheduleOptions::default(),
+            &before,
+            &[],
+            &absence,
+        )
+        .unwrap()
+        .schedule;
+
+        let key = |a: &Assignment| (a.date, a.place.clone(), a.person.clone());
+        let past = |list: &[Assignment]| -> Vec<_> {
+            list.iter().filter(|a| a.date < d(10)).map(key).collect()
+        };
+        assert_eq!(past(&before), past(&after.assignments));
+        assert!(
+            !after
+                .assignments
+                .iter()
+                .any(|a| a.person == "Emily Smith" && a.date >= d(10) && a.date <= d(23))
+        );
+        // every slot from the absence on is planned again
+        let slots = |list: &[Assignment]| list.iter().filter(|a| a.date >= d(10)).count();
+        assert_eq!(slots(&before), slots(&after.assignments));
+        assert!(after.assignments.windows(2).all(|w| w[0].date <= w[1].date));
+
+        // the past counts as served
+        let emily = after
+            .people
+            .iter()
+            .find(|p| p.name() == "Emily Smith")
+            .unwrap();
+        assert_eq!(
+            emily.total_services(),
+            after
+                .assignments
+                .iter()
+                .filter(|a| a.base_person == "Emily Smith")
+                .count()
+        );
+    }
+
+    #[test]
+    fn replan_keeps_locked_assignments() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let before = create_schedule(&dates, &config).unwrap().assignments;
+        let future: Vec<Assignment> = before.iter().filter(|a| a.date >= d(10)).cloned().collect();
+        let absent = future[0].base_person.clone();
+        let kept = future
+            .iter()
+            .find(|a| a.base_person != absent)
+            .unwrap()
+            .clone();
+        let dropped = future[0].clone();
+        let absence = Absence {
+            person: absent.clone(),
+            from: d(10),
+            to: d(30),
+        };
+
+        let after = replan_schedule(
+            &dates,
+            &config,
+            &ScheduleOptions::default(),
+            &before,
+            &[kept.clone(), dropped.clone()],
+            &absence,
+        )
+        .unwrap()
+        .schedule;
+
+        assert!(
+            after
+                .assignments
+                .iter()
+                .any(|a| a.date == kept.date && a.place == kept.place && a.person == kept.person)
+        );
+        // a locked assignment of the absent person during the absence is planned again
+        assert!(
+            !after
+                .assignments
+                .iter()
+                .any(|a| a.person == absent && a.date >= d(10))
+        );
+    }
+
+    #[test]
+    fn replan_drops_fixed_assignments_of_the_absent_person() {
+        let mut config = config();
+        let fixed = |day, person: &str| Fixed {
+            date: d(day),
+            place: "Office".to_string(),
+            person: person.to_string(),
+        };
+        config.fixed = vec![fixed(11, "Emily"), fixed(25, "Emily"), fixed(12, "William")];
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let before = create_schedule(&dates, &config).unwrap().assignments;
+        let absence = Absence {
+            person: "Emily Smith".to_string(),
+            from: d(10),
+            to: d(20),
+        };
+
+        let after = replan_schedule(
+            &dates,
+            &config,
+            &ScheduleOptions::default(),
+            &before,
+            &[],
+            &absence,
+        )
+        .unwrap();
+
+        assert_eq!(after.dropped, vec![fixed(11, "Emily")]);
+        let assigned = |day, person: &str| {
+            after
+                .schedule
+                .assignments
+                .iter()
+                .any(|a| a.date == d(day) && a.place == "Office" && a.person == person)
+        };
+        assert!(!assigned(11, "Emily Smith"));
+        assert!(assigned(25, "Emily Smith"));
+        assert!(assigned(12, "William Smith"));
+    }
+
+    #[test]
+    fn locked_assignments_stay_when_generating_again() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let before = create_schedule(&dates, &config).unwrap().assignments;
+        let locked = vec![before[0].clone(), before[before.len() - 1].clone()];
+
+        let mut locked_config = lock_assignments(&config, &locked);
+        assert_eq!(locked_config.fixed.len(), config.fixed.len() + 2);
+        // locking twice does not fix a slot twice, unfilled slots are not locked
+        let unfilled = Assignment::unfilled(d(4), "Office".to_string());
+        let again = lock_assignments(&locked_config, &[locked[0].clone(), unfilled]);
+        assert_eq!(again.fixed, locked_config.fixed);
+
+        for seed in [1, 2, 3] {
+            locked_config.rules.seed = Some(seed);
+            let after = create_schedule(&dates, &locked_config).unwrap().assignments;
+            for a in &locked {
+                assert!(after.iter().any(|b| b.date == a.date
+                    && b.place == a.place
+                    && b.base_person == a.base_person));
+            }
+        }
+    }
+
+    #[test]
+    fn replan_reports_invalid_absences() {
+        let config = config();
+        let dates = get_weekdays(&config.dates.from, &config.dates.to, &config.dates.weekdays);
+        let replan = |person: &str, from: u32, to: u32| {
+            let absence = Absence {
+                person: person.to_string(),
+                from: d(from),
+                to: d(to),
+            };
+            replan_schedule(
+                &dates,
+                &config,
+                &ScheduleOptions::default(),
+                &[],
+                &[],
+                &absence,
+            )
+        };
+
+        assert!(matches!(
+            replan("Nobody", 10, 12),
+            Err(ScheduleError::InvalidAbsence(_))
+        ));
+        assert!(matches!(
+            replan("Emily", 12, 10),
+            Err(ScheduleError::InvalidAbsence(_))
+        ));
+    }
+}
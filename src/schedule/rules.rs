@@ -0,0 +1,640 @@
+//! sort and filter rules used to pick a person for a slot
+//!
+//! every rule of `[rules]` is looked up by name in a `RuleRegistry`, the built-in rules are
+//! always registered. Library users can add their own rules:
+//!
+//! ```
+//! use duty_roster::schedule::{PersonState, RuleContext, RuleRegistry, SchedulingRule};
+//!
+//! /// prefer people with a short name
+//! struct SortByNameLength;
+//!
+//! impl SchedulingRule for SortByNameLength {
+//!     fn name(&self) -> &str {
+//!         "sortByNameLength"
+//!     }
+//!
+//!     fn sort_key(&self, candidate: &PersonState, _context: &RuleContext) -> i64 {
+//!         candidate.name().len() as i64
+//!     }
+//! }
+//!
+//! let mut registry = RuleRegistry::default();
+//! registry.register(SortByNameLength);
+//! assert!(registry.get("sortByNameLength").is_some());
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{Datelike, NaiveDate};
+
+use super::{Assignment, GroupState, PersonState, ScheduleError};
+use crate::config::{Rule, Rules, SortMode};
+
+/// the state of the schedule a rule can look at
+pub struct RuleContext<'a> {
+    /// date of the slot to fill
+    pub date: NaiveDate,
+    /// place of the slot to fill
+    pub place: &'a str,
+    /// all people including the candidates, with their services so far
+    pub people: &'a [PersonState],
+    /// the services of each group, indexed by `PersonState::group`
+    pub groups: &'a [GroupState],
+    /// the people limited by `filterDifferentPlaceCap`
+    pub place_cap: &'a PlaceCap,
+    /// assignments created so far
+    pub assignments: &'a [Assignment],
+}
+
+/// the people of places larger than the smallest place and their fewest different-place
+/// services, computed once instead of for every candidate
+#[derive(Debug, Clone, Default)]
+pub struct PlaceCap {
+    places: BTreeSet<String>,
+    min_services: usize,
+}
+
+impl PlaceCap {
+    /// the eligible places of the people, they do not change while a schedule is built
+    pub fn new(people: &[PersonState]) -> Self {
+        let mut place_sizes: HashMap<String, usize> = HashMap::new();
+        for p in people {
+            *place_sizes.entry(p.place()).or_default() += 1;
+        }
+        let min_size = place_sizes.values().copied().min().unwrap_or(0);
+        let mut cap = Self {
+            places: place_sizes
+                .into_iter()
+                .filter(|(_, size)| *size > min_size)
+                .map(|(place, _)| place)
+                .collect(),
+            min_services: 0,
+        };
+        cap.update(people);
+        cap
+    }
+
+    /// take the current services of the people, call it before each slot
+    pub fn update(&mut self, people: &[PersonState]) {
+        self.min_services = people
+            .iter()
+            .filter(|p| self.places.contains(&p.place()))
+            .map(|p| p.different_place_services())
+            .min()
+            .unwrap_or(0);
+    }
+
+    /// true if people of this place may work at other places, within the cap
+    pub fn is_eligible(&self, place: &str) -> bool {
+        self.places.contains(place)
+    }
+
+    /// the fewest different-place services among the people of the eligible places
+    pub fn min_services(&self) -> usize {
+        self.min_services
+    }
+}
+
+/// a rule to sort or filter the candidates for a slot
+///
+/// a rule listed in `[rules] sort` is asked for `sort_key`, a rule listed in `[rules] filter`
+/// is asked whether it `allows` a candidate
+pub trait SchedulingRule: Send + Sync {
+    /// name of the rule as used in the config
+    fn name(&self) -> &str;
+
+    /// smaller keys are preferred, candidates are compared rule by rule
+    fn sort_key(&self, _candidate: &PersonState, _context: &RuleContext) -> i64 {
+        0
+    }
+
+    /// false removes the candidate from the slot
+    fn allows(&self, _candidate: &PersonState, _context: &RuleContext) -> bool {
+        true
+    }
+
+    /// penalty of a candidate in the weighted mode, smaller is preferred and `None` is the best possible
+    ///
+    /// the penalties of all candidates of a slot are normalized to 0..=1, so only their relation matters.
+    /// Defaults to the sort key, rules with extreme sort keys should return a proportional value instead
+    fn penalty(&self, candidate: &PersonState, context: &RuleContext) -> Option<f64> {
+        Some(self.sort_key(candidate, context) as f64)
+    }
+}
+
+/// rules that can be referenced by name from `[rules]`
+#[derive(Clone)]
+pub struct RuleRegistry {
+    rules: HashMap<String, Arc<dyn SchedulingRule>>,
+}
+
+impl Default for RuleRegistry {
+    /// a registry with all built-in rules
+    fn default() -> Self {
+        let mut registry = Self {
+            rules: HashMap::new(),
+        };
+        registry.register(FilterSamePlace);
+        registry.register(FilterDifferentPlaceCap);
+        registry.register(SortByLeastServices);
+        registry.register(SortByLessServicesAtSameWeekday);
+        registry.register(SortByLastService);
+        registry.register(SortByMaxDistanceInGroup);
+        registry.register(SortByOwnPlace);
+        registry.register(SortByDifferentPlaceServices);
+        registry
+    }
+}
+
+impl fmt::Debug for RuleRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.rules.keys().collect();
+        names.sort();
+        f.debug_struct("RuleRegistry")
+            .field("rules", &names)
+            .finish()
+    }
+}
+
+impl RuleRegistry {
+    /// add a rule, a rule with the same name is replaced
+    pub fn register(&mut self, rule: impl SchedulingRule + 'static) {
+        self.rules.insert(rule.name().to_string(), Arc::new(rule));
+    }
+
+    /// the rule with the given name
+    pub fn get(&self, name: &str) -> Option<&dyn SchedulingRule> {
+        self.rules.get(name).map(|rule| rule.as_ref())
+    }
+
+    /// look up all sort and filter rules of the config
+    pub fn resolve(&self, rules: &Rules) -> Result<ResolvedRules<'_>, ScheduleError> {
+        let lookup = |list: &[Rule]| -> Result<Vec<&dyn SchedulingRule>, ScheduleError> {
+            list.iter()
+                .map(|rule| {
+                    self.get(rule.name())
+                        .ok_or_else(|| ScheduleError::UnknownRule(rule.name().to_string()))
+                })
+                .collect()
+        };
+        Ok(ResolvedRules {
+            sort: lookup(&rules.sort)?,
+            filter: lookup(&rules.filter)?,
+            mode: rules.mode,
+            weights: rules.sort.iter().map(|rule| rules.weight(rule)).collect(),
+        })
+    }
+}
+
+/// the rules of a config looked up in a `RuleRegistry`
+pub struct ResolvedRules<'a> {
+    pub sort: Vec<&'a dyn SchedulingRule>,
+    pub filter: Vec<&'a dyn SchedulingRule>,
+    pub mode: SortMode,
+    /// weight of each sort rule, in the order of `sort`
+    pub weights: Vec<f64>,
+}
+
+impl ResolvedRules<'_> {
+    /// true if every filter rule allows the candidate
+    pub fn allows(&self, candidate: &PersonState, context: &RuleContext) -> bool {
+        self.rejected_by(candidate, context).is_none()
+    }
+
+    /// name of the first filter rule that does not allow the candidate
+    pub fn rejected_by(&self, candidate: &PersonState, context: &RuleContext) -> Option<&str> {
+        self.filter
+            .iter()
+            .find(|rule| !rule.allows(candidate, context))
+            .map(|rule| rule.name())
+    }
+
+    /// the sort keys of all sort rules, compared lexicographically
+    pub fn sort_key(&self, candidate: &PersonState, context: &RuleContext) -> Vec<i64> {
+        self.sort
+            .iter()
+            .map(|rule| rule.sort_key(candidate, context))
+            .collect()
+    }
+
+    /// the weighted sum of the normalized penalties of each candidate, smaller is preferred
+    ///
+    /// per rule the penalties are scaled to 0..=1 across the candidates, a missing penalty
+    /// counts as one full range below the smallest penalty
+    pub fn weighted_scores(&self, candidates: &[&PersonState], context: &RuleContext) -> Vec<f64> {
+        let mut scores = vec![0.0; candidates.len()];
+        for (rule, weight) in self.sort.iter().zip(&self.weights) {
+            let penalties: Vec<Option<f64>> = candidates
+                .iter()
+                .map(|candidate| rule.penalty(candidate, context))
+                .collect();
+            let values = penalties.iter().flatten();
+            let min = values.clone().copied().fold(f64::INFINITY, f64::min);
+            let max = values.copied().fold(f64::NEG_INFINITY, f64::max);
+
+            let (low, range) = match (penalties.contains(&None), max - min) {
+                (false, range) => (min, range),
+                (true, range) if range > 0.0 => (min - range, 2.0 * range),
+                (true, _) => (min - 1.0, 1.0),
+            };
+
+            for (score, penalty) in scores.iter_mut().zip(&penalties) {
+                if let Some(penalty) = penalty
+                    && range > 0.0
+                {
+                    *score += weight * (penalty - low) / range;
+                }
+            }
+        }
+        scores
+    }
+
+    /// indices of the candidates from the best to the worst, ties keep the order of the candidates
+    ///
+    /// the first index is the one of `best_candidate`
+    pub fn ranking(&self, candidates: &[&PersonState], context: &RuleContext) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        match self.mode {
+            SortMode::Lexicographic => {
+                order.sort_by_cached_key(|idx| self.sort_key(candidates[*idx], context))
+            }
+            SortMode::Weighted => {
+                let scores = self.weighted_scores(candidates, context);
+                order.sort_by(|a, b| scores[*a].total_cmp(&scores[*b]));
+            }
+        }
+        order
+    }
+
+    /// index of the best candidate, the first one wins a tie
+    pub fn best_candidate(
+        &self,
+        candidates: &[&PersonState],
+        context: &RuleContext,
+    ) -> Option<usize> {
+        match self.mode {
+            SortMode::Lexicographic => candidates
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, candidate)| self.sort_key(candidate, context))
+                .map(|(idx, _)| idx),
+            SortMode::Weighted => self
+                .weighted_scores(candidates, context)
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(idx, _)| idx),
+        }
+    }
+}
+
+/// the implementation of a built-in rule, `None` for custom rules
+pub fn builtin(rule: &Rule) -> Option<&'static dyn SchedulingRule> {
+    match rule {
+        Rule::FilterSamePlace => Some(&FilterSamePlace),
+        Rule::FilterDifferentPlaceCap => Some(&FilterDifferentPlaceCap),
+        Rule::SortByLeastServices => Some(&SortByLeastServices),
+        Rule::SortByLessServicesAtSameWeekday => Some(&SortByLessServicesAtSameWeekday),
+        Rule::SortByLastService => Some(&SortByLastService),
+        Rule::SortByMaxDistanceInGroup => Some(&SortByMaxDistanceInGroup),
+        Rule::SortByOwnPlace => Some(&SortByOwnPlace),
+        Rule::SortByDifferentPlaceServices => Some(&SortByDifferentPlaceServices),
+        Rule::Custom(_) => None,
+    }
+}
+
+/// assign people only to their own place
+pub struct FilterSamePlace;
+
+impl SchedulingRule for FilterSamePlace {
+    fn name(&self) -> &str {
+        Rule::FilterSamePlace.name()
+    }
+
+    fn allows(&self, candidate: &PersonState, context: &RuleContext) -> bool {
+        candidate.place() == context.place
+    }
+}
+
+/// cap how many different-place assignments any one person gets
+///
+/// people from places larger than the smallest group are eligible to do cross-place work.
+/// The cap is the minimum cross-place count among eligible people + 1,
+/// so no one gets a second cross-place assignment until everyone has had at least one.
+pub struct FilterDifferentPlaceCap;
+
+impl SchedulingRule for FilterDifferentPlaceCap {
+    fn name(&self) -> &str {
+        Rule::FilterDifferentPlaceCap.name()
+    }
+
+    fn allows(&self, candidate: &PersonState, context: &RuleContext) -> bool {
+        if candidate.place() == context.place {
+            return true;
+        }
+        let cap = context.place_cap;
+        !cap.is_eligible(&candidate.place())
+            || candidate.different_place_services() < cap.min_services() + 1
+    }
+}
+
+/// everyone works the same amount of hours
+pub struct SortByLeastServices;
+
+impl SchedulingRule for SortByLeastServices {
+    fn name(&self) -> &str {
+        Rule::SortByLeastServices.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, _context: &RuleContext) -> i64 {
+        candidate.total_services() as i64
+    }
+}
+
+/// everyone should work on each weekday the same amount
+pub struct SortByLessServicesAtSameWeekday;
+
+impl SchedulingRule for SortByLessServicesAtSameWeekday {
+    fn name(&self) -> &str {
+        Rule::SortByLessServicesAtSameWeekday.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, context: &RuleContext) -> i64 {
+        candidate.weekday_count(context.date.weekday()) as i64
+    }
+}
+
+/// prefer people who were assigned further back in the past
+pub struct SortByLastService;
+
+impl SchedulingRule for SortByLastService {
+    fn name(&self) -> &str {
+        Rule::SortByLastService.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, _context: &RuleContext) -> i64 {
+        match candidate.last_service() {
+            Some(d) => (d.num_days_from_ce() / 7) as i64, // earlier last service is smaller, however calculate only on a weekly basis to not overule rules like SortByDifferentPlaceServices
+            None => i64::MIN,
+        }
+    }
+
+    fn penalty(&self, candidate: &PersonState, _context: &RuleContext) -> Option<f64> {
+        // weights replace the weekly rounding of the sort key
+        candidate
+            .last_service()
+            .map(|d| d.num_days_from_ce() as f64)
+    }
+}
+
+/// prefer people where a person of the same group worked the longest time ago
+pub struct SortByMaxDistanceInGroup;
+
+impl SchedulingRule for SortByMaxDistanceInGroup {
+    fn name(&self) -> &str {
+        Rule::SortByMaxDistanceInGroup.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, context: &RuleContext) -> i64 {
+        match candidate.group_last_service(context.groups) {
+            Some(d) => d.num_days_from_ce() as i64, // earlier last service is smaller
+            None => i64::MIN,
+        }
+    }
+
+    fn penalty(&self, candidate: &PersonState, context: &RuleContext) -> Option<f64> {
+        candidate
+            .group_last_service(context.groups)
+            .map(|d| d.num_days_from_ce() as f64)
+    }
+}
+
+/// prefer people within the same place
+pub struct SortByOwnPlace;
+
+impl SchedulingRule for SortByOwnPlace {
+    fn name(&self) -> &str {
+        Rule::SortByOwnPlace.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, context: &RuleContext) -> i64 {
+        if candidate.place() == context.place {
+            0
+        } else {
+            1
+        } // smaller = preferred
+    }
+}
+
+/// prefer people who were assigned to a different place less
+pub struct SortByDifferentPlaceServices;
+
+impl SchedulingRule for SortByDifferentPlaceServices {
+    fn name(&self) -> &str {
+        Rule::SortByDifferentPlaceServices.name()
+    }
+
+    fn sort_key(&self, candidate: &PersonState, context: &RuleContext) -> i64 {
+        let different_place_services = candidate.different_place_services();
+        if candidate.place() != context.place {
+            different_place_services as i64
+        } else {
+            i64::MIN / different_place_services.max(1) as i64
+        }
+    }
+
+    fn penalty(&self, candidate: &PersonState, context: &RuleContext) -> Option<f64> {
+        // the own place is always preferred
+        (candidate.place() != context.place).then(|| candidate.different_place_services() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn person(name: &str, place: &str) -> PersonState {
+        PersonState::new(name.to_string(), place.to_string(), 0)
+    }
+
+    static NO_CAP: PlaceCap = PlaceCap {
+        places: BTreeSet::new(),
+        min_services: 0,
+    };
+
+    fn context<'a>(place: &'a str, people: &'a [PersonState]) -> RuleContext<'a> {
+        RuleContext {
+            date: d(2025, 9, 4),
+            place,
+            people,
+            groups: &[],
+            place_cap: &NO_CAP,
+            assignments: &[],
+        }
+    }
+
+    struct OnlyAlice;
+
+    impl SchedulingRule for OnlyAlice {
+        fn name(&self) -> &str {
+            "onlyAlice"
+        }
+
+        fn allows(&self, candidate: &PersonState, _context: &RuleContext) -> bool {
+            candidate.name() == "Alice"
+        }
+    }
+
+    #[test]
+    fn registry_contains_all_builtin_rules() {
+        let registry = RuleRegistry::default();
+        for rule in Rule::BUILTIN {
+            assert_eq!(registry.get(rule.name()).unwrap().name(), rule.name());
+        }
+        assert!(registry.get("onlyAlice").is_none());
+    }
+
+    #[test]
+    fn resolve_reports_unknown_rules() {
+        let mut registry = RuleRegistry::default();
+        let rules = Rules {
+            sort: vec![Rule::SortByLeastServices],
+            filter: vec![Rule::Custom("onlyAlice".to_string())],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            registry.resolve(&rules).err(),
+            Some(ScheduleError::UnknownRule("onlyAlice".to_string()))
+        );
+
+        registry.register(OnlyAlice);
+        let resolved = registry.resolve(&rules).unwrap();
+        let people = [person("Alice", "A"), person("Bob", "A")];
+        let context = context("A", &people);
+        assert!(resolved.allows(&people[0], &context));
+        assert!(!resolved.allows(&people[1], &context));
+        assert_eq!(resolved.rejected_by(&people[0], &context), None);
+        assert_eq!(
+            resolved.rejected_by(&people[1], &context),
+            Some("onlyAlice")
+        );
+        assert_eq!(resolved.sort_key(&people[0], &context), vec![0]);
+    }
+
+    #[test]
+    fn weighted_mode_trades_rules_against_each_other() {
+        // Alice: fewer services, but worked recently; Bob: one more service, long ago
+        let mut alice = person("Alice", "A");
+        alice.register_service(d(2025, 9, 3), "A".to_string());
+        let mut bob = person("Bob", "A");
+        bob.register_service(d(2025, 6, 1), "A".to_string());
+        bob.register_service(d(2025, 6, 5), "A".to_string());
+        let people = [alice, bob];
+        let candidates: Vec<&PersonState> = people.iter().collect();
+        let context = context("A", &people);
+
+        let mut rules = Rules {
+            sort: vec![Rule::SortByLeastServices, Rule::SortByLastService],
+            ..Default::default()
+        };
+        let registry = RuleRegistry::default();
+
+        // lexicographic: the number of services always decides first
+        let resolved = registry.resolve(&rules).unwrap();
+        assert_eq!(resolved.best_candidate(&candidates, &context), Some(0));
+
+        // weighted: recency counts more than the number of services
+        rules.mode = SortMode::Weighted;
+        rules.weights.insert("sortByLastService".to_string(), 2.0);
+        let resolved = registry.resolve(&rules).unwrap();
+        assert_eq!(
+            resolved.weighted_scores(&candidates, &context),
+            vec![2.0, 1.0]
+        );
+        assert_eq!(resolved.best_candidate(&candidates, &context), Some(1));
+
+        assert_eq!(resolved.ranking(&candidates, &context), vec![1, 0]);
+
+        // equal weights are a tie, the first candidate wins
+        rules.weights.clear();
+        let resolved = registry.resolve(&rules).unwrap();
+        assert_eq!(resolved.best_candidate(&candidates, &context), Some(0));
+        assert_eq!(resolved.ranking(&candidates, &context), vec![0, 1]);
+    }
+
+    #[test]
+    fn weighted_mode_treats_missing_penalty_as_best() {
+        let mut alice = person("Alice", "A");
+        alice.register_service(d(2025, 9, 1), "A".to_string());
+        let bob = person("Bob", "A");
+        let people = [alice, bob];
+        let candidates: Vec<&PersonState> = people.iter().collect();
+        let context = context("A", &people);
+
+        let rules = Rules {
+            sort: vec![Rule::SortByLastService],
+            mode: SortMode::Weighted,
+            ..Default::default()
+        };
+        let registry = RuleRegistry::default();
+        let resolved = registry.resolve(&rules).unwrap();
+
+        assert_eq!(
+            resolved.weighted_scores(&candidates, &context),
+            vec![1.0, 0.0]
+        );
+        assert_eq!(resolved.best_candidate(&candidates, &context), Some(1));
+        assert_eq!(SortByLastService.penalty(&people[1], &context), None);
+    }
+
+    #[test]
+    fn filter_same_place_allows_own_place_only() {
+        let people = [person("Alice", "A")];
+        assert!(FilterSamePlace.allows(&people[0], &context("A", &people)));
+        assert!(!FilterSamePlace.allows(&people[0], &context("B", &people)));
+    }
+
+    #[test]
+    fn filter_different_place_cap_spreads_cross_place_work() {
+        // place A is larger than B, so people of A help out at B
+        let mut people = vec![
+            person("Alice", "A"),
+            person("Anna", "A"),
+            person("Bob", "B"),
+        ];
+        people[0].register_service(d(2025, 9, 1), "B".to_string());
+
+        let cap = PlaceCap::new(&people);
+        assert!(cap.is_eligible("A"));
+        assert!(!cap.is_eligible("B"));
+        assert_eq!(cap.min_services(), 0);
+
+        let at_b = RuleContext {
+            place_cap: &cap,
+            ..context("B", &people)
+        };
+        assert!(!FilterDifferentPlaceCap.allows(&people[0], &at_b));
+        assert!(FilterDifferentPlaceCap.allows(&people[1], &at_b));
+
+        // people of the smallest place are never capped
+        let at_a = RuleContext {
+            place_cap: &cap,
+            ..context("A", &people)
+        };
+        assert!(FilterDifferentPlaceCap.allows(&people[2], &at_a));
+
+        // once everyone of A helped out, the cap rises
+        people[1].register_service(d(2025, 9, 2), "B".to_string());
+        let mut cap = cap;
+        cap.update(&people);
+        assert_eq!(cap.min_services(), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! why a person was chosen for a slot
+//!
+//! with `ScheduleOptions::explain` the generator records every decision: the candidates ranked
+//! by the sort rules with the key of each rule, and the people left out before the ranking
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::{Assignment, Exclusion};
+
+/// the key of one sort rule for a candidate, smaller is preferred
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleKey {
+    pub rule: String,
+    pub key: i64,
+}
+
+/// a person that was allowed for a slot
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    pub person: String,
+    /// the key of each sort rule, in the order of `[rules] sort`
+    pub keys: Vec<RuleKey>,
+    /// the weighted score in weighted mode, smaller is preferred
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// the decision for one assignment
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlotTrace {
+    pub date: NaiveDate,
+    pub place: String,
+    /// the chosen person, empty for an unfilled slot
+    pub person: String,
+    /// true for a fixed assignment of the config, nobody else was considered
+    pub fixed: bool,
+    /// the allowed people from the best to the worst, the first one was chosen
+    pub candidates: Vec<Candidate>,
+    /// the people left out before the ranking and why
+    pub excluded: Vec<(String, Exclusion)>,
+}
+
+impl SlotTrace {
+    /// a fixed assignment of the config
+    pub fn fixed(date: NaiveDate, place: String, person: String) -> Self {
+        Self {
+            date,
+            place,
+            person,
+            fixed: true,
+            candidates: vec![],
+            excluded: vec![],
+        }
+    }
+
+    /// a readable explanation, one line per candidate and excluded person
+    pub fn lines(&self) -> Vec<String> {
+        let person = if self.person.is_empty() {
+            "nobody"
+        } else {
+            &self.person
+        };
+        let mut lines = vec![format!("{} {}: {person}", self.date, self.place)];
+        if self.fixed {
+            lines.push("fixed in the config".to_string());
+        }
+
+        for (rank, candidate) in self.candidates.iter().enumerate() {
+            let mut keys: Vec<String> = candidate
+                .keys
+                .iter()
+                .map(|key| format!("{} {}", key.rule, key.key))
+                .collect();
+            if let Some(score) = candidate.score {
+                keys.push(format!("score {score:.3}"));
+            }
+            lines.push(format!(
+                "{}. {}: {}",
+                rank + 1,
+                candidate.person,
+                keys.join(", ")
+            ));
+        }
+
+        for (person, exclusion) in &self.excluded {
+            lines.push(format!("left out {person}: {exclusion}"));
+        }
+
+        lines
+    }
+}
+
+/// drop the decisions of slots that no longer hold the chosen person
+///
+/// call it after every step that changes assignments, e.g. `optimize_schedule`, so the trace
+/// only explains what the schedule shows
+pub fn retain_assigned(trace: &mut Vec<SlotTrace>, assignments: &[Assignment]) {
+    let mut slots: HashMap<(NaiveDate, String, String), usize> = HashMap::new();
+    for a in assignments {
+        *slots
+            .entry((a.date, a.place.clone(), a.base_person.clone()))
+            .or_default() += 1;
+    }
+    trace.retain(
+        |t| match slots.get_mut(&(t.date, t.place.clone(), t.person.clone())) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        },
+    );
+}
+
+/// the trace as pretty printed JSON
+pub fn trace_to_json(trace: &[SlotTrace]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace() -> SlotTrace {
+        SlotTrace {
+            date: NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+            place: "Place A".to_string(),
+            person: "Alice Maier".to_string(),
+            fixed: false,
+            candidates: vec![
+                Candidate {
+                    person: "Alice Maier".to_string(),
+                    keys: vec![RuleKey {
+                        rule: "sortByLeastServices".to_string(),
+                        key: 1,
+                    }],
+                    score: None,
+                },
+                Candidate {
+                    person: "Charlie Doe".to_string(),
+                    keys: vec![RuleKey {
+                        rule: "sortByLeastServices".to_string(),
+                        key: 2,
+                    }],
+                    score: None,
+                },
+            ],
+            excluded: vec![("Bob Maier".to_string(), Exclusion::Unavailable)],
+        }
+    }
+
+    #[test]
+    fn lines_rank_the_candidates() {
+        assert_eq!(
+            trace().lines(),
+            vec![
+                "2025-09-04 Place A: Alice Maier",
+                "1. Alice Maier: sortByLeastServices 1",
+                "2. Charlie Doe: sortByLeastServices 2",
+                "left out Bob Maier: unavailable",
+            ]
+        );
+
+        let fixed = SlotTrace::fixed(
+            NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+            "Place A".to_string(),
+            "Bob Maier".to_string(),
+        );
+        assert_eq!(
+            fixed.lines(),
+            vec!["2025-09-04 Place A: Bob Maier", "fixed in the config"]
+        );
+    }
+
+    #[test]
+    fn retain_assigned_drops_changed_slots() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 4).unwrap();
+        let moved = SlotTrace::fixed(date, "Place B".to_string(), "Bob Maier".to_string());
+        let mut trace = vec![trace(), moved];
+        let assignments = vec![
+            Assignment {
+                date,
+                place: "Place A".to_string(),
+                person: "Alice Maier 🪴".to_string(),
+                base_person: "Alice Maier".to_string(),
+            },
+            Assignment {
+                date,
+                place: "Place B".to_string(),
+                person: "Charlie Doe".to_string(),
+                base_person: "Charlie Doe".to_string(),
+            },
+        ];
+
+        retain_assigned(&mut trace, &assignments);
+
+        assert_eq!(trace, vec![self::trace()]);
+    }
+
+    #[test]
+    fn trace_as_json() {
+        let json = trace_to_json(&[trace()]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value[0]["date"], "2025-09-04");
+        assert_eq!(value[0]["person"], "Alice Maier");
+        assert_eq!(value[0]["candidates"][1]["keys"][0]["key"], 2);
+        assert!(value[0]["candidates"][0].get("score").is_none());
+        assert_eq!(
+            value[0]["excluded"][0],
+            serde_json::json!(["Bob Maier", "unavailable"])
+        );
+    }
+}
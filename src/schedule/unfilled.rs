@@ -0,0 +1,113 @@
+//! slots nobody could be assigned to, and why
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Serialize, Serializer};
+
+/// why a person was left out for a slot
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Exclusion {
+    /// the person is unavailable on the date
+    Unavailable,
+    /// the person already fills another slot on the date
+    AlreadyAssigned,
+    /// the filter rule with this name does not allow the person
+    Filter(String),
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exclusion::Unavailable => write!(f, "unavailable"),
+            Exclusion::AlreadyAssigned => write!(f, "already assigned"),
+            Exclusion::Filter(rule) => write!(f, "{rule}"),
+        }
+    }
+}
+
+impl Serialize for Exclusion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// a slot of the schedule that stays empty because every person was left out
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnfilledSlot {
+    pub date: NaiveDate,
+    pub place: String,
+    /// every person of the config with the reason they were left out
+    pub excluded: Vec<(String, Exclusion)>,
+}
+
+impl UnfilledSlot {
+    /// the reason without date and place, e.g. "filterSamePlace: Alice Maier, Bob Maier"
+    pub fn reason(&self) -> String {
+        if self.excluded.is_empty() {
+            return "nobody is configured".to_string();
+        }
+
+        let mut by_reason: BTreeMap<&Exclusion, Vec<&str>> = BTreeMap::new();
+        for (person, exclusion) in &self.excluded {
+            by_reason.entry(exclusion).or_default().push(person);
+        }
+        by_reason
+            .into_iter()
+            .map(|(exclusion, mut people)| {
+                people.sort();
+                format!("{exclusion}: {}", people.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl fmt::Display for UnfilledSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.date, self.place, self.reason())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reason_groups_people_by_exclusion() {
+        let slot = UnfilledSlot {
+            date: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            place: "Place B".to_string(),
+            excluded: vec![
+                (
+                    "Bob Maier".to_string(),
+                    Exclusion::Filter("filterSamePlace".to_string()),
+                ),
+                ("Charlie Doe".to_string(), Exclusion::Unavailable),
+                (
+                    "Alice Maier".to_string(),
+                    Exclusion::Filter("filterSamePlace".to_string()),
+                ),
+                ("Dave Doe".to_string(), Exclusion::AlreadyAssigned),
+            ],
+        };
+
+        assert_eq!(
+            slot.to_string(),
+            "2025-09-01 Place B: unavailable: Charlie Doe; already assigned: Dave Doe; \
+             filterSamePlace: Alice Maier, Bob Maier"
+        );
+    }
+
+    #[test]
+    fn reason_without_people() {
+        let slot = UnfilledSlot {
+            date: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            place: "Place B".to_string(),
+            excluded: vec![],
+        };
+
+        assert_eq!(slot.reason(), "nobody is configured");
+    }
+}
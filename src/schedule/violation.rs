@@ -0,0 +1,362 @@
+//! check manual changes of a schedule against the config
+//!
+//! a change puts other people into some slots, the moved people have to be available, must not
+//! serve twice on a date and have to pass the filter rules at their new slot
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use super::rules::ResolvedRules;
+use super::{Assignment, PlaceCap, RuleContext, ScheduleError, ScheduleOptions};
+use crate::config::Config;
+
+/// a rule of the config a change of the schedule breaks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// the person is unavailable on the date
+    Unavailable { person: String, date: NaiveDate },
+    /// the person serves in another slot of the date
+    DoubleBooking { person: String, date: NaiveDate },
+    /// a filter rule rejects the person for the slot
+    Filter {
+        person: String,
+        date: NaiveDate,
+        place: String,
+        rule: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Unavailable { person, date } => {
+                write!(f, "{person} is unavailable on {date}")
+            }
+            Violation::DoubleBooking { person, date } => {
+                write!(f, "{person} is already on duty on {date}")
+            }
+            Violation::Filter {
+                person,
+                date,
+                place,
+                rule,
+            } => write!(f, "{rule} does not allow {person} at {place} on {date}"),
+        }
+    }
+}
+
+/// the violations of swapping the people of two assignments, empty if the swap keeps the rules
+///
+/// one of the assignments may be an unfilled slot, only the moved people are checked. The history
+/// of `options` counts for rules that look at earlier services. An index outside of `assignments`
+/// is an error
+pub fn swap_violations(
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &[Assignment],
+    first: usize,
+    second: usize,
+) -> Result<Vec<Violation>, ScheduleError> {
+    let rules = options.rules.resolve(&config.rules)?;
+    let person = |slot: usize| {
+        assignments
+            .get(slot)
+            .map(|a| a.base_person.clone())
+            .ok_or(ScheduleError::UnknownSlot(slot))
+    };
+    let change = [(first, person(second)?), (second, person(first)?)];
+    Ok(change_violations(
+        &change,
+        assignments,
+        config,
+        options,
+        &rules,
+    ))
+}
+
+/// the violations of putting `person` into the slot of an assignment, empty if the replacement
+/// keeps the rules
+///
+/// like `swap_violations`, an index outside of `assignments` is an error
+pub fn replace_violations(
+    config: &Config,
+    options: &ScheduleOptions,
+    assignments: &[Assignment],
+    slot: usize,
+    person: &str,
+) -> Result<Vec<Violation>, ScheduleError> {
+    let rules = options.rules.resolve(&config.rules)?;
+    if slot >= assignments.len() {
+        return Err(ScheduleError::UnknownSlot(slot));
+    }
+    Ok(change_violations(
+        &[(slot, person.to_string())],
+        assignments,
+        config,
+        options,
+        &rules,
+    ))
+}
+
+/// the violations of giving each slot of `change` a new person, an empty person leaves the slot unfilled
+pub(crate) fn change_violations(
+    change: &[(usize, String)],
+    assignments: &[Assignment],
+    config: &Config,
+    options: &ScheduleOptions,
+    rules: &ResolvedRules,
+) -> Vec<Violation> {
+    let changed: HashSet<usize> = change.iter().map(|(idx, _)| *idx).collect();
+
+    // the other slots as they stay, the filters judge the moved people against them
+    let rest: Vec<Assignment> = assignments
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !changed.contains(idx))
+        .map(|(_, a)| a.clone())
+        .collect();
+    let states = super::optimize::people_with_history(config, options, &rest);
+    let groups = super::group_states(&states);
+    let place_cap = PlaceCap::new(&states);
+    let context = RuleContext {
+        date: NaiveDate::MIN,
+        place: "",
+        people: &states,
+        groups: &groups,
+        place_cap: &place_cap,
+        assignments,
+    };
+    violations_in(change, &context, rules)
+}
+
+/// like `change_violations`, the people of `context` have the services of all slots except the
+/// changed ones and its assignments are the schedule before the change
+pub(crate) fn violations_in(
+    change: &[(usize, String)],
+    context: &RuleContext,
+    rules: &ResolvedRules,
+) -> Vec<Violation> {
+    let assignments = context.assignments;
+    let changed: HashSet<usize> = change.iter().map(|(idx, _)| *idx).collect();
+
+    let mut violations = Vec::new();
+    for (idx, person) in change.iter().filter(|(_, person)| !person.is_empty()) {
+        let slot = &assignments[*idx];
+        let state = context.people.iter().find(|p| p.name() == *person);
+        if state.is_some_and(|p| !p.is_available(slot.date)) {
+            violations.push(Violation::Unavailable {
+                person: person.clone(),
+                date: slot.date,
+            });
+        }
+        let booked_elsewhere = assignments.iter().enumerate().any(|(other, a)| {
+            !changed.contains(&other) && a.date == slot.date && a.base_person == *person
+        });
+        let booked_twice = change
+            .iter()
+            .any(|(other, p)| other != idx && p == person && assignments[*other].date == slot.date);
+        if booked_elsewhere || booked_twice {
+            violations.push(Violation::DoubleBooking {
+                person: person.clone(),
+                date: slot.date,
+            });
+        }
+        let context = RuleContext {
+            date: slot.date,
+            place: &slot.place,
+            ..*context
+        };
+        if let Some(rule) = state.and_then(|p| rules.rejected_by(p, &context)) {
+            violations.push(Violation::Filter {
+                person: person.clone(),
+                date: slot.date,
+                place: slot.place.clone(),
+                rule: rule.to_string(),
+            });
+        }
+    }
+    violations.dedup();
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Rule, test_config};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap()
+    }
+
+    /// people stay at their own place and Emily is unavailable on the 11th
+    fn config() -> Config {
+        let mut config = test_config();
+        config.rules.filter = vec![Rule::FilterSamePlace];
+        config.group[0].members[0].unavailable.dates = vec![date(11)];
+        config
+    }
+
+    fn assignment(day: u32, place: &str, person: &str) -> Assignment {
+        Assignment {
+            date: date(day),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        }
+    }
+
+    fn violations(assignments: &[Assignment], first: usize, second: usize) -> Vec<String> {
+        swap_violations(
+            &config(),
+            &ScheduleOptions::default(),
+            assignments,
+            first,
+            second,
+        )
+        .unwrap()
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn swap_within_the_rules_has_no_violations() {
+        let assignments = vec![
+            assignment(4, "Office", "Emily Smith"),
+            assignment(25, "Office", "William Smith"),
+        ];
+
+        assert!(violations(&assignments, 0, 1).is_empty());
+    }
+
+    #[test]
+    fn swap_reports_every_broken_rule() {
+        let assignments = vec![
+            assignment(4, "Office", "Emily Smith"),
+            assignment(4, "Transport", "Olivia Jones"),
+            assignment(11, "Office", "William Smith"),
+            assignment(11, "Transport", "Emily Smith"),
+        ];
+
+        // Olivia moves to the Office and Emily to Transport
+        assert_eq!(
+            violations(&assignments, 0, 1),
+            vec![
+                "filterSamePlace does not allow Olivia Jones at Office on 2025-09-04",
+                "filterSamePlace does not allow Emily Smith at Transport on 2025-09-04",
+            ]
+        );
+        // Emily is unavailable on the 11th and would serve twice that day
+        assert_eq!(
+            violations(&assignments, 0, 2),
+            vec![
+                "Emily Smith is unavailable on 2025-09-11",
+                "Emily Smith is already on duty on 2025-09-11",
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_into_an_unfilled_slot_checks_only_the_moved_person() {
+        let assignments = vec![
+            assignment(4, "Office", "William Smith"),
+            Assignment::unfilled(date(11), "Transport".to_string()),
+        ];
+
+        assert_eq!(
+            violations(&assignments, 0, 1),
+            vec!["filterSamePlace does not allow William Smith at Transport on 2025-09-11"]
+        );
+    }
+
+    #[test]
+    fn swap_counts_the_services_of_the_history() {
+        let mut config = test_config();
+        config.rules.filter = vec![Rule::FilterDifferentPlaceCap];
+        let assignments = vec![
+            assignment(4, "Office", "Emily Smith"),
+            assignment(4, "Transport", "Olivia Jones"),
+        ];
+        let options = ScheduleOptions {
+            history: vec![Assignment {
+                date: NaiveDate::from_ymd_opt(2025, 8, 28).unwrap(),
+                ..assignment(4, "Office", "Olivia Jones")
+            }],
+            ..Default::default()
+        };
+
+        let without_history =
+            swap_violations(&config, &ScheduleOptions::default(), &assignments, 0, 1).unwrap();
+        let with_history = swap_violations(&config, &options, &assignments, 0, 1).unwrap();
+
+        assert!(without_history.is_empty());
+        // Olivia already served at another place in August, Daniel and Sophie did not
+        assert_eq!(
+            with_history,
+            vec![Violation::Filter {
+                person: "Olivia Jones".to_string(),
+                date: date(4),
+                place: "Office".to_string(),
+                rule: "filterDifferentPlaceCap".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn swap_with_an_unknown_slot_is_an_error() {
+        let assignments = vec![assignment(4, "Office", "Emily Smith")];
+
+        let error = swap_violations(&config(), &ScheduleOptions::default(), &assignments, 0, 3)
+            .unwrap_err();
+
+        assert_eq!(error, ScheduleError::UnknownSlot(3));
+    }
+
+    #[test]
+    fn replacement_checks_the_new_person() {
+        let assignments = vec![
+            assignment(4, "Office", "Emily Smith"),
+            assignment(11, "Office", "William Smith"),
+            assignment(11, "Transport", "Olivia Jones"),
+        ];
+        let replace = |slot: usize, person: &str| -> Vec<String> {
+            replace_violations(
+                &config(),
+                &ScheduleOptions::default(),
+                &assignments,
+                slot,
+                person,
+            )
+            .unwrap()
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+        };
+
+        assert_eq!(
+            replace(1, "Emily Smith"),
+            vec!["Emily Smith is unavailable on 2025-09-11"]
+        );
+        assert_eq!(
+            replace(2, "William Smith"),
+            vec![
+                "William Smith is already on duty on 2025-09-11",
+                "filterSamePlace does not allow William Smith at Transport on 2025-09-11",
+            ]
+        );
+        assert!(replace(0, "William Smith").is_empty());
+        assert_eq!(
+            replace_violations(
+                &config(),
+                &ScheduleOptions::default(),
+                &assignments,
+                3,
+                "Emily Smith"
+            )
+            .unwrap_err(),
+            ScheduleError::UnknownSlot(3)
+        );
+    }
+}
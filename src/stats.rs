@@ -0,0 +1,399 @@
+//! fairness metrics of a schedule and the services of each person
+//!
+//! the metrics only depend on the assignments and the config, so schedules created with different
+//! seeds, rules or optimisation budgets can be compared with each other
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use chrono::{NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::extra_tasks::extra_tasks_of;
+use crate::schedule::{Assignment, people_from_assignments};
+
+/// fairness metrics of a schedule, smaller spreads and less clustering are fairer
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleStats {
+    /// difference between the most and the fewest services of a person
+    pub total_spread: usize,
+    /// difference between the most and the fewest services of a person on each weekday
+    pub weekday_spread: Vec<(Weekday, usize)>,
+    /// difference between the most and the fewest services among the people of each place
+    pub place_spread: Vec<(String, usize)>,
+    /// difference between the most and the fewest services of a person at another place
+    pub cross_place_spread: usize,
+    /// fewest days between two services of the same person
+    pub min_gap: Option<i64>,
+    /// average days between two services of the same person
+    pub mean_gap: Option<f64>,
+    /// services of a group on the same or the next schedule date as another service of the group
+    pub group_clustering: usize,
+}
+
+impl ScheduleStats {
+    /// compute the metrics of the assignments, every person of the config is taken into account
+    pub fn new(config: &Config, assignments: &[Assignment]) -> Self {
+        let people = people_from_assignments(config, assignments);
+
+        let weekday_spread = config
+            .dates
+            .weekdays
+            .iter()
+            .map(|weekday| {
+                let counts = people.iter().map(|p| p.weekday_count(*weekday));
+                (*weekday, spread(counts))
+            })
+            .collect();
+
+        let place_spread = config
+            .places
+            .places
+            .iter()
+            .map(|place| {
+                let counts = people
+                    .iter()
+                    .filter(|p| &p.place() == place)
+                    .map(|p| p.total_services());
+                (place.clone(), spread(counts))
+            })
+            .collect();
+
+        let gaps = gaps(&services_by_person(assignments));
+
+        Self {
+            total_spread: spread(people.iter().map(|p| p.total_services())),
+            weekday_spread,
+            place_spread,
+            cross_place_spread: spread(people.iter().map(|p| p.different_place_services())),
+            min_gap: gaps.iter().copied().min(),
+            mean_gap: (!gaps.is_empty())
+                .then(|| gaps.iter().sum::<i64>() as f64 / gaps.len() as f64),
+            group_clustering: group_clustering(config, assignments),
+        }
+    }
+
+    /// a single number to compare schedules, the sum of all spreads and the clustering
+    ///
+    /// smaller is fairer, the gaps are left out as they depend on the number of people
+    pub fn score(&self) -> usize {
+        self.total_spread
+            + self.weekday_spread.iter().map(|(_, s)| s).sum::<usize>()
+            + self.place_spread.iter().map(|(_, s)| s).sum::<usize>()
+            + self.cross_place_spread
+            + self.group_clustering
+    }
+
+    /// one line per metric, e.g. "total spread: 2"
+    pub fn lines(&self) -> Vec<String> {
+        let list = |values: Vec<String>| {
+            if values.is_empty() {
+                "-".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+        let gap = match (self.min_gap, self.mean_gap) {
+            (Some(min), Some(mean)) => format!("min {min} days, mean {mean:.1} days"),
+            _ => "-".to_string(),
+        };
+
+        vec![
+            format!("total spread: {}", self.total_spread),
+            format!(
+                "weekday spread: {}",
+                list(
+                    self.weekday_spread
+                        .iter()
+                        .map(|(day, s)| format!("{day} {s}"))
+                        .collect()
+                )
+            ),
+            format!(
+                "place spread: {}",
+                list(
+                    self.place_spread
+                        .iter()
+                        .map(|(place, s)| format!("{place} {s}"))
+                        .collect()
+                )
+            ),
+            format!("cross-place spread: {}", self.cross_place_spread),
+            format!("gap between services: {gap}"),
+            format!("group clustering: {}", self.group_clustering),
+            format!("score: {}", self.score()),
+        ]
+    }
+}
+
+impl fmt::Display for ScheduleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+/// the services of one person, the counts list every weekday, place and extra task of the config
+/// in the order of the config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonSummary {
+    /// full name, e.g. "Emily Smith"
+    pub person: String,
+    pub group: String,
+    /// the own place of the group
+    pub place: String,
+    pub total: usize,
+    pub weekdays: Vec<(Weekday, usize)>,
+    pub places: Vec<(String, usize)>,
+    /// services at another place than the own one
+    pub different_place: usize,
+    pub extra_tasks: Vec<(String, usize)>,
+}
+
+/// a summary for every person of the config, in the order of the config
+pub fn person_summaries(config: &Config, assignments: &[Assignment]) -> Vec<PersonSummary> {
+    people_from_assignments(config, assignments)
+        .iter()
+        .map(|person| {
+            let name = person.name();
+            let place_counts = person.place_counts();
+            let tasks: Vec<String> = assignments
+                .iter()
+                .filter(|a| a.base_person == name)
+                .flat_map(extra_tasks_of)
+                .collect();
+            PersonSummary {
+                group: config.group[person.group()].name.clone(),
+                place: person.place(),
+                total: person.total_services(),
+                weekdays: config
+                    .dates
+                    .weekdays
+                    .iter()
+                    .map(|weekday| (*weekday, person.weekday_count(*weekday)))
+                    .collect(),
+                places: config
+                    .places
+                    .places
+                    .iter()
+                    .map(|place| (place.clone(), place_counts.get(place).copied().unwrap_or(0)))
+                    .collect(),
+                different_place: person.different_place_services(),
+                extra_tasks: config
+                    .extra_task
+                    .iter()
+                    .flatten()
+                    .map(|task| {
+                        let count = tasks.iter().filter(|t| **t == task.name).count();
+                        (task.name.clone(), count)
+                    })
+                    .collect(),
+                person: name,
+            }
+        })
+        .collect()
+}
+
+/// difference between the largest and the smallest value, 0 for no values
+fn spread(values: impl Iterator<Item = usize>) -> usize {
+    let values: Vec<usize> = values.collect();
+    match (values.iter().max(), values.iter().min()) {
+        (Some(max), Some(min)) => max - min,
+        _ => 0,
+    }
+}
+
+/// the sorted dates of the services of each person
+fn services_by_person(assignments: &[Assignment]) -> HashMap<&str, Vec<NaiveDate>> {
+    let mut services: HashMap<&str, Vec<NaiveDate>> = HashMap::new();
+    for a in assignments.iter().filter(|a| !a.is_unfilled()) {
+        services.entry(&a.base_person).or_default().push(a.date);
+    }
+    for dates in services.values_mut() {
+        dates.sort();
+    }
+    services
+}
+
+/// days between consecutive services of each person
+fn gaps(services: &HashMap<&str, Vec<NaiveDate>>) -> Vec<i64> {
+    services
+        .values()
+        .flat_map(|dates| dates.windows(2).map(|w| (w[1] - w[0]).num_days()))
+        .collect()
+}
+
+/// count services of a group that follow another service of the same group on the same or the next schedule date
+fn group_clustering(config: &Config, assignments: &[Assignment]) -> usize {
+    let dates: Vec<NaiveDate> = assignments
+        .iter()
+        .map(|a| a.date)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let index: HashMap<NaiveDate, usize> = dates.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+
+    config
+        .group
+        .iter()
+        .map(|group| {
+            let members: Vec<String> = group
+                .members
+                .iter()
+                .map(|m| format!("{} {}", m.name, group.name))
+                .collect();
+            let mut services: Vec<usize> = assignments
+                .iter()
+                .filter(|a| members.contains(&a.base_person))
+                .map(|a| index[&a.date])
+                .collect();
+            services.sort();
+            services.windows(2).filter(|w| w[1] - w[0] <= 1).count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    /// the Smith and Jones families of `test/schedule.toml` on Mondays and Thursdays
+    fn config() -> Config {
+        let mut config = test_config();
+        config.dates.weekdays = vec![Weekday::Mon, Weekday::Thu];
+        config.group.truncate(2);
+        config
+    }
+
+    fn assignment(day: u32, place: &str, person: &str) -> Assignment {
+        Assignment {
+            date: NaiveDate::from_ymd_opt(2025, 9, day).unwrap(),
+            place: place.to_string(),
+            person: person.to_string(),
+            base_person: person.to_string(),
+        }
+    }
+
+    #[test]
+    fn stats_of_a_schedule() {
+        let assignments = vec![
+            assignment(1, "Office", "Emily Smith"),
+            assignment(1, "Transport", "Olivia Jones"),
+            assignment(4, "Office", "William Smith"),
+            assignment(4, "Transport", "Olivia Jones"),
+            assignment(8, "Office", "Emily Smith"),
+            assignment(8, "Transport", "William Smith"),
+        ];
+        let stats = ScheduleStats::new(&config(), &assignments);
+
+        assert_eq!(stats.total_spread, 0);
+        assert_eq!(
+            stats.weekday_spread,
+            vec![(Weekday::Mon, 1), (Weekday::Thu, 1)]
+        );
+        assert_eq!(
+            stats.place_spread,
+            vec![("Office".to_string(), 0), ("Transport".to_string(), 0)]
+        );
+        assert_eq!(stats.cross_place_spread, 1);
+        assert_eq!(stats.min_gap, Some(3));
+        // Emily 7 days, William 4 days, Olivia 3 days
+        assert_eq!(stats.mean_gap, Some(14.0 / 3.0));
+        // Smith on 1, 4, 8, 8 and Jones on 1, 4: every service follows one on the same or previous date
+        assert_eq!(stats.group_clustering, 4);
+        assert_eq!(stats.score(), 7);
+    }
+
+    #[test]
+    fn stats_of_an_empty_schedule() {
+        let stats = ScheduleStats::new(&config(), &[]);
+
+        assert_eq!(stats.score(), 0);
+        assert_eq!(stats.min_gap, None);
+        assert_eq!(stats.mean_gap, None);
+        assert!(
+            stats
+                .lines()
+                .contains(&"gap between services: -".to_string())
+        );
+    }
+
+    #[test]
+    fn extra_task_icons_do_not_split_a_person() {
+        let mut with_icon = assignment(4, "Office", "Emily Smith");
+        with_icon.person = "Emily Smith 🪴".to_string();
+        let assignments = vec![assignment(1, "Office", "Emily Smith"), with_icon];
+
+        let stats = ScheduleStats::new(&config(), &assignments);
+
+        assert_eq!(stats.min_gap, Some(3));
+        assert_eq!(stats.group_clustering, 1);
+    }
+
+    #[test]
+    fn unfilled_slots_are_no_services() {
+        let unfilled = Assignment::unfilled(
+            NaiveDate::from_ymd_opt(2025, 9, 4).unwrap(),
+            "Transport".to_string(),
+        );
+        let assignments = vec![assignment(1, "Office", "Emily Smith"), unfilled];
+
+        let stats = ScheduleStats::new(&config(), &assignments);
+
+        assert_eq!(stats, ScheduleStats::new(&config(), &assignments[..1]));
+    }
+
+    #[test]
+    fn summaries_list_every_configured_column() {
+        let mut config = config();
+        config.extra_task = Some(vec![crate::config::ExtraTask {
+            name: "🪴".to_string(),
+            groups: vec!["Smith".to_string()],
+        }]);
+        let mut with_icon = assignment(4, "Office", "Emily Smith");
+        with_icon.person = "Emily Smith 🪴".to_string();
+        let assignments = vec![
+            assignment(1, "Transport", "Emily Smith"),
+            with_icon,
+            assignment(4, "Transport", "Olivia Jones"),
+        ];
+
+        let summaries = person_summaries(&config, &assignments);
+
+        let names: Vec<&str> = summaries.iter().map(|s| s.person.as_str()).collect();
+        assert_eq!(names, vec!["Emily Smith", "William Smith", "Olivia Jones"]);
+        assert_eq!(
+            summaries[0],
+            PersonSummary {
+                person: "Emily Smith".to_string(),
+                group: "Smith".to_string(),
+                place: "Office".to_string(),
+                total: 2,
+                weekdays: vec![(Weekday::Mon, 1), (Weekday::Thu, 1)],
+                places: vec![("Office".to_string(), 1), ("Transport".to_string(), 1)],
+                different_place: 1,
+                extra_tasks: vec![("🪴".to_string(), 1)],
+            }
+        );
+        assert_eq!(summaries[1].total, 0);
+        assert_eq!(summaries[1].extra_tasks, vec![("🪴".to_string(), 0)]);
+    }
+
+    #[test]
+    fn display_lists_every_metric() {
+        let stats = ScheduleStats::new(&config(), &[assignment(1, "Office", "Emily Smith")]);
+
+        assert_eq!(
+            stats.to_string(),
+            "\
+total spread: 1
+weekday spread: Mon 1, Thu 0
+place spread: Office 1, Transport 0
+cross-place spread: 0
+gap between services: -
+group clustering: 0
+score: 3"
+        );
+    }
+}